@@ -0,0 +1,194 @@
+//! Assembly programs, whose body is machine code behind an `AsmPrgm` token instead of TI-BASIC
+
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::models::Model;
+use crate::calculator::tokens::bytes::{
+    ASM_84CE_COMPILED, ASM_84CE_PRGM, ASM_84C_COMPILED, ASM_84C_PRGM, ASM_COMPILED, ASM_PRGM,
+    NEWLINE,
+};
+use serde::Serialize;
+use std::fmt;
+
+/// The hex digits of a hex program are tokens, `0` to `9` and `A` to `F`
+const HEX_DIGITS: &[u8] = b"0123456789ABCDEF";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssemblyForm {
+    /// Typed on the calculator as hex digits, which the calculator reads when the program runs
+    Hex,
+    /// The machine code itself, as assemblers and the calculator's own compiling write it
+    Compiled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AssemblyTarget {
+    /// The Z80 of the TI-83+ and TI-84+
+    Z80,
+    /// The Z80 of the TI-84+CSE, which has its own headers
+    Z80Color,
+    /// The eZ80 of the TI-84+CE and TI-83 Premium CE
+    EZ80,
+}
+
+impl AssemblyTarget {
+    /// `userMem`, where the OS copies a program to run it and so the address its code starts at
+    pub fn user_mem(&self) -> u32 {
+        match self {
+            AssemblyTarget::Z80 => 0x9D95,
+            AssemblyTarget::Z80Color => 0xA60B,
+            AssemblyTarget::EZ80 => 0xD1A881,
+        }
+    }
+
+    /// The calculators before the TI-83+ have no assembly tokens
+    pub fn for_model(model: &Model) -> Result<AssemblyTarget, TiToolsError> {
+        match model {
+            Model::TI82 | Model::TI83 | Model::TI82ST | Model::TI82STFR | Model::TI76FR => {
+                Err(TiToolsError::AssemblyUnsupported(model.to_string()))
+            }
+            Model::TI84PlusCSE => Ok(AssemblyTarget::Z80Color),
+            Model::TI84PlusCE
+            | Model::TI84PlusCET
+            | Model::TI83PCE
+            | Model::TI83PCEEP
+            | Model::TI84PlusCEPY
+            | Model::TI84PlusCETPE
+            | Model::TI82AEP
+            | Model::Latest => Ok(AssemblyTarget::EZ80),
+            _ => Ok(AssemblyTarget::Z80),
+        }
+    }
+}
+
+impl fmt::Display for AssemblyTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            AssemblyTarget::Z80 => "z80",
+            AssemblyTarget::Z80Color => "z80-color",
+            AssemblyTarget::EZ80 => "ez80",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Assembly {
+    pub form: AssemblyForm,
+    pub target: AssemblyTarget,
+    /// The machine code, read from the hex digits of hex programs
+    #[serde(skip)]
+    pub code: Vec<u8>,
+}
+
+impl Assembly {
+    /// `None` when the body is TI-BASIC
+    pub fn detect(body: &[u8]) -> Option<Assembly> {
+        let prefix = body.get(0..2)?;
+        let rest = &body[2..];
+
+        let (form, target) = match [prefix[0], prefix[1]] {
+            ASM_PRGM => (AssemblyForm::Hex, AssemblyTarget::Z80),
+            ASM_COMPILED => (AssemblyForm::Compiled, AssemblyTarget::Z80),
+            ASM_84C_PRGM => (AssemblyForm::Hex, AssemblyTarget::Z80Color),
+            ASM_84C_COMPILED => (AssemblyForm::Compiled, AssemblyTarget::Z80Color),
+            ASM_84CE_PRGM => (AssemblyForm::Hex, AssemblyTarget::EZ80),
+            ASM_84CE_COMPILED => (AssemblyForm::Compiled, AssemblyTarget::EZ80),
+            _ => return None,
+        };
+
+        let code = match form {
+            AssemblyForm::Hex => read_hex_tokens(rest),
+            AssemblyForm::Compiled => rest.to_vec(),
+        };
+
+        Some(Assembly { form, target, code })
+    }
+
+    /// Compiled machine code for a model, the form assemblers write
+    pub fn compiled(code: Vec<u8>, model: &Model) -> Result<Assembly, TiToolsError> {
+        Ok(Assembly {
+            form: AssemblyForm::Compiled,
+            target: AssemblyTarget::for_model(model)?,
+            code,
+        })
+    }
+
+    pub fn prefix(&self) -> [u8; 2] {
+        match (self.form, self.target) {
+            (AssemblyForm::Hex, AssemblyTarget::Z80) => ASM_PRGM,
+            (AssemblyForm::Compiled, AssemblyTarget::Z80) => ASM_COMPILED,
+            (AssemblyForm::Hex, AssemblyTarget::Z80Color) => ASM_84C_PRGM,
+            (AssemblyForm::Compiled, AssemblyTarget::Z80Color) => ASM_84C_COMPILED,
+            (AssemblyForm::Hex, AssemblyTarget::EZ80) => ASM_84CE_PRGM,
+            (AssemblyForm::Compiled, AssemblyTarget::EZ80) => ASM_84CE_COMPILED,
+        }
+    }
+
+    /// The body of a program holding the code in this form
+    pub fn to_body(&self) -> Vec<u8> {
+        let mut body = self.prefix().to_vec();
+
+        match self.form {
+            AssemblyForm::Hex => body.extend(self.to_hex().trim_end().bytes().map(|byte| {
+                if byte == b'\n' {
+                    NEWLINE
+                } else {
+                    byte
+                }
+            })),
+            AssemblyForm::Compiled => body.extend(&self.code),
+        }
+
+        body
+    }
+
+    /// The code as lines of 32 hex digits, like hex programs are usually typed
+    pub fn to_hex(&self) -> String {
+        self.code
+            .chunks(16)
+            .map(|line| {
+                line.iter()
+                    .map(|byte| format!("{:02X}", byte))
+                    .collect::<String>()
+                    + "\n"
+            })
+            .collect()
+    }
+}
+
+/// Reads machine code written as hex digits, ignoring whitespace
+pub fn parse_hex(text: &str) -> Result<Vec<u8>, TiToolsError> {
+    let digits: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+
+    if digits.len() % 2 != 0 {
+        return Err(TiToolsError::InvalidHex(
+            "an odd number of hex digits".to_string(),
+        ));
+    }
+
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let pair = pair.iter().collect::<String>();
+            u8::from_str_radix(&pair, 16).map_err(|_| TiToolsError::InvalidHex(pair))
+        })
+        .collect()
+}
+
+/// The hex digit tokens after the prefix, up to the first token that is not one or a newline
+fn read_hex_tokens(bytes: &[u8]) -> Vec<u8> {
+    let digits: Vec<u8> = bytes
+        .iter()
+        .copied()
+        .filter(|byte| *byte != NEWLINE)
+        .take_while(|byte| HEX_DIGITS.contains(byte))
+        .collect();
+
+    digits
+        .chunks_exact(2)
+        .filter_map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
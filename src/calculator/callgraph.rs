@@ -0,0 +1,194 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A program and the programs it calls with `prgm`
+#[derive(Debug, Serialize)]
+pub struct ProgramCalls {
+    pub name: String,
+    pub files: Vec<String>,
+    pub calls: Vec<String>,
+}
+
+/// A program that is called but is not part of the project
+#[derive(Debug, Serialize)]
+pub struct MissingProgram {
+    pub name: String,
+    pub called_by: Vec<String>,
+}
+
+/// How the programs of a project call each other
+#[derive(Debug, Serialize)]
+pub struct CallGraph {
+    pub programs: Vec<ProgramCalls>,
+    pub missing: Vec<MissingProgram>,
+    /// Groups of programs that call each other in a loop, a program calling itself is a group of one
+    pub cycles: Vec<Vec<String>>,
+}
+
+impl CallGraph {
+    /// Builds the graph, programs with the same name (like an 8xp and its txt source) are merged
+    pub fn new(programs: Vec<ProgramCalls>) -> CallGraph {
+        let mut merged: BTreeMap<String, ProgramCalls> = BTreeMap::new();
+
+        for program in programs {
+            match merged.get_mut(&program.name) {
+                Some(existing) => {
+                    existing.files.extend(program.files);
+
+                    for call in program.calls {
+                        if !existing.calls.contains(&call) {
+                            existing.calls.push(call);
+                        }
+                    }
+                }
+                None => {
+                    merged.insert(program.name.clone(), program);
+                }
+            }
+        }
+
+        let mut missing: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        for program in merged.values() {
+            for call in &program.calls {
+                if !merged.contains_key(call) {
+                    missing
+                        .entry(call.to_string())
+                        .or_default()
+                        .push(program.name.to_string());
+                }
+            }
+        }
+
+        let cycles = find_cycles(&merged);
+
+        CallGraph {
+            programs: merged.into_values().collect(),
+            missing: missing
+                .into_iter()
+                .map(|(name, called_by)| MissingProgram { name, called_by })
+                .collect(),
+            cycles,
+        }
+    }
+
+    pub fn in_cycle(&self, caller: &str, callee: &str) -> bool {
+        self.cycles.iter().any(|cycle| {
+            cycle.iter().any(|name| name == caller) && cycle.iter().any(|name| name == callee)
+        })
+    }
+
+    /// The graph in Graphviz DOT, missing programs are dashed and calls in cycles are red
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph calls {\n    node [shape=box];\n");
+
+        for program in &self.programs {
+            dot.push_str(&format!("    \"{}\";\n", program.name));
+        }
+
+        for missing in &self.missing {
+            dot.push_str(&format!(
+                "    \"{}\" [style=dashed, color=red, fontcolor=red];\n",
+                missing.name
+            ));
+        }
+
+        for program in &self.programs {
+            for call in &program.calls {
+                let style = if self.in_cycle(&program.name, call) {
+                    " [color=red]"
+                } else {
+                    ""
+                };
+
+                dot.push_str(&format!(
+                    "    \"{}\" -> \"{}\"{};\n",
+                    program.name, call, style
+                ));
+            }
+        }
+
+        dot.push_str("}\n");
+
+        dot
+    }
+}
+
+/// Tarjan's strongly connected components, keeping the ones that loop
+fn find_cycles(programs: &BTreeMap<String, ProgramCalls>) -> Vec<Vec<String>> {
+    struct State<'a> {
+        programs: &'a BTreeMap<String, ProgramCalls>,
+        index: usize,
+        indices: BTreeMap<&'a str, usize>,
+        low_links: BTreeMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: BTreeSet<&'a str>,
+        cycles: Vec<Vec<String>>,
+    }
+
+    fn visit<'a>(state: &mut State<'a>, name: &'a str) {
+        state.indices.insert(name, state.index);
+        state.low_links.insert(name, state.index);
+        state.index += 1;
+        state.stack.push(name);
+        state.on_stack.insert(name);
+
+        let calls = state.programs[name]
+            .calls
+            .iter()
+            .filter(|call| state.programs.contains_key(*call));
+
+        for call in calls {
+            let call = call.as_str();
+
+            if !state.indices.contains_key(call) {
+                visit(state, call);
+                let low_link = state.low_links[name].min(state.low_links[call]);
+                state.low_links.insert(name, low_link);
+            } else if state.on_stack.contains(call) {
+                let low_link = state.low_links[name].min(state.indices[call]);
+                state.low_links.insert(name, low_link);
+            }
+        }
+
+        if state.low_links[name] == state.indices[name] {
+            let mut component = Vec::new();
+
+            while let Some(member) = state.stack.pop() {
+                state.on_stack.remove(member);
+                component.push(member.to_string());
+
+                if member == name {
+                    break;
+                }
+            }
+
+            let calls_itself = state.programs[name].calls.iter().any(|call| call == name);
+
+            if component.len() > 1 || calls_itself {
+                component.sort();
+                state.cycles.push(component);
+            }
+        }
+    }
+
+    let mut state = State {
+        programs,
+        index: 0,
+        indices: BTreeMap::new(),
+        low_links: BTreeMap::new(),
+        stack: Vec::new(),
+        on_stack: BTreeSet::new(),
+        cycles: Vec::new(),
+    };
+
+    for name in programs.keys() {
+        if !state.indices.contains_key(name.as_str()) {
+            visit(&mut state, name);
+        }
+    }
+
+    state.cycles.sort();
+
+    state.cycles
+}
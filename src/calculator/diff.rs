@@ -0,0 +1,294 @@
+//! Compares two programs line by line and token by token, along with their metadata
+
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::file::{decode_program, DecodedToken};
+use crate::calculator::program::Program;
+use crate::calculator::tokens::bytes::NEWLINE;
+use crate::calculator::DisplayMode;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct MetadataChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Equal,
+    Removed,
+    Added,
+    Changed,
+}
+
+/// A token of a changed line, `Equal` when it is in both versions of the line
+#[derive(Debug, Serialize)]
+pub struct TokenChange {
+    pub kind: ChangeKind,
+    pub text: String,
+}
+
+/// Line numbers start at 1 and are missing on the side the line is not in
+#[derive(Debug, Serialize)]
+pub struct LineChange {
+    pub kind: ChangeKind,
+    #[serde(rename = "old-line", skip_serializing_if = "Option::is_none")]
+    pub old_line: Option<usize>,
+    #[serde(rename = "new-line", skip_serializing_if = "Option::is_none")]
+    pub new_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<String>,
+    /// Both versions of a changed line merged, token by token
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<TokenChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProgramDiff {
+    pub metadata: Vec<MetadataChange>,
+    pub lines: Vec<LineChange>,
+}
+
+impl ProgramDiff {
+    pub fn is_empty(&self) -> bool {
+        self.metadata.is_empty() && self.lines.iter().all(|line| line.kind == ChangeKind::Equal)
+    }
+
+    /// Moves the line numbers of each side, to count them from the top of a txt file
+    pub fn shift_lines(&mut self, old: usize, new: usize) {
+        for line in &mut self.lines {
+            if let Some(old_line) = &mut line.old_line {
+                *old_line += old;
+            }
+
+            if let Some(new_line) = &mut line.new_line {
+                *new_line += new;
+            }
+        }
+    }
+}
+
+/// Decodes both programs with the same display mode and compares them
+pub fn diff_programs(
+    old: &Program,
+    new: &Program,
+    display_mode: &DisplayMode,
+) -> Result<ProgramDiff, TiToolsError> {
+    let old_lines = program_lines(old, display_mode)?;
+    let new_lines = program_lines(new, display_mode)?;
+
+    Ok(ProgramDiff {
+        metadata: diff_metadata(old, new),
+        lines: diff_lines(&old_lines, &new_lines),
+    })
+}
+
+fn diff_metadata(old: &Program, new: &Program) -> Vec<MetadataChange> {
+    let fields = [
+        ("name", &old.metadata.name, &new.metadata.name),
+        ("comment", &old.header.comment, &new.header.comment),
+        (
+            "file-type",
+            &old.metadata.file_type.to_string(),
+            &new.metadata.file_type.to_string(),
+        ),
+        (
+            "destination",
+            &old.metadata.destination.to_string(),
+            &new.metadata.destination.to_string(),
+        ),
+        (
+            "model",
+            &old.model.model.to_string(),
+            &new.model.model.to_string(),
+        ),
+    ];
+
+    fields
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(field, old, new)| MetadataChange {
+            field: field.to_string(),
+            old: old.to_string(),
+            new: new.to_string(),
+        })
+        .collect()
+}
+
+/// The tokens of every line, without the newlines between them
+pub(crate) fn program_lines(
+    program: &Program,
+    display_mode: &DisplayMode,
+) -> Result<Vec<Vec<DecodedToken>>, TiToolsError> {
+    let tokens = decode_program(program, display_mode)?;
+
+    if tokens.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut lines = vec![Vec::new()];
+
+    for token in tokens {
+        if token.bytes == [NEWLINE] {
+            lines.push(Vec::new());
+        } else if let Some(line) = lines.last_mut() {
+            line.push(token);
+        }
+    }
+
+    Ok(lines)
+}
+
+pub(crate) fn line_text(tokens: &[DecodedToken]) -> String {
+    tokens.iter().map(|token| token.text.as_str()).collect()
+}
+
+fn diff_lines(old: &[Vec<DecodedToken>], new: &[Vec<DecodedToken>]) -> Vec<LineChange> {
+    let mut changes = Vec::new();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+
+    for (kind, old_index, new_index) in
+        longest_common_subsequence(old, new, |old, new| same_line(old, new))
+    {
+        match kind {
+            ChangeKind::Removed => removed.push(old_index),
+            ChangeKind::Added => added.push(new_index),
+            _ => {
+                flush_changes(old, new, &mut removed, &mut added, &mut changes);
+
+                changes.push(LineChange {
+                    kind: ChangeKind::Equal,
+                    old_line: Some(old_index + 1),
+                    new_line: Some(new_index + 1),
+                    old: Some(line_text(&old[old_index])),
+                    new: Some(line_text(&new[new_index])),
+                    tokens: Vec::new(),
+                });
+            }
+        }
+    }
+
+    flush_changes(old, new, &mut removed, &mut added, &mut changes);
+
+    changes
+}
+
+/// Lines are the same when their bytes are, so two spellings of one token still match
+pub(crate) fn same_line(old: &[DecodedToken], new: &[DecodedToken]) -> bool {
+    old.len() == new.len() && old.iter().zip(new).all(|(old, new)| old.bytes == new.bytes)
+}
+
+/// Pairs up a run of removed and added lines as changed lines, leaving the rest removed or added
+fn flush_changes(
+    old: &[Vec<DecodedToken>],
+    new: &[Vec<DecodedToken>],
+    removed: &mut Vec<usize>,
+    added: &mut Vec<usize>,
+    changes: &mut Vec<LineChange>,
+) {
+    let paired = removed.len().min(added.len());
+
+    for (&old_index, &new_index) in removed.iter().zip(added.iter()) {
+        changes.push(LineChange {
+            kind: ChangeKind::Changed,
+            old_line: Some(old_index + 1),
+            new_line: Some(new_index + 1),
+            old: Some(line_text(&old[old_index])),
+            new: Some(line_text(&new[new_index])),
+            tokens: diff_tokens(&old[old_index], &new[new_index]),
+        });
+    }
+
+    for &old_index in &removed[paired..] {
+        changes.push(LineChange {
+            kind: ChangeKind::Removed,
+            old_line: Some(old_index + 1),
+            new_line: None,
+            old: Some(line_text(&old[old_index])),
+            new: None,
+            tokens: Vec::new(),
+        });
+    }
+
+    for &new_index in &added[paired..] {
+        changes.push(LineChange {
+            kind: ChangeKind::Added,
+            old_line: None,
+            new_line: Some(new_index + 1),
+            old: None,
+            new: Some(line_text(&new[new_index])),
+            tokens: Vec::new(),
+        });
+    }
+
+    removed.clear();
+    added.clear();
+}
+
+fn diff_tokens(old: &[DecodedToken], new: &[DecodedToken]) -> Vec<TokenChange> {
+    let mut changes: Vec<TokenChange> = Vec::new();
+
+    for (kind, old_index, new_index) in
+        longest_common_subsequence(old, new, |old, new| old.bytes == new.bytes)
+    {
+        let text = match kind {
+            ChangeKind::Removed => &old[old_index].text,
+            _ => &new[new_index].text,
+        };
+
+        // Neighbouring tokens with the same change are merged to keep the output short
+        match changes.last_mut() {
+            Some(last) if last.kind == kind => last.text.push_str(text),
+            _ => changes.push(TokenChange {
+                kind,
+                text: text.to_string(),
+            }),
+        }
+    }
+
+    changes
+}
+
+/// Walks both sequences in order, giving each item as equal, removed or added with its indices
+pub(crate) fn longest_common_subsequence<T>(
+    old: &[T],
+    new: &[T],
+    equal: impl Fn(&T, &T) -> bool,
+) -> Vec<(ChangeKind, usize, usize)> {
+    // lengths[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if equal(&old[i], &new[j]) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut steps = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && equal(&old[i], &new[j]) {
+            steps.push((ChangeKind::Equal, i, j));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            steps.push((ChangeKind::Removed, i, j));
+            i += 1;
+        } else {
+            steps.push((ChangeKind::Added, i, j));
+            j += 1;
+        }
+    }
+
+    steps
+}
@@ -0,0 +1,517 @@
+//! Decoding of single Z80 and eZ80 instructions, following the x/y/z split of the opcode bits
+
+use super::Cpu;
+use std::collections::HashMap;
+
+const REGISTERS: [&str; 8] = ["b", "c", "d", "e", "h", "l", "(hl)", "a"];
+const PAIRS: [&str; 4] = ["bc", "de", "hl", "sp"];
+const PAIRS_AF: [&str; 4] = ["bc", "de", "hl", "af"];
+const CONDITIONS: [&str; 8] = ["nz", "z", "nc", "c", "po", "pe", "p", "m"];
+const ARITHMETIC: [&str; 8] = [
+    "add a,", "adc a,", "sub ", "sbc a,", "and ", "xor ", "or ", "cp ",
+];
+const ROTATIONS: [&str; 8] = ["rlc", "rrc", "rl", "rr", "sla", "sra", "sll", "srl"];
+const INTERRUPT_MODES: [&str; 8] = ["0", "0", "1", "2", "0", "0", "1", "2"];
+const ACCUMULATOR: [&str; 8] = ["rlca", "rrca", "rla", "rra", "daa", "cpl", "scf", "ccf"];
+const BLOCKS: [[&str; 4]; 4] = [
+    ["ldi", "cpi", "ini", "outi"],
+    ["ldd", "cpd", "ind", "outd"],
+    ["ldir", "cpir", "inir", "otir"],
+    ["lddr", "cpdr", "indr", "otdr"],
+];
+
+/// `rst 28h` on the Z80 calculators calls the OS routine at the word after it
+const BCALL: u8 = 0xEF;
+
+pub(super) struct Decoded {
+    pub length: usize,
+    pub text: String,
+}
+
+/// The instruction at the start of `code`, `None` when the code ends in the middle of it
+pub(super) fn decode(
+    code: &[u8],
+    address: u32,
+    cpu: Cpu,
+    symbols: &HashMap<u32, String>,
+) -> Option<Decoded> {
+    let first = *code.first()?;
+
+    // the eZ80 suffixes pick the width of the next instruction, .sis and .lis read 16 bit words
+    if cpu == Cpu::EZ80 && matches!(first, 0x40 | 0x49 | 0x52 | 0x5B) && code.len() > 1 {
+        let (suffix, word_width) = match first {
+            0x40 => (".sis", 2),
+            0x49 => (".lis", 2),
+            0x52 => (".sil", 3),
+            _ => (".lil", 3),
+        };
+
+        let mut reader = Reader::new(code, address, cpu, symbols, word_width);
+        reader.position = 1;
+        let text = reader.instruction();
+
+        if reader.truncated {
+            return None;
+        }
+
+        let text = match text.split_once(' ') {
+            Some((mnemonic, operands)) => format!("{}{} {}", mnemonic, suffix, operands),
+            None => format!("{}{}", text, suffix),
+        };
+
+        return Some(Decoded {
+            length: reader.position,
+            text,
+        });
+    }
+
+    let word_width = match cpu {
+        Cpu::Z80 => 2,
+        Cpu::EZ80 => 3,
+    };
+
+    let mut reader = Reader::new(code, address, cpu, symbols, word_width);
+    let text = reader.instruction();
+
+    if reader.truncated {
+        return None;
+    }
+
+    Some(Decoded {
+        length: reader.position,
+        text,
+    })
+}
+
+struct Reader<'a> {
+    code: &'a [u8],
+    position: usize,
+    address: u32,
+    cpu: Cpu,
+    symbols: &'a HashMap<u32, String>,
+    /// Bytes in an immediate word or address, 3 in eZ80 ADL mode
+    word_width: usize,
+    /// `ix` or `iy` after a DD or FD prefix, which stand in for `hl`
+    index: Option<&'static str>,
+    used_index: bool,
+    truncated: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn new(
+        code: &'a [u8],
+        address: u32,
+        cpu: Cpu,
+        symbols: &'a HashMap<u32, String>,
+        word_width: usize,
+    ) -> Reader<'a> {
+        Reader {
+            code,
+            position: 0,
+            address,
+            cpu,
+            symbols,
+            word_width,
+            index: None,
+            used_index: false,
+            truncated: false,
+        }
+    }
+
+    /// Reading past the end gives zeros and marks the instruction as cut off
+    fn byte(&mut self) -> u8 {
+        match self.code.get(self.position) {
+            Some(byte) => {
+                self.position += 1;
+                *byte
+            }
+            None => {
+                self.truncated = true;
+                0
+            }
+        }
+    }
+
+    fn n(&mut self) -> String {
+        format!("${:02X}", self.byte())
+    }
+
+    fn word(&mut self) -> u32 {
+        (0..self.word_width).fold(0, |word, shift| {
+            word | (u32::from(self.byte()) << (8 * shift))
+        })
+    }
+
+    fn nn(&mut self) -> String {
+        let word = self.word();
+        self.named(word)
+    }
+
+    /// An address by its symbol when the OS has one there
+    fn named(&self, address: u32) -> String {
+        match self.symbols.get(&address) {
+            Some(symbol) => symbol.to_string(),
+            None => format!("${:0width$X}", address, width = self.word_width * 2),
+        }
+    }
+
+    /// The target of `jr` and `djnz`, relative to the end of the instruction
+    fn relative(&mut self) -> String {
+        let displacement = self.byte() as i8;
+        let target = (self.address as i64 + self.position as i64 + displacement as i64) as u32;
+
+        self.named(target & self.cpu.address_mask())
+    }
+
+    fn indexed(&mut self, index: &str) -> String {
+        let displacement = self.byte() as i8;
+
+        if displacement < 0 {
+            format!("({}-${:02X})", index, displacement.unsigned_abs())
+        } else {
+            format!("({}+${:02X})", index, displacement)
+        }
+    }
+
+    /// A register, `(hl)` becomes `(ix+d)` and `h` and `l` the halves of the index register
+    fn register(&mut self, register: usize) -> String {
+        match (self.index, register) {
+            (Some(index), 6) => {
+                self.used_index = true;
+                self.indexed(index)
+            }
+            (Some(index), 4 | 5) => {
+                self.used_index = true;
+                format!("{}{}", index, REGISTERS[register])
+            }
+            _ => REGISTERS[register].to_string(),
+        }
+    }
+
+    fn pair(&mut self, names: &[&str; 4], pair: usize) -> String {
+        match (self.index, pair) {
+            (Some(index), 2) => {
+                self.used_index = true;
+                index.to_string()
+            }
+            _ => names[pair].to_string(),
+        }
+    }
+
+    /// `hl` as the operand of `jp (hl)`, `ld sp,hl` and `ex (sp),hl`
+    fn hl(&mut self) -> String {
+        self.pair(&PAIRS, 2)
+    }
+
+    fn instruction(&mut self) -> String {
+        let start = self.position;
+        let opcode = self.byte();
+
+        match opcode {
+            0xDD | 0xFD => {
+                // a prefix before another prefix does nothing, it is shown as data
+                if matches!(
+                    self.code.get(self.position),
+                    Some(0xDD | 0xED | 0xFD) | None
+                ) {
+                    return format!("db ${:02X}", opcode);
+                }
+
+                self.index = Some(if opcode == 0xDD { "ix" } else { "iy" });
+                let text = self.indexed_instruction();
+
+                if self.used_index || self.truncated {
+                    text
+                } else {
+                    self.position = start + 1;
+                    format!("db ${:02X}", opcode)
+                }
+            }
+            0xED => self.extended(),
+            0xCB => self.bit_instruction(),
+            _ => self.main(opcode),
+        }
+    }
+
+    fn indexed_instruction(&mut self) -> String {
+        let opcode = self.byte();
+
+        if opcode == 0xCB {
+            return self.indexed_bit_instruction();
+        }
+
+        if self.cpu == Cpu::EZ80 {
+            if let Some(text) = self.ez80_indexed(opcode) {
+                self.used_index = true;
+                return text;
+            }
+        }
+
+        self.main(opcode)
+    }
+
+    /// The 24 bit loads the eZ80 added between registers and `(ix+d)`
+    fn ez80_indexed(&mut self, opcode: u8) -> Option<String> {
+        let index = self.index?;
+        let other = if index == "ix" { "iy" } else { "ix" };
+
+        let text = match opcode {
+            0x07 | 0x17 | 0x27 => {
+                let memory = self.indexed(index);
+                format!("ld {},{}", PAIRS[(opcode >> 4) as usize], memory)
+            }
+            0x37 => format!("ld {},{}", index, self.indexed(index)),
+            0x31 => format!("ld {},{}", other, self.indexed(index)),
+            0x0F | 0x1F | 0x2F => {
+                let memory = self.indexed(index);
+                format!("ld {},{}", memory, PAIRS[(opcode >> 4) as usize])
+            }
+            0x3F => format!("ld {},{}", self.indexed(index), index),
+            0x3E => format!("ld {},{}", self.indexed(index), other),
+            _ => return None,
+        };
+
+        Some(text)
+    }
+
+    fn main(&mut self, opcode: u8) -> String {
+        let x = opcode >> 6;
+        let y = ((opcode >> 3) & 7) as usize;
+        let z = opcode & 7;
+        let p = y >> 1;
+        let q = y & 1;
+
+        match (x, z) {
+            (0, 0) => match y {
+                0 => "nop".to_string(),
+                1 => "ex af,af'".to_string(),
+                2 => format!("djnz {}", self.relative()),
+                3 => format!("jr {}", self.relative()),
+                _ => format!("jr {},{}", CONDITIONS[y - 4], self.relative()),
+            },
+            (0, 1) if q == 0 => {
+                let pair = self.pair(&PAIRS, p);
+                format!("ld {},{}", pair, self.nn())
+            }
+            (0, 1) => {
+                let hl = self.hl();
+                format!("add {},{}", hl, self.pair(&PAIRS, p))
+            }
+            (0, 2) => match (q, p) {
+                (0, 0) => "ld (bc),a".to_string(),
+                (0, 1) => "ld (de),a".to_string(),
+                (0, 2) => {
+                    let hl = self.hl();
+                    format!("ld ({}),{}", self.nn(), hl)
+                }
+                (0, _) => format!("ld ({}),a", self.nn()),
+                (_, 0) => "ld a,(bc)".to_string(),
+                (_, 1) => "ld a,(de)".to_string(),
+                (_, 2) => {
+                    let hl = self.hl();
+                    format!("ld {},({})", hl, self.nn())
+                }
+                _ => format!("ld a,({})", self.nn()),
+            },
+            (0, 3) => {
+                let operation = if q == 0 { "inc" } else { "dec" };
+                format!("{} {}", operation, self.pair(&PAIRS, p))
+            }
+            (0, 4) => format!("inc {}", self.register(y)),
+            (0, 5) => format!("dec {}", self.register(y)),
+            (0, 6) => {
+                let register = self.register(y);
+                format!("ld {},{}", register, self.n())
+            }
+            (0, _) => ACCUMULATOR[y].to_string(),
+            (1, 6) if y == 6 => "halt".to_string(),
+            (1, _) => {
+                // the register beside (ix+d) stays h or l
+                let (destination, source) = if z == 6 {
+                    (REGISTERS[y].to_string(), self.register(6))
+                } else if y == 6 {
+                    (self.register(6), REGISTERS[z as usize].to_string())
+                } else {
+                    (self.register(y), self.register(z as usize))
+                };
+
+                format!("ld {},{}", destination, source)
+            }
+            (2, _) => format!("{}{}", ARITHMETIC[y], self.register(z as usize)),
+            (_, 0) => format!("ret {}", CONDITIONS[y]),
+            (_, 1) if q == 0 => format!("pop {}", self.pair(&PAIRS_AF, p)),
+            (_, 1) => match p {
+                0 => "ret".to_string(),
+                1 => "exx".to_string(),
+                2 => format!("jp ({})", self.hl()),
+                _ => format!("ld sp,{}", self.hl()),
+            },
+            (_, 2) => format!("jp {},{}", CONDITIONS[y], self.nn()),
+            (_, 3) => match y {
+                0 => format!("jp {}", self.nn()),
+                1 => self.bit_instruction(),
+                2 => format!("out ({}),a", self.n()),
+                3 => format!("in a,({})", self.n()),
+                4 => format!("ex (sp),{}", self.hl()),
+                5 => "ex de,hl".to_string(),
+                6 => "di".to_string(),
+                _ => "ei".to_string(),
+            },
+            (_, 4) => format!("call {},{}", CONDITIONS[y], self.nn()),
+            (_, 5) if q == 0 => format!("push {}", self.pair(&PAIRS_AF, p)),
+            (_, 5) => match p {
+                0 => format!("call {}", self.nn()),
+                // the other prefixes are read before the main table
+                _ => format!("db ${:02X}", opcode),
+            },
+            (_, 6) => format!("{}{}", ARITHMETIC[y], self.n()),
+            _ if opcode == BCALL && self.cpu == Cpu::Z80 => {
+                // bcalls always name a 16 bit routine in the OS jump table
+                let routine = u32::from(self.byte()) | (u32::from(self.byte()) << 8);
+
+                match self.symbols.get(&routine) {
+                    Some(symbol) => format!("bcall({})", symbol),
+                    None => format!("bcall(${:04X})", routine),
+                }
+            }
+            _ => format!("rst ${:02X}", y * 8),
+        }
+    }
+
+    fn bit_instruction(&mut self) -> String {
+        let opcode = self.byte();
+        let y = ((opcode >> 3) & 7) as usize;
+        let register = REGISTERS[(opcode & 7) as usize];
+
+        match opcode >> 6 {
+            0 => format!("{} {}", ROTATIONS[y], register),
+            1 => format!("bit {},{}", y, register),
+            2 => format!("res {},{}", y, register),
+            _ => format!("set {},{}", y, register),
+        }
+    }
+
+    /// DD CB and FD CB put the displacement before the opcode
+    fn indexed_bit_instruction(&mut self) -> String {
+        let index = self.index.unwrap_or("ix");
+        self.used_index = true;
+
+        let memory = self.indexed(index);
+        let opcode = self.byte();
+        let y = (opcode >> 3) & 7;
+        let z = (opcode & 7) as usize;
+
+        // the undocumented forms also copy the result to a register
+        let copy = if z == 6 {
+            String::new()
+        } else {
+            format!(",{}", REGISTERS[z])
+        };
+
+        match opcode >> 6 {
+            0 => format!("{} {}{}", ROTATIONS[y as usize], memory, copy),
+            1 => format!("bit {},{}", y, memory),
+            2 => format!("res {},{}{}", y, memory, copy),
+            _ => format!("set {},{}{}", y, memory, copy),
+        }
+    }
+
+    fn extended(&mut self) -> String {
+        let opcode = self.byte();
+
+        if self.cpu == Cpu::EZ80 {
+            if let Some(text) = self.ez80_extended(opcode) {
+                return text;
+            }
+        }
+
+        let y = ((opcode >> 3) & 7) as usize;
+        let z = opcode & 7;
+        let p = y >> 1;
+        let q = y & 1;
+
+        match (opcode >> 6, z) {
+            (1, 0) if y == 6 => "in f,(c)".to_string(),
+            (1, 0) => format!("in {},(c)", REGISTERS[y]),
+            (1, 1) if y == 6 => "out (c),0".to_string(),
+            (1, 1) => format!("out (c),{}", REGISTERS[y]),
+            (1, 2) if q == 0 => format!("sbc hl,{}", PAIRS[p]),
+            (1, 2) => format!("adc hl,{}", PAIRS[p]),
+            (1, 3) if q == 0 => format!("ld ({}),{}", self.nn(), PAIRS[p]),
+            (1, 3) => format!("ld {},({})", PAIRS[p], self.nn()),
+            (1, 4) => "neg".to_string(),
+            (1, 5) if y == 1 => "reti".to_string(),
+            (1, 5) => "retn".to_string(),
+            (1, 6) => format!("im {}", INTERRUPT_MODES[y]),
+            (1, _) => match y {
+                0 => "ld i,a".to_string(),
+                1 => "ld r,a".to_string(),
+                2 => "ld a,i".to_string(),
+                3 => "ld a,r".to_string(),
+                4 => "rrd".to_string(),
+                5 => "rld".to_string(),
+                _ => "nop".to_string(),
+            },
+            (2, 0..=3) if y >= 4 => BLOCKS[y - 4][z as usize].to_string(),
+            _ => format!("db $ED,${:02X}", opcode),
+        }
+    }
+
+    /// The instructions the eZ80 added after ED, where the Z80 has none
+    fn ez80_extended(&mut self, opcode: u8) -> Option<String> {
+        let y = ((opcode >> 3) & 7) as usize;
+
+        let text = match opcode {
+            0x00 | 0x08 | 0x10 | 0x18 | 0x20 | 0x28 | 0x38 => {
+                format!("in0 {},({})", REGISTERS[y], self.n())
+            }
+            0x01 | 0x09 | 0x11 | 0x19 | 0x21 | 0x29 | 0x39 => {
+                format!("out0 ({}),{}", self.n(), REGISTERS[y])
+            }
+            0x02 | 0x12 | 0x22 => format!("lea {},{}", PAIRS[y >> 1], self.offset("ix")),
+            0x03 | 0x13 | 0x23 => format!("lea {},{}", PAIRS[y >> 1], self.offset("iy")),
+            0x32 => format!("lea ix,{}", self.offset("ix")),
+            0x33 => format!("lea iy,{}", self.offset("iy")),
+            0x54 => format!("lea ix,{}", self.offset("iy")),
+            0x55 => format!("lea iy,{}", self.offset("ix")),
+            0x65 => format!("pea {}", self.offset("ix")),
+            0x66 => format!("pea {}", self.offset("iy")),
+            0x04 | 0x0C | 0x14 | 0x1C | 0x24 | 0x2C | 0x34 | 0x3C => {
+                format!("tst a,{}", REGISTERS[y])
+            }
+            0x64 => format!("tst a,{}", self.n()),
+            0x74 => format!("tstio {}", self.n()),
+            0x07 | 0x17 | 0x27 => format!("ld {},(hl)", PAIRS[y >> 1]),
+            0x37 => "ld ix,(hl)".to_string(),
+            0x31 => "ld iy,(hl)".to_string(),
+            0x0F | 0x1F | 0x2F => format!("ld (hl),{}", PAIRS[y >> 1]),
+            0x3F => "ld (hl),ix".to_string(),
+            0x3E => "ld (hl),iy".to_string(),
+            0x4C | 0x5C | 0x6C | 0x7C => format!("mlt {}", PAIRS[((opcode >> 4) & 3) as usize]),
+            0x6D => "ld mb,a".to_string(),
+            0x6E => "ld a,mb".to_string(),
+            0x76 => "slp".to_string(),
+            0x7D => "stmix".to_string(),
+            0x7E => "rsmix".to_string(),
+            0xC7 => "ld i,hl".to_string(),
+            0xD7 => "ld hl,i".to_string(),
+            0x82 => "inim".to_string(),
+            0x83 => "otim".to_string(),
+            0x8A => "indm".to_string(),
+            0x8B => "otdm".to_string(),
+            0x92 => "inimr".to_string(),
+            0x93 => "otimr".to_string(),
+            0x9A => "indmr".to_string(),
+            0x9B => "otdmr".to_string(),
+            _ => return None,
+        };
+
+        Some(text)
+    }
+
+    /// The `ix+d` operand of `lea` and `pea`, without the parentheses of a memory operand
+    fn offset(&mut self, index: &str) -> String {
+        let memory = self.indexed(index);
+        memory[1..memory.len() - 1].to_string()
+    }
+}
@@ -0,0 +1,107 @@
+//! Disassembly of the machine code in assembly programs, naming the OS routines it calls
+
+mod decode;
+
+use crate::calculator::assembly::AssemblyTarget;
+use crate::calculator::errors::TiToolsError;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Cpu {
+    Z80,
+    /// The eZ80 in ADL mode, with 24 bit registers and addresses
+    EZ80,
+}
+
+impl Cpu {
+    pub fn from_string(cpu: &str) -> Result<Cpu, TiToolsError> {
+        match cpu {
+            "z80" => Ok(Cpu::Z80),
+            "ez80" => Ok(Cpu::EZ80),
+            _ => Err(TiToolsError::Match(cpu.to_string(), "Cpu".to_string())),
+        }
+    }
+
+    /// The TI-84+CSE runs the same Z80 as the monochrome models
+    pub fn for_target(target: AssemblyTarget) -> Cpu {
+        match target {
+            AssemblyTarget::Z80 | AssemblyTarget::Z80Color => Cpu::Z80,
+            AssemblyTarget::EZ80 => Cpu::EZ80,
+        }
+    }
+
+    /// The highest address, 16 bits on the Z80 and 24 on the eZ80, which addresses wrap around at
+    pub fn address_mask(&self) -> u32 {
+        match self {
+            Cpu::Z80 => 0xFFFF,
+            Cpu::EZ80 => 0xFFFFFF,
+        }
+    }
+}
+
+impl fmt::Display for Cpu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            Cpu::Z80 => "z80",
+            Cpu::EZ80 => "ez80",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    pub address: u32,
+    pub bytes: Vec<u8>,
+    pub text: String,
+}
+
+/// The OS routines and RAM areas of a cpu's calculators by address, like `_PutS`
+pub fn load_symbols(cpu: Cpu) -> Result<HashMap<u32, String>, TiToolsError> {
+    let json_data = include_str!("./symbols.json");
+
+    let symbols: HashMap<String, HashMap<String, String>> =
+        serde_json::from_str(json_data).map_err(|err| TiToolsError::Json(err.to_string()))?;
+
+    symbols
+        .get(&cpu.to_string())
+        .map_or(&HashMap::new(), |symbols| symbols)
+        .iter()
+        .map(|(address, symbol)| {
+            u32::from_str_radix(address, 16)
+                .map(|address| (address, symbol.to_string()))
+                .map_err(|err| TiToolsError::Json(format!("{}: {}", address, err)))
+        })
+        .collect()
+}
+
+/// Every instruction in the code as if it were loaded at `origin`, bytes left over at the end
+/// that do not make a whole instruction are shown as data
+pub fn disassemble(code: &[u8], cpu: Cpu, origin: u32) -> Result<Vec<Instruction>, TiToolsError> {
+    let symbols = load_symbols(cpu)?;
+    let mut instructions = Vec::new();
+    let mut offset = 0;
+
+    while offset < code.len() {
+        let address = origin.wrapping_add(offset as u32) & cpu.address_mask();
+
+        let (length, text) = match decode::decode(&code[offset..], address, cpu, &symbols) {
+            Some(decoded) => (decoded.length, decoded.text),
+            None => (1, format!("db ${:02X}", code[offset])),
+        };
+
+        instructions.push(Instruction {
+            address,
+            bytes: code[offset..offset + length].to_vec(),
+            text,
+        });
+
+        offset += length;
+    }
+
+    Ok(instructions)
+}
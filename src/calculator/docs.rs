@@ -0,0 +1,250 @@
+//! The token reference, written from the same token data and definitions the encoder loads
+
+use crate::calculator::render::escape_html;
+use crate::calculator::tokens::{
+    definition_key, Token, TokenCategory, TokenDefinition, TokenEntry, Translation,
+};
+use std::collections::HashMap;
+
+const TITLE: &str = "Tokens";
+const NOTICE: &str = "Generated by `ti-tools docs` from the token data built into ti-tools.";
+
+/// Every token in byte order with its spellings, the models it is on and its definitions
+pub fn token_reference_markdown(
+    table: &[TokenEntry],
+    definitions: &HashMap<String, Vec<TokenDefinition>>,
+) -> String {
+    let mut markdown = format!("# {}\n\n{}\n", TITLE, NOTICE);
+
+    for entry in table {
+        let heading = english(entry.versions.last())
+            .map_or(String::new(), |translation| code_span(&translation.display));
+
+        markdown.push_str(&format!(
+            "\n## {} {}\n\n- Category: {}\n\n",
+            definition_key(&entry.key),
+            heading,
+            entry.category
+        ));
+
+        markdown.push_str("| Since | Until | Display | Accessible | TI-ASCII |\n");
+        markdown.push_str("| --- | --- | --- | --- | --- |\n");
+
+        for version in &entry.versions {
+            let (since, until) = availability(version);
+            let cell = |text: fn(&Translation) -> &str| {
+                english(Some(version))
+                    .map_or(String::new(), |translation| code_span(text(translation)))
+            };
+
+            markdown.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                since,
+                until,
+                cell(|translation| &translation.display),
+                cell(|translation| &translation.accessible),
+                cell(|translation| &translation.ti_ascii),
+            ));
+        }
+
+        for definition in definitions
+            .get(&definition_key(&entry.key))
+            .map_or(&[][..], |definitions| definitions.as_slice())
+        {
+            markdown.push_str(&format!("\n{}\n", definition.to_markdown()));
+        }
+    }
+
+    markdown
+}
+
+/// The same reference as a standalone HTML page, with the tokens listed by category at the top
+pub fn token_reference_html(
+    table: &[TokenEntry],
+    definitions: &HashMap<String, Vec<TokenDefinition>>,
+) -> String {
+    let mut contents = String::new();
+
+    for category in TokenCategory::all() {
+        let links = table
+            .iter()
+            .filter(|entry| entry.category == category)
+            .map(|entry| {
+                let display = english(entry.versions.last())
+                    .map_or("", |translation| translation.display.as_str());
+
+                format!(
+                    "<a href=\"#{}\">{}</a>",
+                    anchor(entry),
+                    escape_html(&visible(display))
+                )
+            })
+            .collect::<Vec<String>>();
+
+        if !links.is_empty() {
+            contents.push_str(&format!(
+                "<h3>{}</h3>\n<p>{}</p>\n",
+                category,
+                links.join(" ")
+            ));
+        }
+    }
+
+    let mut tokens = String::new();
+
+    for entry in table {
+        let display =
+            english(entry.versions.last()).map_or("", |translation| translation.display.as_str());
+
+        tokens.push_str(&format!(
+            "<section id=\"{}\">\n<h2>{} <code>{}</code></h2>\n<p>Category: {}</p>\n",
+            anchor(entry),
+            definition_key(&entry.key),
+            escape_html(&visible(display)),
+            entry.category
+        ));
+
+        tokens.push_str(
+            "<table>\n<tr><th>Since</th><th>Until</th><th>Display</th><th>Accessible</th><th>TI-ASCII</th></tr>\n",
+        );
+
+        for version in &entry.versions {
+            let (since, until) = availability(version);
+            let cell = |text: fn(&Translation) -> &str| {
+                english(Some(version)).map_or(String::new(), |translation| {
+                    escape_html(&visible(text(translation)))
+                })
+            };
+
+            tokens.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td><code>{}</code></td><td><code>{}</code></td><td><code>{}</code></td></tr>\n",
+                since,
+                until,
+                cell(|translation| &translation.display),
+                cell(|translation| &translation.accessible),
+                cell(|translation| &translation.ti_ascii),
+            ));
+        }
+
+        tokens.push_str("</table>\n");
+
+        for definition in definitions
+            .get(&definition_key(&entry.key))
+            .map_or(&[][..], |definitions| definitions.as_slice())
+        {
+            tokens.push_str(&definition_html(definition));
+        }
+
+        tokens.push_str("</section>\n");
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; max-width: 60em; margin: auto; padding: 1em; }}
+a {{ margin-right: 0.5em; }}
+table {{ border-collapse: collapse; }}
+th, td {{ border: 1px solid #ccc; padding: 0.2em 0.5em; text-align: left; }}
+pre {{ background: #f4f4f4; padding: 0.5em; }}
+section {{ border-top: 1px solid #ccc; margin-top: 1em; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p>{notice}</p>
+{contents}{tokens}</body>
+</html>
+"#,
+        title = TITLE,
+        notice = escape_html(NOTICE),
+        contents = contents,
+        tokens = tokens
+    )
+}
+
+fn definition_html(definition: &TokenDefinition) -> String {
+    let mut html = format!(
+        "<pre>{}</pre>\n<p>{}</p>\n",
+        escape_html(&definition.syntax),
+        escape_html(&definition.description).replace('\n', "<br>")
+    );
+
+    if !definition.arguments.is_empty() {
+        html.push_str("<ul>\n");
+
+        for argument in &definition.arguments {
+            let optional = if argument.optional { ", optional" } else { "" };
+
+            html.push_str(&format!(
+                "<li><code>{}</code> ({}{}): {}</li>\n",
+                escape_html(&argument.name),
+                escape_html(&argument.kind),
+                optional,
+                escape_html(&argument.description)
+            ));
+        }
+
+        html.push_str("</ul>\n");
+    }
+
+    if let Some(returns) = &definition.returns {
+        html.push_str(&format!("<p>Returns {}</p>\n", escape_html(returns)));
+    }
+
+    if !definition.models.is_empty() {
+        html.push_str(&format!(
+            "<p>Models {}</p>\n",
+            escape_html(&definition.models.join(", "))
+        ));
+    }
+
+    for example in &definition.examples {
+        html.push_str(&format!("<pre>{}</pre>\n", escape_html(example)));
+    }
+
+    html
+}
+
+fn english(token: Option<&Token>) -> Option<&Translation> {
+    token.and_then(|token| token.langs.get("en"))
+}
+
+fn availability(version: &Token) -> (String, String) {
+    (
+        format!("{} {}", version.since.model, version.since.version),
+        version.until.as_ref().map_or(String::new(), |until| {
+            format!("{} {}", until.model, until.version)
+        }),
+    )
+}
+
+/// Ids are the bytes of the token, like `BB0A`
+fn anchor(entry: &TokenEntry) -> String {
+    entry.key.replace('$', "")
+}
+
+/// Newlines and spaces would not show up in the reference
+fn visible(text: &str) -> String {
+    text.replace('\n', "\\n").replace(' ', "␣")
+}
+
+/// A markdown code span that holds any text, including backticks and table pipes
+fn code_span(text: &str) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+
+    let text = visible(text).replace('|', "\\|");
+    let fence = if text.contains('`') { "``" } else { "`" };
+    let padding = if text.starts_with('`') || text.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+
+    format!("{fence}{padding}{text}{padding}{fence}")
+}
@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use std::fmt;
 
 pub enum TiToolsError {
     FileRead(String),
@@ -29,17 +29,40 @@ pub enum TiToolsError {
     FailedToSerializeJson(String),
     FailedToReadFile(String),
     FailedToDeserializeJson(String),
+    FileAlreadyExists(String),
+    /**File, Error */
+    FailedToBackupFile(String, String),
+    FormattingChangesProgram(String),
+    /**Name, Error */
+    InvalidVariable(String, String),
+    /**Model */
+    AssemblyUnsupported(String),
+    /**Program name */
+    NotAssembly(String),
+    /**Program name */
+    CompiledAssembly(String),
+    InvalidHex(String),
+    /**Program name */
+    NoShellHeader(String),
+    /**Shell */
+    NoShellDescription(String),
+    /**Shell */
+    NoShellIcon(String),
+    /**Characters the header has room for */
+    ShellDescriptionTooLong(usize),
+    InvalidDescriptionCharacters,
+    InvalidIcon(String),
 }
 
-impl Debug for TiToolsError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+impl fmt::Debug for TiToolsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
     }
 }
 
-impl TiToolsError {
-    fn to_string(&self) -> String {
-        match self {
+impl fmt::Display for TiToolsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
             TiToolsError::FileRead(err) => format!("Failed to read file: {}", err),
             TiToolsError::FileWrite(err) => format!("Failed to write file: {}", err),
             TiToolsError::Match(value, enum_name) => {
@@ -95,11 +118,62 @@ impl TiToolsError {
             TiToolsError::FailedToDeserializeJson(err) => {
                 format!("Failed to deserialize json: {}", err)
             }
-        }
+            TiToolsError::FileAlreadyExists(file) => {
+                format!("A file already exists at {}", file)
+            }
+            TiToolsError::FailedToBackupFile(file, err) => {
+                format!("Failed to back up file {}: {}", file, err)
+            }
+            TiToolsError::FormattingChangesProgram(file) => {
+                format!(
+                    "Formatting {} would change the encoded program, try another display mode",
+                    file
+                )
+            }
+            TiToolsError::InvalidVariable(name, err) => {
+                format!("Invalid variable {}: {}", name, err)
+            }
+            TiToolsError::AssemblyUnsupported(model) => {
+                format!("The {} does not run assembly programs", model)
+            }
+            TiToolsError::NotAssembly(name) => {
+                format!("{} is a TI-BASIC program, not an assembly program", name)
+            }
+            TiToolsError::CompiledAssembly(name) => {
+                format!(
+                    "{} is a compiled assembly program, convert it to bin or hex instead",
+                    name
+                )
+            }
+            TiToolsError::InvalidHex(hex) => format!("Invalid hex: {}", hex),
+            TiToolsError::NoShellHeader(name) => {
+                format!("{} has no Ion, MirageOS, DoorsCS or Cesium header", name)
+            }
+            TiToolsError::NoShellDescription(shell) => {
+                format!("The {} header of this program has no description", shell)
+            }
+            TiToolsError::NoShellIcon(shell) => {
+                format!("The {} header of this program has no icon", shell)
+            }
+            TiToolsError::ShellDescriptionTooLong(room) => {
+                format!(
+                    "The description must be {} or less characters, the room the header has for it",
+                    room
+                )
+            }
+            TiToolsError::InvalidDescriptionCharacters => {
+                "Description must be printable ASCII characters".to_string()
+            }
+            TiToolsError::InvalidIcon(err) => format!("Invalid icon: {}", err),
+        };
+
+        write!(f, "{}", message)
     }
+}
 
+impl TiToolsError {
     pub fn print(self) -> TiToolsError {
-        eprintln!("{}", self.to_string());
+        eprintln!("{}", self);
         self
     }
 
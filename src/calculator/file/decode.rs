@@ -1,15 +1,69 @@
 use crate::calculator::errors::TiToolsError;
-use crate::calculator::tokens::Map;
+use crate::calculator::program::Program;
+use crate::calculator::tokens::{load_tokens, Map, OsVersion};
 use crate::calculator::DisplayMode;
+use serde::Serialize;
 
+/// A single token from a program body with every spelling of it and where it was found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecodedToken {
+    pub bytes: Vec<u8>,
+    /// The key of the token in the token map, like `$BB$6D`
+    pub key: String,
+    /// The spelling in the display mode the token was decoded with
+    pub text: String,
+    pub display: String,
+    pub accessible: String,
+    #[serde(rename = "ti-ascii")]
+    pub ti_ascii: String,
+    /// The position of the first byte of the token in the body
+    pub offset: usize,
+    /// The line of the program the token is on, starting at 1
+    pub line: usize,
+}
+
+/// Decodes a body into a single string, see `decode_tokens` to keep the tokens apart
 pub fn decode(
     bytestream: &[u8],
     map: &Map,
     lang: &str,
     mode: &DisplayMode,
 ) -> Result<String, TiToolsError> {
-    let mut decoded_program = String::new();
+    let tokens = decode_tokens(bytestream, map, lang, mode)?;
+
+    Ok(tokens.into_iter().map(|token| token.text).collect())
+}
+
+/// Decodes the body of a program with the tokens of its model
+pub fn decode_program(
+    program: &Program,
+    mode: &DisplayMode,
+) -> Result<Vec<DecodedToken>, TiToolsError> {
+    let map = load_program_tokens(program)?;
+
+    decode_tokens(&program.body.bytes, &map, "en", mode)
+}
+
+pub fn load_program_tokens(program: &Program) -> Result<Map, TiToolsError> {
+    let version = OsVersion {
+        model: program.model.model.clone(),
+        version: "latest".to_string(),
+    };
+
+    load_tokens(&version)
+}
+
+/// Decodes a body into its tokens in the order they appear
+pub fn decode_tokens(
+    bytestream: &[u8],
+    map: &Map,
+    lang: &str,
+    mode: &DisplayMode,
+) -> Result<Vec<DecodedToken>, TiToolsError> {
+    let mut decoded_tokens = Vec::new();
+    let mut line = 1;
     let mut index = 0;
+    let mut offset = 0;
     let mut current_bytes = Vec::new();
 
     while index < bytestream.len() {
@@ -34,15 +88,32 @@ pub fn decode(
                 DisplayMode::Accessible => &token.accessible,
                 DisplayMode::TiAscii => &token.ti_ascii,
             };
-            decoded_program.push_str(representation);
+
+            let is_newline = current_bytes == [0x3F];
+
+            decoded_tokens.push(DecodedToken {
+                bytes: current_bytes.clone(),
+                key,
+                text: representation.to_string(),
+                display: token.display.to_string(),
+                accessible: token.accessible.to_string(),
+                ti_ascii: token.ti_ascii.to_string(),
+                offset,
+                line,
+            });
             current_bytes.clear();
+            offset = index + 1;
+
+            if is_newline {
+                line += 1;
+            }
         }
 
         index += 1;
     }
 
     if current_bytes.is_empty() {
-        Ok(decoded_program)
+        Ok(decoded_tokens)
     } else {
         Err(TiToolsError::TokenNotFound(format!(
             "{:02X?}",
@@ -56,7 +56,8 @@ pub fn encode(
 
     let mut state = EncodeState::new(encode_mode);
     for line in decoded_program.lines() {
-        let mut temp_line = line.to_string();
+        // tabs have no token, so leading ones are only indentation
+        let mut temp_line = line.trim_start_matches('\t').to_string();
 
         while !temp_line.is_empty() {
             let token: Option<(String, String)> = match state.current_mode {
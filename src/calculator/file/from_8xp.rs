@@ -1,4 +1,5 @@
 use super::decode::decode;
+use crate::calculator::assembly::{Assembly, AssemblyForm};
 use crate::calculator::errors::TiToolsError;
 use crate::calculator::models::ModelDetails;
 use crate::calculator::program::{
@@ -18,6 +19,14 @@ pub fn create_from_8xp(
         Err(err) => return Err(TiToolsError::FileRead(err)),
     };
 
+    // header, shortest metadata and checksum
+    if bytes.len() < 55 + 17 + 2 {
+        return Err(TiToolsError::FileRead(format!(
+            "{} is too short to be a program file",
+            path.display()
+        )));
+    }
+
     let (header_bytes, bytes) = bytes.split_at(55);
     let (metadata_bytes, bytes) = match file_type {
         ProgramFileType::XP => bytes.split_at(19),
@@ -33,6 +42,13 @@ pub fn create_from_8xp(
             ))
         }
     };
+    if bytes.len() < 2 {
+        return Err(TiToolsError::FileRead(format!(
+            "{} is too short to be a program file",
+            path.display()
+        )));
+    }
+
     let (body_bytes, checksum_bytes) = bytes.split_at(bytes.len() - 2);
 
     // header translation
@@ -145,8 +161,11 @@ pub fn create_from_8xp(
         }
     };
 
-    // body translation
-    let translation = decode(body_bytes, &tokens, "en", display_mode)?;
+    // body translation, compiled assembly is machine code that only reads as garbage tokens
+    let translation = match Assembly::detect(body_bytes) {
+        Some(assembly) if assembly.form == AssemblyForm::Compiled => assembly.to_hex(),
+        _ => decode(body_bytes, &tokens, "en", display_mode)?,
+    };
 
     let body = Body::new(body_bytes.to_vec(), translation);
 
@@ -1,7 +1,9 @@
 use super::encode::encode;
 use crate::calculator::errors::TiToolsError;
 use crate::calculator::models::{Model, ModelDetails};
-use crate::calculator::program::{Body, Checksum, Destination, FileType, Header, Metadata};
+use crate::calculator::program::{
+    Body, Checksum, Destination, FileType, Header, Metadata, TXT_HEADER_LINES,
+};
 use crate::calculator::tokens::{load_tokens, OsVersion};
 use crate::calculator::{DisplayMode, EncodeMode};
 use std::path::PathBuf;
@@ -49,6 +51,33 @@ pub fn create_from_txt(
 
     let tokens = load_tokens(&version)?;
 
+    let body_string = file_string
+        .lines()
+        .skip(TXT_HEADER_LINES)
+        .collect::<Vec<&str>>()
+        .join("\n");
+
+    let body_bytes = encode(&body_string, &tokens, true, display_mode, encode_mode)?;
+
+    create_from_body(
+        name,
+        file_type,
+        destination,
+        model_details,
+        body_bytes,
+        body_string,
+    )
+}
+
+/// Builds the header, metadata and checksum of a new program around its body
+pub(crate) fn create_from_body(
+    name: &str,
+    file_type: FileType,
+    destination: Destination,
+    model_details: ModelDetails,
+    body_bytes: Vec<u8>,
+    translation: String,
+) -> Result<(Header, Metadata, Body, Checksum, ModelDetails), TiToolsError> {
     if name.len() > 8 {
         return Err(TiToolsError::InvalidNameLength);
     }
@@ -57,14 +86,6 @@ pub fn create_from_txt(
         return Err(TiToolsError::InvalidNameCharacters);
     }
 
-    let body_string = file_string
-        .lines()
-        .skip(6)
-        .collect::<Vec<&str>>()
-        .join("\n");
-
-    let body_bytes = encode(&body_string, &tokens, true, display_mode, encode_mode)?;
-
     let mut header_bytes = Vec::new();
     let signature = &model_details.signature.to_string();
     header_bytes.extend(signature.as_bytes());
@@ -124,7 +145,7 @@ pub fn create_from_txt(
 
     let checksum = Checksum::new(checksum.to_vec(), u16::from_le_bytes(checksum));
 
-    let body = Body::new(body_bytes, body_string);
+    let body = Body::new(body_bytes, translation);
 
     Ok((header, metadata, body, checksum, model_details))
 }
@@ -2,3 +2,6 @@ mod decode;
 mod encode;
 pub mod from_8xp;
 pub mod from_txt;
+
+pub use decode::{decode, decode_program, decode_tokens, load_program_tokens, DecodedToken};
+pub(crate) use encode::encode;
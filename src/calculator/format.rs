@@ -0,0 +1,115 @@
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::file::{decode_program, encode, load_program_tokens, DecodedToken};
+use crate::calculator::program::Program;
+use crate::calculator::tokens::bytes::{
+    COLON, ELSE, END, FOR, IF, NEWLINE, QUOTE, REPEAT, STORE, THEN, WHILE,
+};
+use crate::calculator::{DisplayMode, EncodeMode};
+use std::path::Path;
+
+/// Formats a txt program, spelling every token the way `display_mode` does and optionally
+/// indenting blocks with tabs, which are ignored when encoding.
+///
+/// The comment line is kept as written since it is not used when encoding.
+/// Fails instead of returning text that would encode to different bytes.
+pub fn format_txt(
+    path: &Path,
+    display_mode: &DisplayMode,
+    encode_mode: &EncodeMode,
+    indent: bool,
+) -> Result<String, TiToolsError> {
+    let program = Program::load_from_txt(path.to_path_buf(), encode_mode)?;
+
+    let source = std::fs::read_to_string(path)
+        .map_err(|err| TiToolsError::FailedToReadFile(err.to_string()))?;
+    let comment = source.lines().nth(1).unwrap_or_default().trim_end();
+
+    let tokens = decode_program(&program, display_mode)?;
+    let body = format_body(&tokens, indent);
+
+    let map = load_program_tokens(&program)?;
+    let encoded = encode(&body, &map, true, display_mode.clone(), encode_mode)?;
+
+    if encoded != program.body.bytes {
+        return Err(TiToolsError::FormattingChangesProgram(
+            path.display().to_string(),
+        ));
+    }
+
+    Ok(format!(
+        "{}\n{}",
+        program.txt_header(comment, display_mode),
+        body
+    ))
+}
+
+fn format_body(tokens: &[DecodedToken], indent: bool) -> String {
+    let mut lines: Vec<Vec<&DecodedToken>> = vec![Vec::new()];
+
+    for token in tokens {
+        if token.bytes == [NEWLINE] {
+            lines.push(Vec::new());
+        } else if let Some(line) = lines.last_mut() {
+            line.push(token);
+        }
+    }
+
+    let mut depth: usize = 0;
+    let mut previous_command = None;
+    let mut formatted_lines = Vec::new();
+
+    for line in lines {
+        let text = line
+            .iter()
+            .map(|token| token.text.as_str())
+            .collect::<String>();
+        let commands = statement_commands(&line);
+
+        let line_depth = match commands.first().copied() {
+            Some(END) | Some(ELSE) => depth.saturating_sub(1),
+            _ => depth,
+        };
+
+        if indent && !text.is_empty() {
+            formatted_lines.push(format!("{}{}", "\t".repeat(line_depth), text));
+        } else {
+            formatted_lines.push(text);
+        }
+
+        for command in commands {
+            match command {
+                THEN if previous_command == Some(IF) => depth += 1,
+                WHILE | REPEAT | FOR => depth += 1,
+                END => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+
+            previous_command = Some(command);
+        }
+    }
+
+    formatted_lines.join("\n")
+}
+
+/// The first byte of every statement on a line, statements are separated by colons outside of strings
+fn statement_commands(line: &[&DecodedToken]) -> Vec<u8> {
+    let mut commands = Vec::new();
+    let mut statement_start = true;
+    let mut in_string = false;
+
+    for token in line {
+        if statement_start {
+            commands.push(token.bytes[0]);
+            statement_start = false;
+        }
+
+        match token.bytes.as_slice() {
+            [QUOTE] => in_string = !in_string,
+            [STORE] => in_string = false,
+            [COLON] if !in_string => statement_start = true,
+            _ => {}
+        }
+    }
+
+    commands
+}
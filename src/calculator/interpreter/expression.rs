@@ -0,0 +1,761 @@
+use crate::calculator::file::DecodedToken;
+use crate::calculator::interpreter::value::Value;
+use crate::calculator::interpreter::State;
+use crate::calculator::tokens::bytes::{
+    AND, ANS, CLOSE_BRACE, CLOSE_PARENTHESIS, COMBINATIONS, COMMA, CUSTOM_LIST, DECIMAL_POINT,
+    DIVIDE, EQUAL, EULER, EXPONENT, FACTORIAL, GET_KEY, GREATER, GREATER_OR_EQUAL, INVERSE, LESS,
+    LESS_OR_EQUAL, LIST, MINUS, MULTIPLY, NEGATIVE, NOT, NOT_EQUAL, OPEN_BRACE, OPEN_PARENTHESIS,
+    OR, PERMUTATIONS, PI, PLUS, POWER, QUOTE, RAND, SPACE, SQUARE, STRING, THETA, XOR,
+};
+use crate::calculator::xref::VariableKind;
+
+pub(crate) type Eval<T> = Result<T, String>;
+
+/// A variable named in an expression, like `A`, `L₁`, `ʟABC` or `Str1`
+pub(crate) struct Variable {
+    pub(crate) name: String,
+    pub(crate) kind: VariableKind,
+    pub(crate) length: usize,
+}
+
+/// Finds the variable that starts at `index`, matrices are not supported
+pub(crate) fn variable_at(tokens: &[DecodedToken], index: usize) -> Option<Variable> {
+    let token = tokens.get(index)?;
+
+    let (kind, length) = match token.bytes.as_slice() {
+        [0x41..=THETA] => (VariableKind::Real, 1),
+        [LIST, _] => (VariableKind::List, 1),
+        [STRING, _] => (VariableKind::String, 1),
+        [CUSTOM_LIST] => {
+            let length = tokens[index + 1..]
+                .iter()
+                .take(5)
+                .take_while(|token| matches!(token.bytes.as_slice(), [0x30..=0x39 | 0x41..=THETA]))
+                .count();
+
+            (VariableKind::List, 1 + length)
+        }
+        _ => return None,
+    };
+
+    Some(Variable {
+        name: tokens[index..index + length]
+            .iter()
+            .map(|token| token.text.as_str())
+            .collect(),
+        kind,
+        length,
+    })
+}
+
+#[derive(Clone, Copy)]
+enum Function {
+    Round,
+    Max,
+    Min,
+    Mean,
+    Augment,
+    Int,
+    Abs,
+    Dim,
+    Sum,
+    Not,
+    IPart,
+    FPart,
+    Sqrt,
+    Ln,
+    EPower,
+    Log,
+    TenPower,
+    Sin,
+    Cos,
+    Tan,
+    Lcm,
+    Gcd,
+    RandInt,
+    Sub,
+    InString,
+    CumSum,
+    Expr,
+    Length,
+    Remainder,
+    ToString,
+}
+
+fn function_at(bytes: &[u8]) -> Option<Function> {
+    let function = match bytes {
+        [0x12] => Function::Round,
+        [0x19] => Function::Max,
+        [0x1A] => Function::Min,
+        [0x21] => Function::Mean,
+        [0x14] => Function::Augment,
+        [0xB1] => Function::Int,
+        [0xB2] => Function::Abs,
+        [0xB5] => Function::Dim,
+        [0xB6] => Function::Sum,
+        [NOT] => Function::Not,
+        [0xB9] => Function::IPart,
+        [0xBA] => Function::FPart,
+        [0xBC] => Function::Sqrt,
+        [0xBE] => Function::Ln,
+        [0xBF] => Function::EPower,
+        [0xC0] => Function::Log,
+        [0xC1] => Function::TenPower,
+        [0xC2] => Function::Sin,
+        [0xC4] => Function::Cos,
+        [0xC6] => Function::Tan,
+        [0xBB, 0x08] => Function::Lcm,
+        [0xBB, 0x09] => Function::Gcd,
+        [0xBB, 0x0A] => Function::RandInt,
+        [0xBB, 0x0C] => Function::Sub,
+        [0xBB, 0x0F] => Function::InString,
+        [0xBB, 0x29] => Function::CumSum,
+        [0xBB, 0x2A] => Function::Expr,
+        [0xBB, 0x2B] => Function::Length,
+        [0xEF, 0x32] => Function::Remainder,
+        [0xEF, 0x97] => Function::ToString,
+        _ => return None,
+    };
+
+    Some(function)
+}
+
+/// Evaluates the tokens of a single expression with operator precedence,
+/// closing parentheses, braces and quotes at the end are optional
+pub(crate) struct Parser<'a> {
+    tokens: &'a [DecodedToken],
+    position: usize,
+    state: &'a mut State,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(tokens: &'a [DecodedToken], state: &'a mut State) -> Parser<'a> {
+        Parser {
+            tokens,
+            position: 0,
+            state,
+        }
+    }
+
+    /// Evaluates every token, failing if any are left over
+    pub(crate) fn evaluate(mut self) -> Eval<Value> {
+        let value = self.expression()?;
+
+        // the calculator ignores spaces left after an expression
+        while self.next_is(SPACE) {
+            self.position += 1;
+        }
+
+        match self.tokens.get(self.position) {
+            Some(token) => Err(format!("SYNTAX: unexpected {}", token.text.trim())),
+            None => Ok(value),
+        }
+    }
+
+    fn peek(&self) -> Option<&[u8]> {
+        self.tokens
+            .get(self.position)
+            .map(|token| token.bytes.as_slice())
+    }
+
+    fn next_is(&self, byte: u8) -> bool {
+        self.peek() == Some(&[byte])
+    }
+
+    fn close(&mut self, byte: u8) -> Eval<()> {
+        match self.tokens.get(self.position) {
+            None => Ok(()),
+            Some(token) if token.bytes == [byte] => {
+                self.position += 1;
+                Ok(())
+            }
+            Some(token) => Err(format!("SYNTAX: unexpected {}", token.text.trim())),
+        }
+    }
+
+    fn expression(&mut self) -> Eval<Value> {
+        let mut value = self.and_expression()?;
+
+        while self.next_is(OR) || self.next_is(XOR) {
+            let exclusive = self.next_is(XOR);
+            self.position += 1;
+            let right = self.and_expression()?;
+
+            value = value.combine(&right, |left, right| {
+                let result = if exclusive {
+                    (left != 0.0) != (right != 0.0)
+                } else {
+                    left != 0.0 || right != 0.0
+                };
+
+                Ok(result as u8 as f64)
+            })?;
+        }
+
+        Ok(value)
+    }
+
+    fn and_expression(&mut self) -> Eval<Value> {
+        let mut value = self.relational()?;
+
+        while self.next_is(AND) {
+            self.position += 1;
+            let right = self.relational()?;
+
+            value = value.combine(&right, |left, right| {
+                Ok((left != 0.0 && right != 0.0) as u8 as f64)
+            })?;
+        }
+
+        Ok(value)
+    }
+
+    fn relational(&mut self) -> Eval<Value> {
+        let mut value = self.additive()?;
+
+        while let Some(
+            &[operator @ (EQUAL | NOT_EQUAL | LESS | GREATER | LESS_OR_EQUAL | GREATER_OR_EQUAL)],
+        ) = self.peek()
+        {
+            self.position += 1;
+            let right = self.additive()?;
+
+            value = match (&value, &right) {
+                (Value::String(left), Value::String(right)) => match operator {
+                    EQUAL => Value::Number((left == right) as u8 as f64),
+                    NOT_EQUAL => Value::Number((left != right) as u8 as f64),
+                    _ => {
+                        return Err(
+                            "DATA TYPE: strings can only be compared with = and ≠".to_string()
+                        )
+                    }
+                },
+                _ => value.combine(&right, |left, right| {
+                    let result = match operator {
+                        EQUAL => left == right,
+                        NOT_EQUAL => left != right,
+                        LESS => left < right,
+                        GREATER => left > right,
+                        LESS_OR_EQUAL => left <= right,
+                        _ => left >= right,
+                    };
+
+                    Ok(result as u8 as f64)
+                })?,
+            };
+        }
+
+        Ok(value)
+    }
+
+    fn additive(&mut self) -> Eval<Value> {
+        let mut value = self.multiplicative()?;
+
+        while self.next_is(PLUS) || self.next_is(MINUS) {
+            let subtract = self.next_is(MINUS);
+            self.position += 1;
+            let right = self.multiplicative()?;
+
+            value = match (&value, &right, subtract) {
+                (Value::String(left), Value::String(right), false) => {
+                    Value::String(format!("{}{}", left, right))
+                }
+                _ if subtract => value.combine(&right, |left, right| Ok(left - right))?,
+                _ => value.combine(&right, |left, right| Ok(left + right))?,
+            };
+        }
+
+        Ok(value)
+    }
+
+    fn multiplicative(&mut self) -> Eval<Value> {
+        let mut value = self.negation()?;
+
+        loop {
+            let operator = match self.peek() {
+                Some(&[operator @ (MULTIPLY | DIVIDE | PERMUTATIONS | COMBINATIONS)]) => {
+                    self.position += 1;
+                    operator
+                }
+                // a value directly followed by another one is multiplied, like 2A or 3(B+1)
+                Some(bytes) if starts_value(bytes) => MULTIPLY,
+                _ => break,
+            };
+
+            let right = self.negation()?;
+
+            value = value.combine(&right, |left, right| match operator {
+                MULTIPLY => Ok(left * right),
+                DIVIDE if right == 0.0 => Err("DIVIDE BY 0".to_string()),
+                DIVIDE => Ok(left / right),
+                PERMUTATIONS => Ok(factorial(left)? / factorial(left - right)?),
+                _ => Ok(factorial(left)? / (factorial(right)? * factorial(left - right)?)),
+            })?;
+        }
+
+        Ok(value)
+    }
+
+    /// Negation binds looser than powers, so ⁻2² is ⁻4
+    fn negation(&mut self) -> Eval<Value> {
+        if self.next_is(NEGATIVE) {
+            self.position += 1;
+            return self.negation()?.map(|number| Ok(-number));
+        }
+
+        self.power()
+    }
+
+    fn power(&mut self) -> Eval<Value> {
+        let mut value = self.postfix()?;
+
+        while self.next_is(POWER) {
+            self.position += 1;
+
+            let negative = self.next_is(NEGATIVE);
+            if negative {
+                self.position += 1;
+            }
+
+            let mut exponent = self.postfix()?;
+            if negative {
+                exponent = exponent.map(|number| Ok(-number))?;
+            }
+
+            value = value.combine(&exponent, power)?;
+        }
+
+        Ok(value)
+    }
+
+    fn postfix(&mut self) -> Eval<Value> {
+        let mut value = self.primary()?;
+
+        loop {
+            value = match self.peek() {
+                Some(&[SQUARE]) => value.map(|number| Ok(number * number))?,
+                Some(&[INVERSE]) => value.map(|number| {
+                    if number == 0.0 {
+                        Err("DIVIDE BY 0".to_string())
+                    } else {
+                        Ok(1.0 / number)
+                    }
+                })?,
+                Some(&[FACTORIAL]) => value.map(factorial)?,
+                _ => break,
+            };
+
+            self.position += 1;
+        }
+
+        Ok(value)
+    }
+
+    fn primary(&mut self) -> Eval<Value> {
+        let token = match self.tokens.get(self.position) {
+            Some(token) => token,
+            None => return Err("SYNTAX: missing a value".to_string()),
+        };
+
+        if let Some(variable) = variable_at(self.tokens, self.position) {
+            self.position += variable.length;
+            let value = self.state.get_variable(&variable.name, variable.kind)?;
+
+            // L₁(2) is the second element of L₁
+            if variable.kind == VariableKind::List && self.next_is(OPEN_PARENTHESIS) {
+                self.position += 1;
+                let index = self.expression()?.as_number()?;
+                self.close(CLOSE_PARENTHESIS)?;
+
+                return list_element(value.as_list()?, index).map(Value::Number);
+            }
+
+            return Ok(value);
+        }
+
+        if let Some(function) = function_at(&token.bytes) {
+            self.position += 1;
+            let arguments = self.arguments()?;
+
+            return self.call(function, arguments);
+        }
+
+        match token.bytes.as_slice() {
+            [0x30..=0x39] | [DECIMAL_POINT] | [EXPONENT] => self.number(),
+            [QUOTE] => {
+                self.position += 1;
+                let start = self.position;
+
+                while self.position < self.tokens.len() && !self.next_is(QUOTE) {
+                    self.position += 1;
+                }
+
+                let string = self.tokens[start..self.position]
+                    .iter()
+                    .map(|token| token.text.as_str())
+                    .collect();
+
+                self.close(QUOTE)?;
+
+                Ok(Value::String(string))
+            }
+            [OPEN_PARENTHESIS] => {
+                self.position += 1;
+                let value = self.expression()?;
+                self.close(CLOSE_PARENTHESIS)?;
+
+                Ok(value)
+            }
+            [OPEN_BRACE] => {
+                self.position += 1;
+                let mut list = Vec::new();
+
+                loop {
+                    list.push(self.expression()?.as_number()?);
+
+                    if self.next_is(COMMA) {
+                        self.position += 1;
+                    } else {
+                        self.close(CLOSE_BRACE)?;
+                        break;
+                    }
+                }
+
+                Ok(Value::List(list))
+            }
+            [ANS] => {
+                self.position += 1;
+                Ok(self.state.ans.clone())
+            }
+            [PI] => {
+                self.position += 1;
+                Ok(Value::Number(std::f64::consts::PI))
+            }
+            [RAND] => {
+                self.position += 1;
+                Ok(Value::Number(self.state.random()))
+            }
+            [GET_KEY] => {
+                self.position += 1;
+                Ok(Value::Number(
+                    self.state.keys.pop_front().unwrap_or(0) as f64
+                ))
+            }
+            bytes if bytes == EULER => {
+                self.position += 1;
+                Ok(Value::Number(std::f64::consts::E))
+            }
+            // operators in place of a value, like a subtraction at the start
+            [PLUS | MINUS | MULTIPLY | DIVIDE | POWER | EQUAL | NOT_EQUAL | LESS | GREATER
+            | LESS_OR_EQUAL | GREATER_OR_EQUAL | AND | OR | XOR | COMMA | CLOSE_PARENTHESIS
+            | CLOSE_BRACE] => Err(format!("SYNTAX: unexpected {}", token.text.trim())),
+            _ => Err(format!("SYNTAX: {} is not supported", token.text.trim())),
+        }
+    }
+
+    fn number(&mut self) -> Eval<Value> {
+        let mut literal = String::new();
+
+        while let Some(&[byte @ (0x30..=0x39 | DECIMAL_POINT)]) = self.peek() {
+            literal.push(if byte == DECIMAL_POINT {
+                '.'
+            } else {
+                byte as char
+            });
+            self.position += 1;
+        }
+
+        if literal.is_empty() {
+            literal.push('1');
+        }
+
+        if self.next_is(EXPONENT) {
+            self.position += 1;
+            literal.push('e');
+
+            if self.next_is(NEGATIVE) {
+                self.position += 1;
+                literal.push('-');
+            }
+
+            while let Some(&[byte @ 0x30..=0x39]) = self.peek() {
+                literal.push(byte as char);
+                self.position += 1;
+            }
+        }
+
+        literal
+            .parse::<f64>()
+            .map(Value::Number)
+            .map_err(|_| format!("SYNTAX: {} is not a number", literal))
+    }
+
+    fn arguments(&mut self) -> Eval<Vec<Value>> {
+        let mut arguments = Vec::new();
+
+        loop {
+            arguments.push(self.expression()?);
+
+            if self.next_is(COMMA) {
+                self.position += 1;
+            } else {
+                self.close(CLOSE_PARENTHESIS)?;
+                return Ok(arguments);
+            }
+        }
+    }
+
+    fn call(&mut self, function: Function, arguments: Vec<Value>) -> Eval<Value> {
+        let argument = |index: usize| -> Eval<&Value> {
+            arguments
+                .get(index)
+                .ok_or_else(|| "ARGUMENT: missing an argument".to_string())
+        };
+
+        let value = match function {
+            Function::Abs => argument(0)?.map(|number| Ok(number.abs()))?,
+            Function::Int => argument(0)?.map(|number| Ok(number.floor()))?,
+            Function::IPart => argument(0)?.map(|number| Ok(number.trunc()))?,
+            Function::FPart => argument(0)?.map(|number| Ok(number.fract()))?,
+            Function::Not => argument(0)?.map(|number| Ok((number == 0.0) as u8 as f64))?,
+            Function::Sqrt => argument(0)?.map(|number| {
+                if number < 0.0 {
+                    Err("NONREAL ANSWER".to_string())
+                } else {
+                    Ok(number.sqrt())
+                }
+            })?,
+            Function::Ln => argument(0)?.map(|number| logarithm(number, f64::ln))?,
+            Function::Log => argument(0)?.map(|number| logarithm(number, f64::log10))?,
+            Function::EPower => argument(0)?.map(|number| Ok(number.exp()))?,
+            Function::TenPower => argument(0)?.map(|number| Ok(10f64.powf(number)))?,
+            Function::Sin => argument(0)?.map(|number| Ok(number.sin()))?,
+            Function::Cos => argument(0)?.map(|number| Ok(number.cos()))?,
+            Function::Tan => argument(0)?.map(|number| Ok(number.tan()))?,
+            Function::Round => {
+                let digits = match arguments.get(1) {
+                    Some(digits) => digits.as_number()?,
+                    None => 9.0,
+                };
+                let scale = 10f64.powi(digits as i32);
+
+                argument(0)?.map(|number| Ok((number * scale).round() / scale))?
+            }
+            Function::Min | Function::Max => {
+                let numbers = match arguments.as_slice() {
+                    [Value::List(list)] => list.clone(),
+                    [left, right] => {
+                        return left.combine(right, |left, right| {
+                            Ok(match function {
+                                Function::Min => left.min(right),
+                                _ => left.max(right),
+                            })
+                        })
+                    }
+                    _ => return Err("ARGUMENT: expected a list or two values".to_string()),
+                };
+
+                let result = numbers
+                    .iter()
+                    .copied()
+                    .reduce(|left, right| match function {
+                        Function::Min => left.min(right),
+                        _ => left.max(right),
+                    });
+
+                Value::Number(result.ok_or("INVALID DIM: the list is empty")?)
+            }
+            Function::Sum => Value::Number(argument(0)?.as_list()?.iter().sum()),
+            Function::Mean => {
+                let list = argument(0)?.as_list()?;
+
+                if list.is_empty() {
+                    return Err("INVALID DIM: the list is empty".to_string());
+                }
+
+                Value::Number(list.iter().sum::<f64>() / list.len() as f64)
+            }
+            Function::CumSum => {
+                let mut total = 0.0;
+
+                Value::List(
+                    argument(0)?
+                        .as_list()?
+                        .iter()
+                        .map(|number| {
+                            total += number;
+                            total
+                        })
+                        .collect(),
+                )
+            }
+            Function::Augment => {
+                let mut list = argument(0)?.as_list()?.clone();
+                list.extend(argument(1)?.as_list()?);
+
+                Value::List(list)
+            }
+            Function::Dim => Value::Number(argument(0)?.as_list()?.len() as f64),
+            Function::Length => Value::Number(argument(0)?.as_string()?.chars().count() as f64),
+            Function::Remainder => argument(0)?.combine(argument(1)?, |left, right| {
+                if right == 0.0 {
+                    Err("DIVIDE BY 0".to_string())
+                } else {
+                    Ok(left % right)
+                }
+            })?,
+            Function::Gcd => {
+                argument(0)?.combine(argument(1)?, |left, right| Ok(gcd(left, right)))?
+            }
+            Function::Lcm => argument(0)?.combine(argument(1)?, |left, right| {
+                let divisor = gcd(left, right);
+
+                if divisor == 0.0 {
+                    Ok(0.0)
+                } else {
+                    Ok((left * right / divisor).abs())
+                }
+            })?,
+            Function::RandInt => {
+                let low = argument(0)?.as_number()?;
+                let high = argument(1)?.as_number()?;
+                let (low, high) = (low.min(high), low.max(high));
+                let mut draw = || low + (self.state.random() * (high - low + 1.0)).floor();
+
+                match arguments.get(2) {
+                    Some(count) => {
+                        let count = list_length(count.as_number()?)?;
+                        Value::List((0..count).map(|_| draw()).collect())
+                    }
+                    None => Value::Number(draw()),
+                }
+            }
+            Function::Sub => {
+                let string: Vec<char> = argument(0)?.as_string()?.chars().collect();
+                let start = argument(1)?.as_number()? as usize;
+                let length = argument(2)?.as_number()? as usize;
+
+                if start == 0
+                    || start
+                        .checked_add(length)
+                        .is_none_or(|end| end > string.len() + 1)
+                {
+                    return Err("DOMAIN: sub( is outside of the string".to_string());
+                }
+
+                Value::String(string[start - 1..start - 1 + length].iter().collect())
+            }
+            Function::InString => {
+                let string: Vec<char> = argument(0)?.as_string()?.chars().collect();
+                let search: Vec<char> = argument(1)?.as_string()?.chars().collect();
+                let start = match arguments.get(2) {
+                    Some(start) => (start.as_number()? as usize).max(1),
+                    None => 1,
+                };
+
+                let position = (start - 1..string.len())
+                    .find(|&index| string[index..].starts_with(&search))
+                    .map(|index| index + 1)
+                    .unwrap_or(0);
+
+                Value::Number(position as f64)
+            }
+            Function::ToString => Value::String(argument(0)?.to_string()),
+            Function::Expr => {
+                let text = argument(0)?.as_string()?.to_string();
+                self.state.evaluate_text(&text)?
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+/// Tokens that can start a value, used to spot implied multiplication
+fn starts_value(bytes: &[u8]) -> bool {
+    matches!(
+        bytes,
+        [0x30..=0x39]
+            | [DECIMAL_POINT | EXPONENT | 0x41..=THETA | QUOTE | OPEN_PARENTHESIS | OPEN_BRACE]
+            | [LIST | STRING, _]
+            | [CUSTOM_LIST | ANS | PI | RAND | GET_KEY]
+    ) || bytes == EULER
+        || function_at(bytes).is_some()
+}
+
+/// The number of elements a list is made with, the calculator holds at most 999
+pub(crate) fn list_length(length: f64) -> Eval<usize> {
+    if length < 0.0 || length.fract() != 0.0 || length.is_nan() {
+        return Err(format!("DOMAIN: {} is not a list length", length));
+    }
+
+    if length > 999.0 {
+        return Err(format!(
+            "INVALID DIM: {} elements, lists hold at most 999",
+            length
+        ));
+    }
+
+    Ok(length as usize)
+}
+
+/// Elements are numbered from 1, fractions and numbers that overflowed are no index at all
+pub(crate) fn list_index(index: f64) -> Eval<usize> {
+    if !index.is_finite() || index.fract() != 0.0 {
+        return Err(format!("DOMAIN: {} is not a list index", index));
+    }
+
+    Ok(index.max(0.0) as usize)
+}
+
+fn list_element(list: &[f64], index: f64) -> Eval<f64> {
+    let index = list_index(index)?;
+
+    if index == 0 || index > list.len() {
+        return Err(format!(
+            "INVALID DIM: element {} of a list with {} elements",
+            index,
+            list.len()
+        ));
+    }
+
+    Ok(list[index - 1])
+}
+
+fn power(base: f64, exponent: f64) -> Eval<f64> {
+    if base == 0.0 && exponent <= 0.0 {
+        return Err("DOMAIN: 0 to a power that is not positive".to_string());
+    }
+
+    if base < 0.0 && exponent.fract() != 0.0 {
+        return Err("NONREAL ANSWER".to_string());
+    }
+
+    Ok(base.powf(exponent))
+}
+
+fn logarithm(number: f64, function: fn(f64) -> f64) -> Eval<f64> {
+    if number <= 0.0 {
+        Err("NONREAL ANSWER".to_string())
+    } else {
+        Ok(function(number))
+    }
+}
+
+fn factorial(number: f64) -> Eval<f64> {
+    if number < 0.0 || number.fract() != 0.0 || number > 69.0 {
+        return Err("DOMAIN: factorials need a whole number from 0 to 69".to_string());
+    }
+
+    Ok((1..=number as u64).map(|factor| factor as f64).product())
+}
+
+fn gcd(left: f64, right: f64) -> f64 {
+    let (mut left, mut right) = (left.abs().trunc(), right.abs().trunc());
+
+    while right != 0.0 {
+        (left, right) = (right, left % right);
+    }
+
+    left
+}
@@ -0,0 +1,759 @@
+//! Runs TI-BASIC programs without a calculator, with `Input`, `Prompt` and `getKey` fed from a script
+
+mod expression;
+mod screen;
+mod value;
+
+pub use value::{format_number, Value};
+
+use crate::calculator::assembly::AssemblyTarget;
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::file::{
+    decode_program, decode_tokens, encode, load_program_tokens, DecodedToken,
+};
+use crate::calculator::interpreter::expression::{
+    list_index, list_length, variable_at, Eval, Parser, Variable,
+};
+use crate::calculator::interpreter::screen::HomeScreen;
+use crate::calculator::lint::{name_of, split_statements, Statement};
+use crate::calculator::program::Program;
+use crate::calculator::tokens::bytes::{
+    CLOSE_BRACE, CLOSE_PARENTHESIS, CLR_HOME, COMMA, DEL_VAR, DIM, DISP, DS_LESS, ELSE, END, FOR,
+    GOTO, IF, INPUT, IS_GREATER, LBL, MENU, OPEN_PARENTHESIS, OUTPUT, PAUSE, PRGM, PROMPT, QUOTE,
+    REPEAT, RETURN, STOP, STORE, THEN, WHILE,
+};
+use crate::calculator::tokens::Map;
+use crate::calculator::xref::VariableKind;
+use crate::calculator::{DisplayMode, EncodeMode};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+
+/// A string can run `expr(` on itself, so the strings evaluated inside each other are limited
+const MAX_TEXT_DEPTH: usize = 32;
+
+pub struct RunOptions {
+    /// Lines answered to `Input` and `Prompt`, in order
+    pub inputs: Vec<String>,
+    /// Key codes returned by `getKey`, it returns 0 once they run out
+    pub keys: Vec<u16>,
+    /// Statements to run before giving up, to stop programs that never end
+    pub max_steps: usize,
+    /// Variables set before the program starts, named like `A`, `L1` or `Str1`
+    pub variables: BTreeMap<String, Value>,
+}
+
+/// An error that stopped a program, lines start at 1 with the first line of the program body
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuntimeError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ERR:{} on line {}", self.message, self.line)
+    }
+}
+
+/// What a program did while it ran
+#[derive(Debug, Serialize)]
+pub struct Execution {
+    /// Every line written by `Disp` and `Pause`, and the questions asked by `Input` and `Prompt` with their answers
+    pub output: Vec<String>,
+    /// The home screen once the program stopped
+    pub screen: Vec<String>,
+    pub variables: BTreeMap<String, Value>,
+    pub steps: usize,
+    pub error: Option<RuntimeError>,
+}
+
+/// Variables and scripted input shared by statements and expressions
+pub(crate) struct State {
+    variables: BTreeMap<String, Value>,
+    ans: Value,
+    inputs: VecDeque<String>,
+    keys: VecDeque<u16>,
+    seed: u64,
+    map: Map,
+    /// How many strings are being evaluated inside each other, by `expr(` running `expr(`
+    text_depth: usize,
+}
+
+impl State {
+    fn get_variable(&self, name: &str, kind: VariableKind) -> Eval<Value> {
+        match self.variables.get(name) {
+            Some(value) => Ok(value.clone()),
+            // real variables always exist and start at 0
+            None if kind == VariableKind::Real => Ok(Value::Number(0.0)),
+            None => Err(format!("UNDEFINED: {} has no value", name)),
+        }
+    }
+
+    fn set_variable(&mut self, name: &str, kind: VariableKind, value: Value) -> Eval<()> {
+        let matches_kind = matches!(
+            (kind, &value),
+            (VariableKind::Real, Value::Number(_))
+                | (VariableKind::List, Value::List(_))
+                | (VariableKind::String, Value::String(_))
+        );
+
+        if !matches_kind {
+            return Err(format!("DATA TYPE: {} cannot hold {}", name, value));
+        }
+
+        self.variables.insert(name.to_string(), value);
+
+        Ok(())
+    }
+
+    /// A repeatable xorshift generator, so runs can be compared
+    fn random(&mut self) -> f64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+
+        (self.seed >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Evaluates typed text, like an answer to `Input` or the string given to `expr(`
+    fn evaluate_text(&mut self, text: &str) -> Eval<Value> {
+        if self.text_depth == MAX_TEXT_DEPTH {
+            return Err(format!(
+                "ILLEGAL NEST: more than {} strings evaluated inside each other",
+                MAX_TEXT_DEPTH
+            ));
+        }
+
+        let tokens = self.tokenize(text)?;
+
+        self.text_depth += 1;
+        let value = Parser::new(&tokens, self).evaluate();
+        self.text_depth -= 1;
+
+        value
+    }
+
+    fn tokenize(&self, text: &str) -> Eval<Vec<DecodedToken>> {
+        tokenize(&self.map, text, DisplayMode::Accessible)
+    }
+}
+
+fn tokenize(map: &Map, text: &str, display_mode: DisplayMode) -> Eval<Vec<DecodedToken>> {
+    let bytes = encode(
+        &text.to_string(),
+        map,
+        true,
+        display_mode,
+        &EncodeMode::Smart,
+    )
+    .map_err(|err| format!("SYNTAX: {}", err))?;
+
+    decode_tokens(&bytes, map, "en", &DisplayMode::Pretty).map_err(|err| format!("SYNTAX: {}", err))
+}
+
+/// Reads a typed variable name, giving the name it is stored under so `L1` and `L₁` are the same list
+pub(crate) fn parse_variable(map: &Map, name: &str) -> Option<Variable> {
+    [DisplayMode::Accessible, DisplayMode::Pretty]
+        .into_iter()
+        .filter_map(|display_mode| tokenize(map, name, display_mode).ok())
+        .find_map(|tokens| {
+            variable_at(&tokens, 0).filter(|variable| variable.length == tokens.len())
+        })
+}
+
+enum Frame {
+    If,
+    While(usize),
+    Repeat(usize),
+    For {
+        start: usize,
+        name: String,
+        end: f64,
+        step: f64,
+    },
+}
+
+enum Flow {
+    Next,
+    Jump(usize),
+    Stop,
+}
+
+/// Runs the body of a program until it ends, fails or takes more than `max_steps` statements
+pub fn run_program(program: &Program, options: RunOptions) -> Result<Execution, TiToolsError> {
+    let tokens = decode_program(program, &DisplayMode::Pretty)?;
+
+    // the color models, the CSE as well as the CE, have a bigger home screen
+    let (width, height) = match AssemblyTarget::for_model(&program.model.model) {
+        Ok(AssemblyTarget::Z80Color | AssemblyTarget::EZ80) => (26, 10),
+        _ => (16, 8),
+    };
+
+    let mut interpreter = Interpreter {
+        statements: split_statements(&tokens),
+        state: State {
+            variables: BTreeMap::new(),
+            ans: Value::Number(0.0),
+            inputs: options.inputs.into(),
+            keys: options.keys.into(),
+            seed: 0x2545_F491_4F6C_DD1D,
+            map: load_program_tokens(program)?,
+            text_depth: 0,
+        },
+        frames: Vec::new(),
+        screen: HomeScreen::new(width, height),
+        output: Vec::new(),
+        ends_with_expression: false,
+    };
+
+    for (name, value) in options.variables {
+        let result = match parse_variable(&interpreter.state.map, &name) {
+            Some(variable) => interpreter
+                .state
+                .set_variable(&variable.name, variable.kind, value),
+            None => Err("not a real, list or string variable".to_string()),
+        };
+
+        if let Err(err) = result {
+            return Err(TiToolsError::InvalidVariable(name, err));
+        }
+    }
+
+    let mut steps = 0;
+    let mut position = 0;
+    let mut error = None;
+
+    while position < interpreter.statements.len() {
+        if steps == options.max_steps {
+            error = Some(RuntimeError {
+                line: interpreter.statements[position].line,
+                message: format!("STOPPED: ran {} statements without ending", steps),
+            });
+            break;
+        }
+
+        steps += 1;
+
+        match interpreter.execute(position) {
+            Ok(Flow::Next) => position += 1,
+            Ok(Flow::Jump(next)) => position = next,
+            Ok(Flow::Stop) => break,
+            Err(message) => {
+                error = Some(RuntimeError {
+                    line: interpreter.statements[position].line,
+                    message,
+                });
+                break;
+            }
+        }
+    }
+
+    // a program ending on a bare expression leaves its value on the home screen
+    if error.is_none() && interpreter.ends_with_expression {
+        let ans = interpreter.state.ans.clone();
+        interpreter.display(&ans);
+    }
+
+    Ok(Execution {
+        output: interpreter.output,
+        screen: interpreter.screen.lines(),
+        variables: interpreter.state.variables,
+        steps,
+        error,
+    })
+}
+
+struct Interpreter<'a> {
+    statements: Vec<Statement<'a>>,
+    state: State,
+    frames: Vec<Frame>,
+    screen: HomeScreen,
+    output: Vec<String>,
+    ends_with_expression: bool,
+}
+
+impl<'a> Interpreter<'a> {
+    fn execute(&mut self, position: usize) -> Eval<Flow> {
+        let tokens: &'a [DecodedToken] = self.statements[position].tokens;
+        let command = self.statements[position].command();
+        self.ends_with_expression = false;
+
+        match command {
+            Some(IF) => {
+                let condition = self.evaluate(&tokens[1..])?.is_true()?;
+                let then = self
+                    .statements
+                    .get(position + 1)
+                    .is_some_and(|statement| statement.command() == Some(THEN));
+
+                if !then {
+                    return Ok(Flow::Jump(position + if condition { 1 } else { 2 }));
+                }
+
+                if condition {
+                    self.frames.push(Frame::If);
+                    return Ok(Flow::Jump(position + 2));
+                }
+
+                let skipped_to = self.skip_block(position + 2, true)?;
+                if self.statements[skipped_to].command() == Some(ELSE) {
+                    self.frames.push(Frame::If);
+                }
+
+                Ok(Flow::Jump(skipped_to + 1))
+            }
+            Some(THEN) => Err("SYNTAX: Then must directly follow an If".to_string()),
+            // reached at the end of the Then branch, so the Else branch is skipped
+            Some(ELSE) => {
+                self.frames.pop();
+                Ok(Flow::Jump(self.skip_block(position + 1, false)? + 1))
+            }
+            Some(END) => self.end_block(position),
+            Some(WHILE) => {
+                if self.evaluate(&tokens[1..])?.is_true()? {
+                    self.frames.push(Frame::While(position));
+                    Ok(Flow::Next)
+                } else {
+                    Ok(Flow::Jump(self.skip_block(position + 1, false)? + 1))
+                }
+            }
+            Some(REPEAT) => {
+                self.frames.push(Frame::Repeat(position));
+                Ok(Flow::Next)
+            }
+            Some(FOR) => self.start_for(position),
+            Some(LBL) => Ok(Flow::Next),
+            Some(GOTO) => {
+                let name = name_of(&tokens[1..]);
+
+                match self.statements.iter().position(|statement| {
+                    statement.command() == Some(LBL) && statement.argument() == name
+                }) {
+                    Some(label) => Ok(Flow::Jump(label + 1)),
+                    None => Err(format!("LABEL: Lbl {} does not exist", name)),
+                }
+            }
+            Some(RETURN | STOP) => Ok(Flow::Stop),
+            Some(DISP | PAUSE) => {
+                for argument in split_arguments(&tokens[1..]) {
+                    let value = self.evaluate(argument)?;
+                    self.display(&value);
+                }
+
+                Ok(Flow::Next)
+            }
+            Some(OUTPUT) => {
+                let arguments = split_arguments(&tokens[1..]);
+
+                if arguments.len() != 3 {
+                    return Err("ARGUMENT: Output( needs a row, a column and a value".to_string());
+                }
+
+                let row = self.evaluate(arguments[0])?.as_number()?;
+                let column = self.evaluate(arguments[1])?.as_number()?;
+                let value = self.evaluate(arguments[2])?;
+
+                self.screen
+                    .output(row as usize, column as usize, &value.to_string())?;
+
+                Ok(Flow::Next)
+            }
+            Some(CLR_HOME) => {
+                self.screen.clear();
+                Ok(Flow::Next)
+            }
+            Some(INPUT) => {
+                let arguments = split_arguments(&tokens[1..]);
+
+                let (question, target) = match arguments.as_slice() {
+                    [target] => ("?".to_string(), *target),
+                    [question, target] => {
+                        let question = self.evaluate(question)?;
+                        (question.as_string()?.to_string(), *target)
+                    }
+                    _ => return Err("ARGUMENT: Input needs a variable".to_string()),
+                };
+
+                self.ask(&question, target)?;
+
+                Ok(Flow::Next)
+            }
+            Some(PROMPT) => {
+                for target in split_arguments(&tokens[1..]) {
+                    let name: String = target.iter().map(|token| token.text.as_str()).collect();
+                    self.ask(&format!("{}=?", name), target)?;
+                }
+
+                Ok(Flow::Next)
+            }
+            Some(IS_GREATER | DS_LESS) => {
+                let arguments = split_arguments(&tokens[1..]);
+                let increment = command == Some(IS_GREATER);
+
+                let variable = match arguments
+                    .first()
+                    .and_then(|argument| variable_at(argument, 0))
+                {
+                    Some(variable) if variable.kind == VariableKind::Real => variable,
+                    _ => return Err("ARGUMENT: IS>( and DS<( need a real variable".to_string()),
+                };
+
+                let limit = match arguments.get(1) {
+                    Some(limit) => self.evaluate(limit)?.as_number()?,
+                    None => return Err("ARGUMENT: IS>( and DS<( need a value".to_string()),
+                };
+
+                let value = self
+                    .state
+                    .get_variable(&variable.name, variable.kind)?
+                    .as_number()?;
+                let value = if increment { value + 1.0 } else { value - 1.0 };
+
+                self.state
+                    .set_variable(&variable.name, variable.kind, Value::Number(value))?;
+
+                let skip = if increment {
+                    value > limit
+                } else {
+                    value < limit
+                };
+
+                Ok(Flow::Jump(position + if skip { 2 } else { 1 }))
+            }
+            Some(PRGM) => Err("UNSUPPORTED: calling other programs".to_string()),
+            Some(MENU) => Err("UNSUPPORTED: Menu(".to_string()),
+            _ if tokens.first().is_some_and(|token| token.bytes == DEL_VAR) => {
+                self.delete_variables(tokens)?;
+                Ok(Flow::Next)
+            }
+            _ => {
+                self.assign(tokens)?;
+                Ok(Flow::Next)
+            }
+        }
+    }
+
+    fn evaluate(&mut self, tokens: &[DecodedToken]) -> Eval<Value> {
+        Parser::new(tokens, &mut self.state).evaluate()
+    }
+
+    /// Strings are written on the left and everything else on the right, like on the calculator
+    fn display(&mut self, value: &Value) {
+        let text = value.to_string();
+
+        self.screen
+            .display(&text, !matches!(value, Value::String(_)));
+        self.output.push(text);
+    }
+
+    fn ask(&mut self, question: &str, target: &[DecodedToken]) -> Eval<()> {
+        let variable = match variable_at(target, 0) {
+            Some(variable) if variable.length == target.len() => variable,
+            _ => return Err("ARGUMENT: expected a variable to store the answer in".to_string()),
+        };
+
+        let answer = match self.state.inputs.pop_front() {
+            Some(answer) => answer,
+            None => return Err(format!("INPUT: no scripted answer left for {}", question)),
+        };
+
+        let line = format!("{}{}", question, answer);
+        self.screen.display(&line, false);
+        self.output.push(line);
+
+        let value = match variable.kind {
+            VariableKind::String => Value::String(answer),
+            _ => self.state.evaluate_text(&answer)?,
+        };
+
+        self.state
+            .set_variable(&variable.name, variable.kind, value)
+    }
+
+    /// `value→target`, or a lone value which only updates Ans
+    fn assign(&mut self, tokens: &[DecodedToken]) -> Eval<()> {
+        let mut in_string = false;
+        let store = tokens.iter().position(|token| {
+            match token.bytes.as_slice() {
+                [QUOTE] => in_string = !in_string,
+                [STORE] => return true,
+                _ => {}
+            }
+
+            false
+        });
+
+        let (expression, target) = match store {
+            Some(store) => (&tokens[..store], &tokens[store + 1..]),
+            None => (tokens, &[] as &[DecodedToken]),
+        };
+
+        if expression.is_empty() {
+            return Ok(());
+        }
+
+        let value = self.evaluate(expression)?;
+
+        if target.is_empty() {
+            self.ends_with_expression = true;
+        } else {
+            self.store(value.clone(), target)?;
+        }
+
+        self.state.ans = value;
+
+        Ok(())
+    }
+
+    fn store(&mut self, value: Value, target: &[DecodedToken]) -> Eval<()> {
+        // value→dim(L₁) resizes a list, filling new elements with 0
+        if target.first().is_some_and(|token| token.bytes == [DIM]) {
+            let list = match variable_at(target, 1) {
+                Some(list) if list.kind == VariableKind::List => list,
+                _ => return Err("SYNTAX: dim( needs a list".to_string()),
+            };
+
+            let mut elements = match self.state.get_variable(&list.name, list.kind) {
+                Ok(Value::List(elements)) => elements,
+                _ => Vec::new(),
+            };
+
+            elements.resize(list_length(value.as_number()?)?, 0.0);
+
+            return self
+                .state
+                .set_variable(&list.name, list.kind, Value::List(elements));
+        }
+
+        let variable = match variable_at(target, 0) {
+            Some(variable) => variable,
+            None => {
+                let target: String = target.iter().map(|token| token.text.as_str()).collect();
+                return Err(format!("SYNTAX: cannot store to {}", target));
+            }
+        };
+
+        let index_tokens = &target[variable.length..];
+
+        if index_tokens.is_empty() {
+            return self
+                .state
+                .set_variable(&variable.name, variable.kind, value);
+        }
+
+        // value→L₁(3) sets an element, one past the end adds it
+        let index = match index_tokens.first() {
+            Some(token)
+                if variable.kind == VariableKind::List && token.bytes == [OPEN_PARENTHESIS] =>
+            {
+                match split_arguments(&index_tokens[1..]).as_slice() {
+                    [index] => list_index(self.evaluate(index)?.as_number()?)?,
+                    _ => return Err("SYNTAX: a list element needs one index".to_string()),
+                }
+            }
+            _ => return Err("SYNTAX: unexpected tokens after the variable".to_string()),
+        };
+
+        let mut elements = match self.state.get_variable(&variable.name, variable.kind) {
+            Ok(Value::List(elements)) => elements,
+            _ => Vec::new(),
+        };
+
+        if index == 0 || index > elements.len() + 1 {
+            return Err(format!(
+                "INVALID DIM: element {} of a list with {} elements",
+                index,
+                elements.len()
+            ));
+        }
+
+        if index == elements.len() + 1 {
+            elements.push(value.as_number()?);
+        } else {
+            elements[index - 1] = value.as_number()?;
+        }
+
+        self.state
+            .set_variable(&variable.name, variable.kind, Value::List(elements))
+    }
+
+    /// `DelVar` can be chained, like `DelVar ADelVar B`
+    fn delete_variables(&mut self, tokens: &[DecodedToken]) -> Eval<()> {
+        let mut index = 0;
+
+        while tokens
+            .get(index)
+            .is_some_and(|token| token.bytes == DEL_VAR)
+        {
+            let variable = match variable_at(tokens, index + 1) {
+                Some(variable) => variable,
+                None => return Err("SYNTAX: DelVar needs a variable".to_string()),
+            };
+
+            self.state.variables.remove(&variable.name);
+            index += 1 + variable.length;
+        }
+
+        if index < tokens.len() {
+            return Err("UNSUPPORTED: a command after DelVar on the same statement".to_string());
+        }
+
+        Ok(())
+    }
+
+    fn start_for(&mut self, position: usize) -> Eval<Flow> {
+        let tokens: &'a [DecodedToken] = self.statements[position].tokens;
+        let arguments = split_arguments(&tokens[1..]);
+
+        let variable = match arguments
+            .first()
+            .and_then(|argument| variable_at(argument, 0))
+        {
+            Some(variable) if variable.kind == VariableKind::Real => variable,
+            _ => return Err("ARGUMENT: For( needs a real variable".to_string()),
+        };
+
+        if !(3..=4).contains(&arguments.len()) {
+            return Err(
+                "ARGUMENT: For( needs a variable, a start, an end and an optional step".to_string(),
+            );
+        }
+
+        let start = self.evaluate(arguments[1])?.as_number()?;
+        let end = self.evaluate(arguments[2])?.as_number()?;
+        let step = match arguments.get(3) {
+            Some(step) => self.evaluate(step)?.as_number()?,
+            None => 1.0,
+        };
+
+        if step == 0.0 {
+            return Err("DOMAIN: For( step cannot be 0".to_string());
+        }
+
+        self.state
+            .set_variable(&variable.name, variable.kind, Value::Number(start))?;
+
+        if (step > 0.0 && start > end) || (step < 0.0 && start < end) {
+            return Ok(Flow::Jump(self.skip_block(position + 1, false)? + 1));
+        }
+
+        self.frames.push(Frame::For {
+            start: position,
+            name: variable.name,
+            end,
+            step,
+        });
+
+        Ok(Flow::Next)
+    }
+
+    fn end_block(&mut self, position: usize) -> Eval<Flow> {
+        match self.frames.pop() {
+            Some(Frame::If) => Ok(Flow::Next),
+            Some(Frame::While(start)) => Ok(Flow::Jump(start)),
+            Some(Frame::Repeat(start)) => {
+                let tokens: &'a [DecodedToken] = self.statements[start].tokens;
+                let condition = self.evaluate(&tokens[1..])?;
+
+                if condition.is_true()? {
+                    Ok(Flow::Next)
+                } else {
+                    self.frames.push(Frame::Repeat(start));
+                    Ok(Flow::Jump(start + 1))
+                }
+            }
+            Some(Frame::For {
+                start,
+                name,
+                end,
+                step,
+            }) => {
+                let value = self
+                    .state
+                    .get_variable(&name, VariableKind::Real)?
+                    .as_number()?
+                    + step;
+
+                self.state
+                    .set_variable(&name, VariableKind::Real, Value::Number(value))?;
+
+                if (step > 0.0 && value <= end) || (step < 0.0 && value >= end) {
+                    self.frames.push(Frame::For {
+                        start,
+                        name,
+                        end,
+                        step,
+                    });
+                    Ok(Flow::Jump(start + 1))
+                } else {
+                    Ok(Flow::Next)
+                }
+            }
+            None => Err(format!(
+                "SYNTAX: End on line {} does not close any block",
+                self.statements[position].line
+            )),
+        }
+    }
+
+    /// Finds the End that closes the block starting at `start`, or the Else of it when `stop_at_else`
+    fn skip_block(&self, start: usize, stop_at_else: bool) -> Eval<usize> {
+        let mut depth = 0;
+
+        for index in start..self.statements.len() {
+            match self.statements[index].command() {
+                Some(WHILE | REPEAT | FOR) => depth += 1,
+                Some(THEN) if self.statements[index - 1].command() == Some(IF) => depth += 1,
+                Some(ELSE) if depth == 0 && stop_at_else => return Ok(index),
+                Some(END) if depth == 0 => return Ok(index),
+                Some(END) => depth -= 1,
+                _ => {}
+            }
+        }
+
+        Err("SYNTAX: a block is never closed with End".to_string())
+    }
+}
+
+/// Splits the arguments of a command on commas outside of strings, parentheses and braces,
+/// dropping the parenthesis that closes the command
+fn split_arguments(tokens: &[DecodedToken]) -> Vec<&[DecodedToken]> {
+    let mut arguments = Vec::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut start = 0;
+    let mut end = tokens.len();
+
+    for (index, token) in tokens.iter().enumerate() {
+        if token.bytes == [QUOTE] {
+            in_string = !in_string;
+            continue;
+        }
+
+        if in_string {
+            continue;
+        }
+
+        match token.bytes.as_slice() {
+            [COMMA] if depth == 0 => {
+                arguments.push(&tokens[start..index]);
+                start = index + 1;
+            }
+            [CLOSE_PARENTHESIS] if depth == 0 => {
+                end = index;
+                break;
+            }
+            [CLOSE_PARENTHESIS | CLOSE_BRACE] => depth -= 1,
+            // tokens like (, {, sin( and L₁( open a group
+            _ if token.text.ends_with(['(', '{']) => depth += 1,
+            _ => {}
+        }
+    }
+
+    if start < end {
+        arguments.push(&tokens[start..end]);
+    }
+
+    arguments
+}
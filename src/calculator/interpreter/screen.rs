@@ -0,0 +1,75 @@
+/// The home screen, written to by `Disp`, `Output(` and `Input`
+pub struct HomeScreen {
+    rows: Vec<Vec<char>>,
+    width: usize,
+    cursor_row: usize,
+}
+
+impl HomeScreen {
+    pub fn new(width: usize, height: usize) -> HomeScreen {
+        HomeScreen {
+            rows: vec![vec![' '; width]; height],
+            width,
+            cursor_row: 0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for row in &mut self.rows {
+            row.fill(' ');
+        }
+
+        self.cursor_row = 0;
+    }
+
+    /// Writes a line below the last one, scrolling when the screen is full
+    pub fn display(&mut self, text: &str, right_align: bool) {
+        if self.cursor_row == self.rows.len() {
+            self.rows.remove(0);
+            self.rows.push(vec![' '; self.width]);
+            self.cursor_row -= 1;
+        }
+
+        let characters: Vec<char> = text.chars().take(self.width).collect();
+        let start = if right_align {
+            self.width - characters.len()
+        } else {
+            0
+        };
+
+        let row = &mut self.rows[self.cursor_row];
+        row.fill(' ');
+        row[start..start + characters.len()].copy_from_slice(&characters);
+
+        self.cursor_row += 1;
+    }
+
+    /// Writes text from a 1 based position, wrapping onto the next rows and stopping at the bottom
+    pub fn output(&mut self, row: usize, column: usize, text: &str) -> Result<(), String> {
+        if row == 0 || row > self.rows.len() || column == 0 || column > self.width {
+            return Err(format!(
+                "DOMAIN: Output({},{} is outside of the {} by {} screen",
+                row,
+                column,
+                self.rows.len(),
+                self.width
+            ));
+        }
+
+        let start = (row - 1) * self.width + column - 1;
+        let end = self.rows.len() * self.width;
+
+        for (position, character) in (start..end).zip(text.chars()) {
+            self.rows[position / self.width][position % self.width] = character;
+        }
+
+        Ok(())
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.rows
+            .iter()
+            .map(|row| row.iter().collect::<String>().trim_end().to_string())
+            .collect()
+    }
+}
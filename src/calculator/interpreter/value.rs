@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A value a TI-BASIC expression can produce
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Number(f64),
+    String(String),
+    List(Vec<f64>),
+}
+
+impl Value {
+    pub fn as_number(&self) -> Result<f64, String> {
+        match self {
+            Value::Number(number) => Ok(*number),
+            _ => Err("DATA TYPE: expected a number".to_string()),
+        }
+    }
+
+    pub fn as_list(&self) -> Result<&Vec<f64>, String> {
+        match self {
+            Value::List(list) => Ok(list),
+            _ => Err("DATA TYPE: expected a list".to_string()),
+        }
+    }
+
+    pub fn as_string(&self) -> Result<&str, String> {
+        match self {
+            Value::String(string) => Ok(string),
+            _ => Err("DATA TYPE: expected a string".to_string()),
+        }
+    }
+
+    pub fn is_true(&self) -> Result<bool, String> {
+        Ok(self.as_number()? != 0.0)
+    }
+
+    /// Applies an operation to numbers, or to every element when either side is a list
+    pub fn combine(
+        &self,
+        other: &Value,
+        operation: impl Fn(f64, f64) -> Result<f64, String>,
+    ) -> Result<Value, String> {
+        match (self, other) {
+            (Value::Number(left), Value::Number(right)) => {
+                Ok(Value::Number(operation(*left, *right)?))
+            }
+            (Value::List(left), Value::Number(right)) => Ok(Value::List(
+                left.iter()
+                    .map(|left| operation(*left, *right))
+                    .collect::<Result<_, _>>()?,
+            )),
+            (Value::Number(left), Value::List(right)) => Ok(Value::List(
+                right
+                    .iter()
+                    .map(|right| operation(*left, *right))
+                    .collect::<Result<_, _>>()?,
+            )),
+            (Value::List(left), Value::List(right)) => {
+                if left.len() != right.len() {
+                    return Err("DIM MISMATCH: lists have different lengths".to_string());
+                }
+
+                Ok(Value::List(
+                    left.iter()
+                        .zip(right)
+                        .map(|(left, right)| operation(*left, *right))
+                        .collect::<Result<_, _>>()?,
+                ))
+            }
+            _ => Err("DATA TYPE: expected numbers or lists".to_string()),
+        }
+    }
+
+    /// Applies an operation to a number or every element of a list
+    pub fn map(&self, operation: impl Fn(f64) -> Result<f64, String>) -> Result<Value, String> {
+        match self {
+            Value::Number(number) => Ok(Value::Number(operation(*number)?)),
+            Value::List(list) => Ok(Value::List(
+                list.iter()
+                    .map(|number| operation(*number))
+                    .collect::<Result<_, _>>()?,
+            )),
+            Value::String(_) => Err("DATA TYPE: expected a number or a list".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{}", format_number(*number)),
+            Value::String(string) => write!(f, "{}", string),
+            Value::List(list) => {
+                let elements = list
+                    .iter()
+                    .map(|number| format_number(*number))
+                    .collect::<Vec<_>>();
+
+                write!(f, "{{{}}}", elements.join(" "))
+            }
+        }
+    }
+}
+
+/// Formats a number the way the home screen does, with up to 10 significant digits
+pub fn format_number(number: f64) -> String {
+    if number == 0.0 || !number.is_finite() {
+        return "0".to_string();
+    }
+
+    let magnitude = number.abs().log10().floor() as i32;
+
+    if !(-3..10).contains(&magnitude) {
+        let mantissa = number / 10f64.powi(magnitude);
+        return format!(
+            "{}E{}",
+            trim_decimals(format!("{:.9}", mantissa)),
+            magnitude
+        );
+    }
+
+    let decimals = (9 - magnitude).max(0) as usize;
+
+    trim_decimals(format!("{:.*}", decimals, number))
+}
+
+fn trim_decimals(number: String) -> String {
+    if number.contains('.') {
+        let number = number.trim_end_matches('0').trim_end_matches('.');
+
+        if number == "-0" {
+            return "0".to_string();
+        }
+
+        number.to_string()
+    } else {
+        number
+    }
+}
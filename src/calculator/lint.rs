@@ -0,0 +1,362 @@
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::file::{decode_program, DecodedToken};
+use crate::calculator::program::Program;
+use crate::calculator::tokens::bytes::{
+    CLOSE_PARENTHESIS, COLON, COMMA, ELSE, END, FOR, GOTO, IF, LBL, MENU, NEWLINE, PRGM, QUOTE,
+    REPEAT, STOP, STORE, THEN, WHILE,
+};
+use crate::calculator::DisplayMode;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+/// A problem found in a program, lines start at 1 with the first line of the program body
+#[derive(Debug, Serialize)]
+pub struct Diagnostic {
+    pub rule: String,
+    pub severity: Severity,
+    pub line: usize,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(rule: &str, severity: Severity, line: usize, message: String) -> Diagnostic {
+        Diagnostic {
+            rule: rule.to_string(),
+            severity,
+            line,
+            message,
+        }
+    }
+}
+
+/// Statements are separated by new lines and colons outside of strings
+pub(crate) struct Statement<'a> {
+    pub(crate) tokens: &'a [DecodedToken],
+    pub(crate) line: usize,
+}
+
+impl Statement<'_> {
+    pub(crate) fn command(&self) -> Option<u8> {
+        match self.tokens.first() {
+            Some(token) if token.bytes.len() == 1 => Some(token.bytes[0]),
+            _ => None,
+        }
+    }
+
+    /// The text after the command, used for label names
+    pub(crate) fn argument(&self) -> String {
+        name_of(&self.tokens[1..])
+    }
+}
+
+#[derive(Clone, Copy)]
+enum BlockKind {
+    IfThen,
+    While,
+    Repeat,
+    For,
+}
+
+impl fmt::Display for BlockKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            BlockKind::IfThen => "If-Then",
+            BlockKind::While => "While",
+            BlockKind::Repeat => "Repeat",
+            BlockKind::For => "For(",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+struct Block {
+    kind: BlockKind,
+    line: usize,
+    has_else: bool,
+}
+
+/// Lints the body of a program, `subprogram` enables the rules for programs called by other programs
+pub fn lint_program(program: &Program, subprogram: bool) -> Result<Vec<Diagnostic>, TiToolsError> {
+    let tokens = decode_program(program, &DisplayMode::Accessible)?;
+
+    Ok(lint(&tokens, subprogram))
+}
+
+/// The names of the programs called with `prgm` in the body of a program
+pub fn program_calls(program: &Program) -> Result<Vec<String>, TiToolsError> {
+    let tokens = decode_program(program, &DisplayMode::Accessible)?;
+    let mut calls = Vec::new();
+
+    for (index, token) in tokens.iter().enumerate() {
+        if token.bytes != [PRGM] {
+            continue;
+        }
+
+        // program names are made of A-Z, θ and 0-9
+        let name = tokens[index + 1..]
+            .iter()
+            .take_while(|token| matches!(token.bytes.as_slice(), [0x30..=0x39 | 0x41..=0x5B]))
+            .map(|token| token.text.as_str())
+            .collect::<String>();
+
+        if !name.is_empty() && !calls.contains(&name) {
+            calls.push(name);
+        }
+    }
+
+    Ok(calls)
+}
+
+pub(crate) fn lint(tokens: &[DecodedToken], subprogram: bool) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let statements = split_statements(tokens);
+
+    check_blocks(&statements, &mut diagnostics);
+    check_labels(&statements, &mut diagnostics);
+
+    if subprogram {
+        for statement in &statements {
+            if statement.command() == Some(STOP) {
+                diagnostics.push(Diagnostic::new(
+                    "stop-in-subprogram",
+                    Severity::Warning,
+                    statement.line,
+                    "Stop ends every running program, use Return to go back to the caller"
+                        .to_string(),
+                ));
+            }
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+
+    diagnostics
+}
+
+pub(crate) fn split_statements(tokens: &[DecodedToken]) -> Vec<Statement<'_>> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut in_string = false;
+
+    for (index, token) in tokens.iter().enumerate() {
+        match token.bytes.as_slice() {
+            [QUOTE] => in_string = !in_string,
+            [STORE] => in_string = false,
+            [COLON] if in_string => {}
+            // the calculator closes strings at the end of the line, so that is not a mistake
+            [COLON] | [NEWLINE] => {
+                in_string = false;
+
+                if start < index {
+                    statements.push(Statement {
+                        tokens: &tokens[start..index],
+                        line: tokens[start].line,
+                    });
+                }
+
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if start < tokens.len() {
+        statements.push(Statement {
+            tokens: &tokens[start..],
+            line: tokens[start].line,
+        });
+    }
+
+    statements
+}
+
+fn check_blocks(statements: &[Statement], diagnostics: &mut Vec<Diagnostic>) {
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut previous_command = None;
+
+    for statement in statements {
+        let command = statement.command();
+
+        let opened = match command {
+            Some(THEN) if previous_command == Some(IF) => Some(BlockKind::IfThen),
+            Some(THEN) => {
+                diagnostics.push(Diagnostic::new(
+                    "unmatched-then",
+                    Severity::Error,
+                    statement.line,
+                    "Then must directly follow an If".to_string(),
+                ));
+                None
+            }
+            Some(WHILE) => Some(BlockKind::While),
+            Some(REPEAT) => Some(BlockKind::Repeat),
+            Some(FOR) => Some(BlockKind::For),
+            _ => None,
+        };
+
+        if let Some(kind) = opened {
+            blocks.push(Block {
+                kind,
+                line: statement.line,
+                has_else: false,
+            });
+        }
+
+        match command {
+            Some(ELSE) => match blocks.last_mut() {
+                Some(block) if matches!(block.kind, BlockKind::IfThen) && !block.has_else => {
+                    block.has_else = true
+                }
+                _ => diagnostics.push(Diagnostic::new(
+                    "unmatched-else",
+                    Severity::Error,
+                    statement.line,
+                    "Else is not inside an If-Then block".to_string(),
+                )),
+            },
+            Some(END) if blocks.pop().is_none() => diagnostics.push(Diagnostic::new(
+                "unmatched-end",
+                Severity::Error,
+                statement.line,
+                "End does not close any block".to_string(),
+            )),
+            _ => {}
+        }
+
+        previous_command = command;
+    }
+
+    for block in blocks {
+        diagnostics.push(Diagnostic::new(
+            "missing-end",
+            Severity::Error,
+            block.line,
+            format!("{} block is never closed with End", block.kind),
+        ));
+    }
+}
+
+fn check_labels(statements: &[Statement], diagnostics: &mut Vec<Diagnostic>) {
+    let mut labels: BTreeMap<String, usize> = BTreeMap::new();
+    let mut references: Vec<(String, usize, &str)> = Vec::new();
+
+    for statement in statements {
+        match statement.command() {
+            Some(LBL) => {
+                let name = statement.argument();
+
+                if let Some(line) = labels.get(&name) {
+                    diagnostics.push(Diagnostic::new(
+                        "duplicate-label",
+                        Severity::Error,
+                        statement.line,
+                        format!("Label {} is already defined on line {}", name, line),
+                    ));
+                } else {
+                    labels.insert(name, statement.line);
+                }
+            }
+            Some(GOTO) => references.push((statement.argument(), statement.line, "Goto")),
+            Some(MENU) => {
+                for name in menu_labels(statement) {
+                    references.push((name, statement.line, "Menu("));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (name, line, command) in &references {
+        if !labels.contains_key(name) {
+            diagnostics.push(Diagnostic::new(
+                "undefined-label",
+                Severity::Error,
+                *line,
+                format!(
+                    "{} refers to label {} which is never defined",
+                    command, name
+                ),
+            ));
+        }
+    }
+
+    for (name, line) in labels {
+        if !references
+            .iter()
+            .any(|(reference, _, _)| *reference == name)
+        {
+            diagnostics.push(Diagnostic::new(
+                "unused-label",
+                Severity::Warning,
+                line,
+                format!("Label {} is never used", name),
+            ));
+        }
+    }
+}
+
+/// `Menu("TITLE","OPTION",LABEL,...)`, every second argument after the title is a label
+pub(crate) fn menu_labels(statement: &Statement) -> Vec<String> {
+    let mut arguments: Vec<&[DecodedToken]> = Vec::new();
+    let tokens = &statement.tokens[1..];
+    let mut start = 0;
+    let mut in_string = false;
+
+    for (index, token) in tokens.iter().enumerate() {
+        match token.bytes.as_slice() {
+            [QUOTE] => in_string = !in_string,
+            [COMMA] if !in_string => {
+                arguments.push(&tokens[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    arguments.push(&tokens[start..]);
+
+    arguments
+        .iter()
+        .skip(2)
+        .step_by(2)
+        .map(|argument| {
+            let argument = match argument.last() {
+                Some(token) if token.bytes == [CLOSE_PARENTHESIS] => {
+                    &argument[..argument.len() - 1]
+                }
+                _ => argument,
+            };
+
+            name_of(argument)
+        })
+        .collect()
+}
+
+pub(crate) fn name_of(tokens: &[DecodedToken]) -> String {
+    tokens
+        .iter()
+        .map(|token| token.text.as_str())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
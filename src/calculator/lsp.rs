@@ -0,0 +1,575 @@
+//! A language server for txt programs, answering Language Server Protocol messages one at a time
+
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::file::{decode_tokens, encode, DecodedToken};
+use crate::calculator::lint::{lint, menu_labels, name_of, split_statements, Severity};
+use crate::calculator::models::Model;
+use crate::calculator::program::{Destination, FileType, TXT_HEADER_LINES};
+use crate::calculator::tokens::bytes::{
+    CLOSE_PARENTHESIS, COLON, COMMA, GOTO, LBL, MENU, NEWLINE, QUOTE,
+};
+use crate::calculator::tokens::{
+    definition_key, load_token_definitions, load_tokens, Map, OsVersion, TokenDefinition,
+};
+use crate::calculator::{DisplayMode, EncodeMode};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_REQUEST: i64 = -32600;
+
+const FULL_SYNC: u8 = 1;
+const COMPLETION_KEYWORD: u8 = 14;
+
+/// A txt file open in the editor, with its body split into tokens
+struct Document {
+    display_mode: DisplayMode,
+    map: Rc<Map>,
+    /// The tokens of the body, numbered and placed like the tokens of a decoded program
+    tokens: Vec<DecodedToken>,
+    /// The first and last column of each token in the file, counted in UTF-16 like the protocol
+    columns: Vec<(usize, usize)>,
+    diagnostics: Vec<Value>,
+}
+
+impl Document {
+    fn parse(
+        text: &str,
+        default_display_mode: &DisplayMode,
+        encode_mode: &EncodeMode,
+        maps: &mut HashMap<String, Rc<Map>>,
+    ) -> Result<Document, TiToolsError> {
+        let lines: Vec<String> = text.lines().map(|line| line.to_string()).collect();
+        let mut diagnostics = header_diagnostics(&lines);
+
+        let display_mode = lines
+            .get(4)
+            .and_then(|line| DisplayMode::from_string(line).ok())
+            .unwrap_or_else(|| default_display_mode.clone());
+
+        let model = match lines.get(5) {
+            Some(line) => Model::from_string(line),
+            None => Model::Latest,
+        };
+        // the whole file is parsed again on every change, so the tokens are only loaded once
+        let map = match maps.get(&model.to_string()) {
+            Some(map) => Rc::clone(map),
+            None => {
+                let map = Rc::new(load_tokens(&OsVersion::new(
+                    model.clone(),
+                    "latest".to_string(),
+                ))?);
+                maps.insert(model.to_string(), Rc::clone(&map));
+                map
+            }
+        };
+
+        let mut tokens = Vec::new();
+        let mut columns = Vec::new();
+        let mut unknown_tokens = false;
+        let mut offset = 0;
+
+        for (index, line) in lines.iter().enumerate().skip(TXT_HEADER_LINES) {
+            let body_line = index - TXT_HEADER_LINES + 1;
+
+            if body_line > 1 {
+                let end = utf16_length(&lines[index - 1]);
+
+                tokens.push(newline_token(&map, body_line - 1, offset, &display_mode)?);
+                columns.push((end, end));
+                offset += 1;
+            }
+
+            let bytes = match encode(
+                &line.to_string(),
+                &map,
+                true,
+                display_mode.clone(),
+                encode_mode,
+            ) {
+                Ok(bytes) => bytes,
+                Err(TiToolsError::TokenNotFound(rest)) => {
+                    let column = utf16_length(line) - utf16_length(&rest);
+                    let length = rest.chars().next().map_or(0, char::len_utf16);
+
+                    diagnostics.push(diagnostic(
+                        index,
+                        (column, column + length),
+                        Severity::Error,
+                        "unknown-token",
+                        TiToolsError::TokenNotFound(rest).to_string(),
+                    ));
+                    unknown_tokens = true;
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+
+            // tabs are only indentation, so the tokens start after them
+            let mut column = line.chars().take_while(|c| *c == '\t').count();
+
+            for mut token in decode_tokens(&bytes, &map, "en", &display_mode)? {
+                let length = utf16_length(&token.text);
+                token.line = body_line;
+                token.offset += offset;
+
+                tokens.push(token);
+                columns.push((column, column + length));
+                column += length;
+            }
+
+            offset += bytes.len();
+        }
+
+        // The lint rules would only repeat the unknown tokens as broken blocks and labels
+        if !unknown_tokens {
+            for finding in lint(&tokens, false) {
+                let index = finding.line - 1 + TXT_HEADER_LINES;
+                let length = lines.get(index).map_or(0, |line| utf16_length(line));
+
+                diagnostics.push(diagnostic(
+                    index,
+                    (0, length),
+                    finding.severity,
+                    &finding.rule,
+                    finding.message,
+                ));
+            }
+        }
+
+        Ok(Document {
+            display_mode,
+            map,
+            tokens,
+            columns,
+            diagnostics,
+        })
+    }
+
+    /// The index of the token under a position, or just before it when the cursor ends a token
+    fn token_at(&self, line: usize, character: usize) -> Option<usize> {
+        if line < TXT_HEADER_LINES {
+            return None;
+        }
+
+        let body_line = line - TXT_HEADER_LINES + 1;
+        let on_line = |index: &usize| {
+            self.tokens[*index].line == body_line && self.tokens[*index].bytes != [NEWLINE]
+        };
+
+        (0..self.tokens.len())
+            .filter(on_line)
+            .find(|index| self.columns[*index].0 <= character && character < self.columns[*index].1)
+            .or_else(|| {
+                (0..self.tokens.len())
+                    .filter(on_line)
+                    .find(|index| self.columns[*index].1 == character)
+            })
+    }
+
+    fn range(&self, first: usize, last: usize) -> Value {
+        let line = self.tokens[first].line - 1 + TXT_HEADER_LINES;
+
+        json!({
+            "start": { "line": line, "character": self.columns[first].0 },
+            "end": { "line": line, "character": self.columns[last].1 },
+        })
+    }
+}
+
+pub struct Server {
+    documents: HashMap<String, Document>,
+    definitions: HashMap<String, Vec<TokenDefinition>>,
+    /// The tokens of each model a document has named, by model name
+    maps: HashMap<String, Rc<Map>>,
+    display_mode: DisplayMode,
+    encode_mode: EncodeMode,
+    shutdown: bool,
+    exit_code: Option<i32>,
+}
+
+impl Server {
+    /// `display_mode` is used for files whose header does not name a valid one
+    pub fn new(display_mode: DisplayMode, encode_mode: EncodeMode) -> Result<Server, TiToolsError> {
+        Ok(Server {
+            documents: HashMap::new(),
+            definitions: load_token_definitions()?,
+            maps: HashMap::new(),
+            display_mode,
+            encode_mode,
+            shutdown: false,
+            exit_code: None,
+        })
+    }
+
+    /// Set once the client sends exit, 0 when it asked for a shutdown first
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    /// Handles a request or notification, giving back the messages to send to the client
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = match message["method"].as_str() {
+            Some(method) => method,
+            // responses to requests the server never makes
+            None => return Vec::new(),
+        };
+        let id = message.get("id").cloned();
+        let params = &message["params"];
+
+        if self.shutdown && method != "exit" {
+            return match id {
+                Some(id) => vec![error_response(
+                    id,
+                    INVALID_REQUEST,
+                    "The server is shut down",
+                )],
+                None => Vec::new(),
+            };
+        }
+
+        let result = match method {
+            "initialize" => Some(self.initialize()),
+            "shutdown" => {
+                self.shutdown = true;
+                Some(Value::Null)
+            }
+            "exit" => {
+                self.exit_code = Some(if self.shutdown { 0 } else { 1 });
+                return Vec::new();
+            }
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                return self.update(document["uri"].as_str(), document["text"].as_str());
+            }
+            "textDocument/didChange" => {
+                // the whole file is sent on every change
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                return self.update(params["textDocument"]["uri"].as_str(), text);
+            }
+            "textDocument/didClose" => {
+                return match params["textDocument"]["uri"].as_str() {
+                    Some(uri) => {
+                        self.documents.remove(uri);
+                        vec![publish_diagnostics(uri, Vec::new())]
+                    }
+                    None => Vec::new(),
+                };
+            }
+            "textDocument/hover" => Some(self.hover(params)),
+            "textDocument/completion" => Some(self.completion(params)),
+            "textDocument/definition" => Some(self.definition(params)),
+            _ => None,
+        };
+
+        match (id, result) {
+            (Some(id), Some(result)) => {
+                vec![json!({ "jsonrpc": "2.0", "id": id, "result": result })]
+            }
+            (Some(id), None) => vec![error_response(
+                id,
+                METHOD_NOT_FOUND,
+                &format!("{} is not supported", method),
+            )],
+            (None, _) => Vec::new(),
+        }
+    }
+
+    fn initialize(&self) -> Value {
+        json!({
+            "capabilities": {
+                "textDocumentSync": FULL_SYNC,
+                "hoverProvider": true,
+                "completionProvider": {},
+                "definitionProvider": true,
+            },
+            "serverInfo": {
+                "name": "ti-tools",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        })
+    }
+
+    fn update(&mut self, uri: Option<&str>, text: Option<&str>) -> Vec<Value> {
+        let (uri, text) = match (uri, text) {
+            (Some(uri), Some(text)) => (uri, text),
+            _ => return Vec::new(),
+        };
+
+        match Document::parse(text, &self.display_mode, &self.encode_mode, &mut self.maps) {
+            Ok(document) => {
+                let notification = publish_diagnostics(uri, document.diagnostics.clone());
+                self.documents.insert(uri.to_string(), document);
+                vec![notification]
+            }
+            Err(err) => {
+                self.documents.remove(uri);
+                vec![publish_diagnostics(
+                    uri,
+                    vec![diagnostic(
+                        0,
+                        (0, 0),
+                        Severity::Error,
+                        "load",
+                        err.to_string(),
+                    )],
+                )]
+            }
+        }
+    }
+
+    fn document(&self, params: &Value) -> Option<(&Document, usize, usize)> {
+        let document = self
+            .documents
+            .get(params["textDocument"]["uri"].as_str()?)?;
+        let line = params["position"]["line"].as_u64()? as usize;
+        let character = params["position"]["character"].as_u64()? as usize;
+
+        Some((document, line, character))
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let hover = self
+            .document(params)
+            .and_then(|(document, line, character)| {
+                let index = document.token_at(line, character)?;
+                let definitions = self
+                    .definitions
+                    .get(&definition_key(&document.tokens[index].key))?;
+
+                let contents = definitions
+                    .iter()
+                    .map(TokenDefinition::to_markdown)
+                    .collect::<Vec<String>>()
+                    .join("\n\n---\n\n");
+
+                Some(json!({
+                    "contents": { "kind": "markdown", "value": contents },
+                    "range": document.range(index, index),
+                }))
+            });
+
+        hover.unwrap_or(Value::Null)
+    }
+
+    /// Every token of the model of the file, spelled in its display mode
+    fn completion(&self, params: &Value) -> Value {
+        let (map, display_mode) = match self.document(params) {
+            Some((document, _, _)) => (&document.map, &document.display_mode),
+            None => return Value::Null,
+        };
+
+        let mut items: BTreeMap<&str, Value> = BTreeMap::new();
+
+        for (key, translation) in &map.map {
+            let Some(key) = key.strip_suffix(" en") else {
+                continue;
+            };
+
+            let label = match display_mode {
+                DisplayMode::Pretty => &translation.display,
+                DisplayMode::Accessible => &translation.accessible,
+                DisplayMode::TiAscii => &translation.ti_ascii,
+            };
+
+            if label.trim().is_empty() || items.contains_key(label.as_str()) {
+                continue;
+            }
+
+            let mut item = json!({ "label": label, "kind": COMPLETION_KEYWORD });
+
+            if let Some(definitions) = self.definitions.get(&definition_key(key)) {
+                if let Some(definition) = definitions.first() {
+                    item["detail"] = json!(definition.syntax);
+                    item["documentation"] = json!(definition.description);
+                }
+            }
+
+            items.insert(label, item);
+        }
+
+        json!(items.into_values().collect::<Vec<Value>>())
+    }
+
+    /// Jumps from a label named by `Goto` or `Menu(` to its `Lbl`
+    fn definition(&self, params: &Value) -> Value {
+        let location = self
+            .document(params)
+            .and_then(|(document, line, character)| {
+                let index = document.token_at(line, character)?;
+                let offset = document.tokens[index].offset;
+                let line = document.tokens[index].line;
+
+                let statements = split_statements(&document.tokens);
+                let statement = statements.iter().find(|statement| {
+                    statement.line == line
+                        && statement.tokens[0].offset <= offset
+                        && offset <= statement.tokens[statement.tokens.len() - 1].offset
+                })?;
+
+                let name = match statement.command() {
+                    Some(GOTO) => statement.argument(),
+                    Some(MENU) => {
+                        let name = word_at(&document.tokens, index);
+                        if !menu_labels(statement).contains(&name) {
+                            return None;
+                        }
+                        name
+                    }
+                    _ => return None,
+                };
+
+                // statements borrow the tokens, so their first token is found again by its offset
+                let label = statements.iter().find(|statement| {
+                    statement.command() == Some(LBL) && statement.argument() == name
+                })?;
+                let first = document
+                    .tokens
+                    .iter()
+                    .position(|token| token.offset == label.tokens[0].offset)?;
+
+                Some(json!({
+                    "uri": params["textDocument"]["uri"],
+                    "range": document.range(first, first + label.tokens.len() - 1),
+                }))
+            });
+
+        location.unwrap_or(Value::Null)
+    }
+}
+
+/// The tokens around `index` up to the punctuation that separates the arguments of a command
+fn word_at(tokens: &[DecodedToken], index: usize) -> String {
+    let separator = |token: &DecodedToken| {
+        matches!(
+            token.bytes.as_slice(),
+            [COMMA | QUOTE | COLON | NEWLINE | CLOSE_PARENTHESIS]
+        )
+    };
+
+    if separator(&tokens[index]) {
+        return String::new();
+    }
+
+    let start = tokens[..index]
+        .iter()
+        .rposition(separator)
+        .map_or(0, |position| position + 1);
+    let end = tokens[index..]
+        .iter()
+        .position(separator)
+        .map_or(tokens.len(), |position| index + position);
+
+    name_of(&tokens[start..end])
+}
+
+/// Checks the lines before the body the same way loading a txt file does
+fn header_diagnostics(lines: &[String]) -> Vec<Value> {
+    let fields = [
+        "name",
+        "comment",
+        "file type",
+        "destination",
+        "display mode",
+        "model",
+    ];
+    let mut diagnostics = Vec::new();
+
+    if lines.len() < TXT_HEADER_LINES {
+        let index = lines.len().saturating_sub(1);
+
+        diagnostics.push(diagnostic(
+            index,
+            (0, lines.get(index).map_or(0, |line| utf16_length(line))),
+            Severity::Error,
+            "header",
+            TiToolsError::MissingProgramInfo(fields[lines.len()].to_string()).to_string(),
+        ));
+    }
+
+    for (index, line) in lines.iter().enumerate().take(TXT_HEADER_LINES) {
+        let result = match index {
+            0 if line.len() > 8 => Err(TiToolsError::InvalidNameLength),
+            0 if !line.chars().all(|c| c.is_ascii_alphabetic()) => {
+                Err(TiToolsError::InvalidNameCharacters)
+            }
+            2 => FileType::from_string(line).map(|_| ()),
+            3 => Destination::from_string(line).map(|_| ()),
+            4 => DisplayMode::from_string(line).map(|_| ()),
+            _ => Ok(()),
+        };
+
+        if let Err(err) = result {
+            diagnostics.push(diagnostic(
+                index,
+                (0, utf16_length(line)),
+                Severity::Error,
+                "header",
+                err.to_string(),
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+fn newline_token(
+    map: &Map,
+    line: usize,
+    offset: usize,
+    display_mode: &DisplayMode,
+) -> Result<DecodedToken, TiToolsError> {
+    let mut token = decode_tokens(&[NEWLINE], map, "en", display_mode)?.remove(0);
+    token.line = line;
+    token.offset = offset;
+
+    Ok(token)
+}
+
+fn diagnostic(
+    line: usize,
+    (start, end): (usize, usize),
+    severity: Severity,
+    rule: &str,
+    message: String,
+) -> Value {
+    let severity = match severity {
+        Severity::Error => 1,
+        Severity::Warning => 2,
+    };
+
+    json!({
+        "range": {
+            "start": { "line": line, "character": start },
+            "end": { "line": line, "character": end },
+        },
+        "severity": severity,
+        "code": rule,
+        "source": "ti-tools",
+        "message": message,
+    })
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn utf16_length(text: &str) -> usize {
+    text.encode_utf16().count()
+}
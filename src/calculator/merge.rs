@@ -0,0 +1,292 @@
+//! Three way merges of programs, line by line on their decoded tokens
+
+use crate::calculator::diff::{
+    line_text, longest_common_subsequence, program_lines, same_line, ChangeKind,
+};
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::file::{decode, load_program_tokens, DecodedToken};
+use crate::calculator::program::Program;
+use crate::calculator::tokens::bytes::NEWLINE;
+use crate::calculator::DisplayMode;
+use serde::Serialize;
+
+/// A detail both sides changed differently, the merged program keeps ours
+#[derive(Debug, Serialize)]
+pub struct MetadataConflict {
+    pub field: String,
+    pub base: String,
+    pub ours: String,
+    pub theirs: String,
+}
+
+pub struct Merge {
+    /// Ours with the merged details, and the merged body when no lines conflict
+    pub program: Program,
+    /// The merged program as a txt file, with conflict markers around the lines both sides changed
+    pub text: String,
+    /// How many runs of lines both sides changed differently
+    pub conflicts: usize,
+    pub metadata_conflicts: Vec<MetadataConflict>,
+}
+
+impl Merge {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts == 0 && self.metadata_conflicts.is_empty()
+    }
+}
+
+enum Chunk<'a> {
+    Lines(&'a [Vec<DecodedToken>]),
+    Conflict {
+        ours: &'a [Vec<DecodedToken>],
+        base: &'a [Vec<DecodedToken>],
+        theirs: &'a [Vec<DecodedToken>],
+    },
+}
+
+/// Merges the changes from base to theirs into ours, comparing lines decoded with `display_mode`
+pub fn merge_programs(
+    base: &Program,
+    ours: Program,
+    theirs: &Program,
+    display_mode: &DisplayMode,
+) -> Result<Merge, TiToolsError> {
+    let base_lines = program_lines(base, display_mode)?;
+    let ours_lines = program_lines(&ours, display_mode)?;
+    let theirs_lines = program_lines(theirs, display_mode)?;
+
+    let chunks = merge_lines(&base_lines, &ours_lines, &theirs_lines);
+    let conflicts = chunks
+        .iter()
+        .filter(|chunk| matches!(chunk, Chunk::Conflict { .. }))
+        .count();
+
+    let mut program = ours;
+    let metadata_conflicts = merge_metadata(base, &mut program, theirs)?;
+
+    let mut text = String::new();
+    let mut bytes = Vec::new();
+
+    for chunk in &chunks {
+        match chunk {
+            Chunk::Lines(lines) => {
+                for line in lines.iter() {
+                    text.push_str(&line_text(line));
+                    text.push('\n');
+
+                    bytes.extend(line.iter().flat_map(|token| token.bytes.iter()));
+                    bytes.push(NEWLINE);
+                }
+            }
+            Chunk::Conflict { ours, base, theirs } => {
+                for (marker, lines) in [
+                    ("<<<<<<< ours", ours),
+                    ("||||||| base", base),
+                    ("=======", theirs),
+                ] {
+                    text.push_str(marker);
+                    text.push('\n');
+
+                    for line in lines.iter() {
+                        text.push_str(&line_text(line));
+                        text.push('\n');
+                    }
+                }
+
+                text.push_str(">>>>>>> theirs\n");
+            }
+        }
+    }
+
+    // Every line ended with a newline, but the last line of a program has none
+    text.pop();
+    bytes.pop();
+
+    if conflicts > 0 {
+        bytes = program.body.bytes.clone();
+    }
+
+    // Replacing the body also updates the checksum for the merged details
+    let map = load_program_tokens(&program)?;
+    let translation = decode(&bytes, &map, "en", &program.display_mode)?;
+    program.replace_body(bytes, translation);
+
+    let text = format!(
+        "{}\n{}",
+        program.txt_header(&program.header.comment, display_mode),
+        text
+    );
+
+    Ok(Merge {
+        program,
+        text,
+        conflicts,
+        metadata_conflicts,
+    })
+}
+
+/// Splits the three versions at the base lines both sides kept, then resolves the runs between them
+fn merge_lines<'a>(
+    base: &'a [Vec<DecodedToken>],
+    ours: &'a [Vec<DecodedToken>],
+    theirs: &'a [Vec<DecodedToken>],
+) -> Vec<Chunk<'a>> {
+    let ours_matches = matching_lines(base, ours);
+    let theirs_matches = matching_lines(base, theirs);
+
+    let mut chunks = Vec::new();
+    let (mut base_start, mut ours_start, mut theirs_start) = (0, 0, 0);
+
+    loop {
+        let kept = (base_start..base.len()).find_map(|index| {
+            match (ours_matches[index], theirs_matches[index]) {
+                (Some(ours_index), Some(theirs_index)) => Some((index, ours_index, theirs_index)),
+                _ => None,
+            }
+        });
+
+        let (base_end, ours_end, theirs_end) =
+            kept.unwrap_or((base.len(), ours.len(), theirs.len()));
+
+        let base_run = &base[base_start..base_end];
+        let ours_run = &ours[ours_start..ours_end];
+        let theirs_run = &theirs[theirs_start..theirs_end];
+
+        if same_lines(ours_run, base_run) {
+            chunks.push(Chunk::Lines(theirs_run));
+        } else if same_lines(theirs_run, base_run) || same_lines(ours_run, theirs_run) {
+            chunks.push(Chunk::Lines(ours_run));
+        } else {
+            chunks.push(Chunk::Conflict {
+                ours: ours_run,
+                base: base_run,
+                theirs: theirs_run,
+            });
+        }
+
+        match kept {
+            Some(_) => {
+                chunks.push(Chunk::Lines(&ours[ours_end..ours_end + 1]));
+                base_start = base_end + 1;
+                ours_start = ours_end + 1;
+                theirs_start = theirs_end + 1;
+            }
+            None => break,
+        }
+    }
+
+    chunks
+}
+
+/// For every base line, the line it matches in the other version
+fn matching_lines(base: &[Vec<DecodedToken>], other: &[Vec<DecodedToken>]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; base.len()];
+
+    for (kind, base_index, other_index) in
+        longest_common_subsequence(base, other, |base, other| same_line(base, other))
+    {
+        if kind == ChangeKind::Equal {
+            matches[base_index] = Some(other_index);
+        }
+    }
+
+    matches
+}
+
+fn same_lines(left: &[Vec<DecodedToken>], right: &[Vec<DecodedToken>]) -> bool {
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .all(|(left, right)| same_line(left, right))
+}
+
+/// Takes whichever side changed each detail, keeping ours when both changed it differently
+fn merge_metadata(
+    base: &Program,
+    program: &mut Program,
+    theirs: &Program,
+) -> Result<Vec<MetadataConflict>, TiToolsError> {
+    let mut conflicts = Vec::new();
+
+    let mut merge = |field: &str, base: String, ours: String, theirs: String| {
+        if theirs == base || theirs == ours {
+            return None;
+        }
+
+        if ours == base {
+            return Some(theirs);
+        }
+
+        conflicts.push(MetadataConflict {
+            field: field.to_string(),
+            base,
+            ours,
+            theirs,
+        });
+
+        None
+    };
+
+    let name = merge(
+        "name",
+        base.metadata.name.to_string(),
+        program.metadata.name.to_string(),
+        theirs.metadata.name.to_string(),
+    );
+    let comment = merge(
+        "comment",
+        base.header.comment.to_string(),
+        program.header.comment.to_string(),
+        theirs.header.comment.to_string(),
+    );
+    let file_type = merge(
+        "file-type",
+        base.metadata.file_type.to_string(),
+        program.metadata.file_type.to_string(),
+        theirs.metadata.file_type.to_string(),
+    );
+    let destination = merge(
+        "destination",
+        base.metadata.destination.to_string(),
+        program.metadata.destination.to_string(),
+        theirs.metadata.destination.to_string(),
+    );
+
+    // The model decides the tokens, so a program is never moved to the model of theirs
+    let (base_model, ours_model, theirs_model) = (
+        base.model.model.to_string(),
+        program.model.model.to_string(),
+        theirs.model.model.to_string(),
+    );
+    if theirs_model != base_model && theirs_model != ours_model {
+        conflicts.push(MetadataConflict {
+            field: "model".to_string(),
+            base: base_model,
+            ours: ours_model,
+            theirs: theirs_model,
+        });
+    }
+
+    if let Some(name) = name {
+        program.metadata.rename(name)?;
+    }
+
+    if let Some(comment) = comment {
+        program.header.comment(comment)?;
+    }
+
+    match file_type.as_deref() {
+        Some("Locked Program") => program.metadata.lock(),
+        Some("Program") => program.metadata.unlock(),
+        _ => {}
+    }
+
+    match destination.as_deref() {
+        Some("Archive") => program.metadata.archive(),
+        Some("RAM") => program.metadata.unarchive(),
+        _ => {}
+    }
+
+    Ok(conflicts)
+}
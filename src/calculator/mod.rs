@@ -1,11 +1,27 @@
 use crate::calculator::errors::TiToolsError;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
+pub mod assembly;
+pub mod callgraph;
+pub mod diff;
+pub mod disasm;
+pub mod docs;
 pub mod errors;
-mod file;
+pub mod file;
+pub mod format;
+pub mod interpreter;
+pub mod lint;
+pub mod lsp;
+pub mod merge;
 pub mod models;
+pub mod optimize;
 pub mod program;
+pub mod render;
+pub mod shell;
+pub mod testing;
 pub mod tokens;
+pub mod xref;
 
 #[derive(Serialize)]
 pub enum EncodeMode {
@@ -47,13 +63,16 @@ impl DisplayMode {
             )),
         }
     }
+}
 
-    pub fn to_string(&self) -> String {
-        match self {
+impl fmt::Display for DisplayMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
             DisplayMode::Pretty => "pretty",
             DisplayMode::Accessible => "accessible",
             DisplayMode::TiAscii => "ti",
-        }
-        .to_string()
+        };
+
+        write!(f, "{}", string)
     }
 }
@@ -1,6 +1,7 @@
 use crate::calculator::errors::TiToolsError;
 use serde::Deserialize;
 use serde::Serialize;
+use std::fmt;
 
 #[derive(Debug, Eq, Serialize, Deserialize)]
 pub struct ModelDetails {
@@ -148,8 +149,69 @@ impl Model {
         }
     }
 
-    pub fn to_string(&self) -> String {
+    pub fn model_order(&self) -> u32 {
         match self {
+            Model::TI82 => 10,
+            Model::TI83 | Model::TI82ST | Model::TI82STFR | Model::TI76FR => 20,
+            Model::TI83Plus | Model::TI83PlusSE | Model::TI83PlusFR | Model::TI82Plus => 30,
+            Model::TI84Plus
+            | Model::TI84PlusSE
+            | Model::TI83PlusFRUSB
+            | Model::TI84PFR
+            | Model::TI84PlusPSE => 40,
+            Model::TI82A | Model::TI84PlusT => 45,
+            Model::TI84PlusCSE => 50,
+            Model::TI84PlusCE
+            | Model::TI84PlusCET
+            | Model::TI83PCE
+            | Model::TI83PCEEP
+            | Model::TI84PlusCEPY
+            | Model::TI84PlusCETPE
+            | Model::TI82AEP => 60,
+            Model::Latest => 9999999,
+        }
+    }
+
+    pub fn all() -> Vec<Model> {
+        vec![
+            Model::TI82,
+            Model::TI83,
+            Model::TI82ST,
+            Model::TI82STFR,
+            Model::TI76FR,
+            Model::TI83Plus,
+            Model::TI83PlusSE,
+            Model::TI83PlusFR,
+            Model::TI82Plus,
+            Model::TI84Plus,
+            Model::TI84PlusSE,
+            Model::TI83PlusFRUSB,
+            Model::TI84PFR,
+            Model::TI84PlusPSE,
+            Model::TI82A,
+            Model::TI84PlusT,
+            Model::TI84PlusCSE,
+            Model::TI84PlusCE,
+            Model::TI84PlusCET,
+            Model::TI83PCE,
+            Model::TI83PCEEP,
+            Model::TI84PlusCEPY,
+            Model::TI84PlusCETPE,
+            Model::TI82AEP,
+            Model::Latest,
+        ]
+    }
+
+    pub fn display_models() {
+        for model in Model::all() {
+            println!("{}", model);
+        }
+    }
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
             Model::TI82 => "TI-82",
             Model::TI83 => "TI-83",
             Model::TI82ST => "TI-82ST",
@@ -175,60 +237,8 @@ impl Model {
             Model::TI84PlusCETPE => "TI-84+CETPE",
             Model::TI82AEP => "TI-82AEP",
             Model::Latest => "latest",
-        }
-        .to_string()
-    }
+        };
 
-    pub fn model_order(&self) -> u32 {
-        match self {
-            Model::TI82 => 10,
-            Model::TI83 | Model::TI82ST | Model::TI82STFR | Model::TI76FR => 20,
-            Model::TI83Plus | Model::TI83PlusSE | Model::TI83PlusFR | Model::TI82Plus => 30,
-            Model::TI84Plus
-            | Model::TI84PlusSE
-            | Model::TI83PlusFRUSB
-            | Model::TI84PFR
-            | Model::TI84PlusPSE => 40,
-            Model::TI82A | Model::TI84PlusT => 45,
-            Model::TI84PlusCSE => 50,
-            Model::TI84PlusCE
-            | Model::TI84PlusCET
-            | Model::TI83PCE
-            | Model::TI83PCEEP
-            | Model::TI84PlusCEPY
-            | Model::TI84PlusCETPE
-            | Model::TI82AEP => 60,
-            Model::Latest => 9999999,
-        }
-    }
-
-    pub fn display_models() {
-        println!(
-            "TI-82\n\
-            TI-83\n\
-            TI-82ST\n\
-            TI-82ST.fr\n\
-            TI-76.fr\n\
-            TI-83+\n\
-            TI-83+SE\n\
-            TI-83+.fr\n\
-            TI-82+\n\
-            TI-84+\n\
-            TI-84+SE\n\
-            TI-83+.fr:USB\n\
-            TI-84P.fr\n\
-            TI-84+PSE\n\
-            TI-82A\n\
-            TI-84+T\n\
-            TI-84+CSE\n\
-            TI-84+CE\n\
-            TI-84+CET\n\
-            TI-83PCE\n\
-            TI-83PCEEP\n\
-            TI-84+CEPY\n\
-            TI-84+CETPE\n\
-            TI-82AEP\n\
-            latest"
-        );
+        write!(f, "{}", string)
     }
 }
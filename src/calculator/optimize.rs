@@ -0,0 +1,329 @@
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::file::{decode, decode_program, load_program_tokens};
+use crate::calculator::program::Program;
+use crate::calculator::tokens::bytes::{
+    AND, CLOSE_BRACE, CLOSE_BRACKET, CLOSE_PARENTHESIS, COLON, EQUAL, FOR, GREATER,
+    GREATER_OR_EQUAL, IF, LESS, LESS_OR_EQUAL, NEWLINE, NOT, NOT_EQUAL, OR, QUOTE, REPEAT, STORE,
+    WHILE, XOR, ZERO,
+};
+use crate::calculator::DisplayMode;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+
+/// A byte saving rewrite that does not change what a program does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    /// Closing parentheses before a store arrow or the end of a line, except on For( lines
+    Parens,
+    /// Closing braces and brackets before a store arrow or the end of a line
+    Braces,
+    /// Closing quotes before a store arrow or the end of a line
+    Quotes,
+    /// `If X=0` to `If not(X` and `If X≠0` to `If X`, also for While and Repeat
+    ZeroCompare,
+}
+
+impl Transform {
+    pub fn all() -> Vec<Transform> {
+        vec![
+            Transform::Parens,
+            Transform::Braces,
+            Transform::Quotes,
+            Transform::ZeroCompare,
+        ]
+    }
+
+    pub fn from_string(transform: &str) -> Result<Transform, TiToolsError> {
+        match transform {
+            "parens" => Ok(Transform::Parens),
+            "braces" => Ok(Transform::Braces),
+            "quotes" => Ok(Transform::Quotes),
+            "zero-compare" => Ok(Transform::ZeroCompare),
+            _ => Err(TiToolsError::Match(
+                transform.to_string(),
+                "Transform".to_string(),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            Transform::Parens => "parens",
+            Transform::Braces => "braces",
+            Transform::Quotes => "quotes",
+            Transform::ZeroCompare => "zero-compare",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransformSavings {
+    pub transform: String,
+    pub bytes_saved: usize,
+}
+
+struct Token {
+    bytes: Vec<u8>,
+    in_string: bool,
+    closing_quote: bool,
+}
+
+/// Applies the transforms to the body of a program, returning the bytes saved by each of them
+pub fn optimize_program(
+    program: &mut Program,
+    transforms: &[Transform],
+) -> Result<Vec<TransformSavings>, TiToolsError> {
+    let decoded = decode_program(program, &DisplayMode::Accessible)?;
+
+    // functions like `sum(` and the brackets themselves, which leave a group open until closed
+    let openers: HashSet<Vec<u8>> = decoded
+        .iter()
+        .filter(|token| token.text.ends_with(['(', '{', '[']))
+        .map(|token| token.bytes.clone())
+        .collect();
+
+    let tokens: Vec<Vec<u8>> = decoded.into_iter().map(|token| token.bytes).collect();
+
+    let mut savings: Vec<TransformSavings> = transforms
+        .iter()
+        .map(|transform| TransformSavings {
+            transform: transform.to_string(),
+            bytes_saved: 0,
+        })
+        .collect();
+
+    let mut record = |transform: Transform, bytes: usize| {
+        if let Some(index) = transforms.iter().position(|t| *t == transform) {
+            savings[index].bytes_saved += bytes;
+        }
+    };
+
+    let tokens = if transforms.contains(&Transform::ZeroCompare) {
+        rewrite_zero_compares(tokens, &openers, &mut record)
+    } else {
+        tokens
+    };
+
+    let tokens = strip_closers(tokens, transforms, &mut record);
+
+    let bytes: Vec<u8> = tokens.concat();
+    let map = load_program_tokens(program)?;
+    let translation = decode(&bytes, &map, "en", &program.display_mode)?;
+
+    program.replace_body(bytes, translation);
+
+    Ok(savings)
+}
+
+fn rewrite_zero_compares(
+    tokens: Vec<Vec<u8>>,
+    openers: &HashSet<Vec<u8>>,
+    record: &mut impl FnMut(Transform, usize),
+) -> Vec<Vec<u8>> {
+    let mut output = Vec::new();
+
+    for statement in split_statements(tokens) {
+        let rewritten = match statement.first().map(|token| token.as_slice()) {
+            Some([IF | WHILE | REPEAT]) => rewrite_condition(&statement, openers),
+            _ => None,
+        };
+
+        match rewritten {
+            Some(rewritten) => {
+                record(
+                    Transform::ZeroCompare,
+                    statement.concat().len() - rewritten.concat().len(),
+                );
+                output.extend(rewritten);
+            }
+            None => output.extend(statement),
+        }
+    }
+
+    output
+}
+
+/// Only conditions without operators that bind looser than `=` can be rewritten, and only when
+/// the comparison is not inside a group left open, like the `=0` of `If sum(L1=0`
+fn rewrite_condition(statement: &[Vec<u8>], openers: &HashSet<Vec<u8>>) -> Option<Vec<Vec<u8>>> {
+    let separator = match statement.last() {
+        Some(token) if matches!(token.as_slice(), [NEWLINE] | [COLON]) => 1,
+        _ => 0,
+    };
+
+    let condition = &statement[1..statement.len() - separator];
+
+    if condition.len() < 3 || condition[condition.len() - 1] != [ZERO] {
+        return None;
+    }
+
+    let comparison = condition[condition.len() - 2].as_slice();
+    let expression = &condition[..condition.len() - 2];
+
+    let simple = expression.iter().all(|token| {
+        !matches!(
+            token.as_slice(),
+            [EQUAL
+                | NOT_EQUAL
+                | LESS
+                | GREATER
+                | LESS_OR_EQUAL
+                | GREATER_OR_EQUAL
+                | AND
+                | OR
+                | XOR
+                | QUOTE
+                | STORE]
+        )
+    });
+
+    let mut depth = 0;
+
+    for token in expression {
+        if openers.contains(token) {
+            depth += 1;
+        } else if matches!(
+            token.as_slice(),
+            [CLOSE_PARENTHESIS | CLOSE_BRACE | CLOSE_BRACKET]
+        ) {
+            depth -= 1;
+        }
+    }
+
+    if !simple || depth != 0 {
+        return None;
+    }
+
+    let mut rewritten = vec![statement[0].clone()];
+
+    match comparison {
+        [EQUAL] => {
+            rewritten.push(vec![NOT]);
+            rewritten.extend(expression.iter().cloned());
+        }
+        [NOT_EQUAL] => rewritten.extend(expression.iter().cloned()),
+        _ => return None,
+    }
+
+    rewritten.extend(statement[statement.len() - separator..].iter().cloned());
+
+    Some(rewritten)
+}
+
+/// Statements end with a new line or a colon outside of a string, which stays part of the statement
+fn split_statements(tokens: Vec<Vec<u8>>) -> Vec<Vec<Vec<u8>>> {
+    let mut statements = Vec::new();
+    let mut statement = Vec::new();
+    let mut in_string = false;
+
+    for token in tokens {
+        let ends_statement = match token.as_slice() {
+            [QUOTE] => {
+                in_string = !in_string;
+                false
+            }
+            [STORE] => {
+                in_string = false;
+                false
+            }
+            [COLON] => !in_string,
+            [NEWLINE] => {
+                in_string = false;
+                true
+            }
+            _ => false,
+        };
+
+        statement.push(token);
+
+        if ends_statement {
+            statements.push(std::mem::take(&mut statement));
+        }
+    }
+
+    if !statement.is_empty() {
+        statements.push(statement);
+    }
+
+    statements
+}
+
+fn strip_closers(
+    tokens: Vec<Vec<u8>>,
+    transforms: &[Transform],
+    record: &mut impl FnMut(Transform, usize),
+) -> Vec<Vec<u8>> {
+    let mut output: Vec<Token> = Vec::new();
+    let mut in_string = false;
+    let mut statement_start = 0;
+
+    for token in tokens {
+        if matches!(token.as_slice(), [NEWLINE] | [STORE]) {
+            let for_statement = output
+                .get(statement_start)
+                .is_some_and(|token| token.bytes == [FOR]);
+
+            strip_line_end(&mut output, transforms, for_statement, record);
+            in_string = false;
+        }
+
+        let closing_quote = token == [QUOTE] && in_string;
+
+        if token == [QUOTE] {
+            in_string = !in_string;
+        }
+
+        let ends_statement = token == [NEWLINE] || (token == [COLON] && !in_string);
+
+        output.push(Token {
+            bytes: token,
+            in_string,
+            closing_quote,
+        });
+
+        if ends_statement {
+            statement_start = output.len();
+        }
+    }
+
+    let for_statement = output
+        .get(statement_start)
+        .is_some_and(|token| token.bytes == [FOR]);
+
+    strip_line_end(&mut output, transforms, for_statement, record);
+
+    output.into_iter().map(|token| token.bytes).collect()
+}
+
+fn strip_line_end(
+    output: &mut Vec<Token>,
+    transforms: &[Transform],
+    for_statement: bool,
+    record: &mut impl FnMut(Transform, usize),
+) {
+    while let Some(token) = output.last() {
+        let transform = match token.bytes.as_slice() {
+            [QUOTE] if token.closing_quote => Transform::Quotes,
+            [CLOSE_PARENTHESIS] if !token.in_string && !for_statement => Transform::Parens,
+            [CLOSE_BRACE | CLOSE_BRACKET] if !token.in_string => Transform::Braces,
+            _ => break,
+        };
+
+        if !transforms.contains(&transform) {
+            break;
+        }
+
+        output.pop();
+        record(transform, 1);
+
+        // anything before a closing quote is part of the string
+        if transform == Transform::Quotes {
+            break;
+        }
+    }
+}
@@ -1,12 +1,18 @@
 use super::models::ModelDetails;
+use crate::calculator::assembly::{Assembly, AssemblyForm};
 use crate::calculator::errors::TiToolsError;
 use crate::calculator::file::from_8xp::create_from_8xp;
-use crate::calculator::file::from_txt::create_from_txt;
+use crate::calculator::file::from_txt::{create_from_body, create_from_txt};
+use crate::calculator::shell::{Icon, ShellHeader};
 use crate::calculator::{DisplayMode, EncodeMode};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
+/// Name, comment, file type, destination, display mode and model come before the body of a txt file
+pub const TXT_HEADER_LINES: usize = 6;
+
 #[derive(Serialize, Deserialize)]
 pub struct Program {
     pub header: Header,
@@ -131,38 +137,204 @@ impl Program {
         Ok(program)
     }
 
-    pub fn save_to(&self, path: &PathBuf) -> Result<(), TiToolsError> {
-        if path.exists() {
-            println!("A file already exists at the output path, would you like to delete its content and proceed? [y/N]");
-            let mut input = String::new();
-            print!("> ");
-            input.clear();
-            std::io::stdout().flush().unwrap();
-            std::io::stdin().read_line(&mut input).unwrap();
-            let input = input.trim();
-            if input == "y" || input == "Y" {
-                println!("Deleting existing file");
-
-                match std::fs::remove_file(path) {
-                    Ok(_) => {
-                        println!("Deleted existing file");
-                    }
-                    Err(err) => {
-                        return Err(TiToolsError::FailedToDeleteFile(err.to_string()));
-                    }
-                }
-            } else {
-                return Err(TiToolsError::Quit(
-                    "User chose not to overwrite file".to_string(),
-                ));
-            }
+    /// Loads an 8xp/83p/82p, txt or json file as `file_type` whatever its extension, which
+    /// `detect_file_type` also finds for files without one, like the temporary files git hands to
+    /// merge drivers
+    pub fn load_from_file(
+        path: PathBuf,
+        file_type: ProgramFileType,
+        display_mode: DisplayMode,
+        encode_mode: &EncodeMode,
+    ) -> Result<Program, TiToolsError> {
+        if !path.exists() {
+            return Err(TiToolsError::FailedToFindFile(
+                path.to_str().unwrap().to_string(),
+            ));
         }
 
-        let file_type = match get_file_type(path) {
-            Ok(file_type) => file_type,
-            Err(err) => return Err(err),
+        let (header, metadata, body, checksum, model, display_mode) = match file_type {
+            ProgramFileType::XP | ProgramFileType::XPThree | ProgramFileType::XPTwo => {
+                let (header, metadata, body, checksum, model) =
+                    create_from_8xp(path, file_type, &display_mode)?;
+                (header, metadata, body, checksum, model, display_mode)
+            }
+            ProgramFileType::TXT => {
+                let (header, metadata, body, checksum, model) = create_from_txt(path, encode_mode)?;
+                (
+                    header,
+                    metadata,
+                    body,
+                    checksum,
+                    model,
+                    DisplayMode::Accessible,
+                )
+            }
+            ProgramFileType::JSON => {
+                let file_string = match std::fs::read_to_string(&path) {
+                    Ok(file_string) => file_string,
+                    Err(err) => return Err(TiToolsError::FailedToReadFile(err.to_string())),
+                };
+
+                return serde_json::from_str(&file_string)
+                    .map_err(|err| TiToolsError::FailedToDeserializeJson(err.to_string()));
+            }
         };
 
+        Ok(Program {
+            header,
+            metadata,
+            body,
+            checksum,
+            display_mode,
+            model,
+        })
+    }
+
+    /// A new program holding compiled machine code, with the header of the model it is for
+    pub fn from_assembly(
+        name: &str,
+        model_details: ModelDetails,
+        assembly: &Assembly,
+    ) -> Result<Program, TiToolsError> {
+        // assemblers protect their programs, so the program editor does not open machine code
+        let (header, metadata, body, checksum, model) = create_from_body(
+            name,
+            FileType::LockedProgram,
+            Destination::RAM,
+            model_details,
+            assembly.to_body(),
+            assembly.to_hex(),
+        )?;
+
+        Ok(Program {
+            header,
+            metadata,
+            body,
+            checksum,
+            display_mode: DisplayMode::Accessible,
+            model,
+        })
+    }
+
+    /// The machine code of an assembly program, `None` for TI-BASIC
+    pub fn assembly(&self) -> Option<Assembly> {
+        Assembly::detect(&self.body.bytes)
+    }
+
+    /// The header shells read a description and icon from, `None` when there is none
+    pub fn shell_header(&self) -> Option<ShellHeader> {
+        ShellHeader::detect(&self.body.bytes)
+    }
+
+    /// Rewrites the description shells show, in the room the old description had
+    pub fn set_shell_description(&mut self, description: &str) -> Result<(), TiToolsError> {
+        let header = self
+            .shell_header()
+            .ok_or_else(|| TiToolsError::NoShellHeader(self.metadata.name.to_string()))?;
+
+        let mut bytes = self.body.bytes.clone();
+        header.write_description(&mut bytes, description)?;
+
+        // only compiled programs have descriptions, their translation is the code as hex
+        let translation = Assembly::detect(&bytes)
+            .map_or(self.body.translation.to_string(), |assembly| {
+                assembly.to_hex()
+            });
+
+        self.replace_body(bytes, translation);
+
+        Ok(())
+    }
+
+    /// Draws over the icon shells show, the new icon must be the same size
+    pub fn set_shell_icon(&mut self, icon: &Icon) -> Result<(), TiToolsError> {
+        let header = self
+            .shell_header()
+            .ok_or_else(|| TiToolsError::NoShellHeader(self.metadata.name.to_string()))?;
+
+        let mut bytes = self.body.bytes.clone();
+        header.write_icon(&mut bytes, icon)?;
+
+        let translation = match (Assembly::detect(&bytes), &header.icon) {
+            (Some(assembly), _) => assembly.to_hex(),
+            // the icon of a TI-BASIC program is a line of hex digits in its translation too
+            (None, Some(old)) => self
+                .body
+                .translation
+                .replacen(&old.to_hex(), &icon.to_hex(), 1),
+            (None, None) => self.body.translation.to_string(),
+        };
+
+        self.replace_body(bytes, translation);
+
+        Ok(())
+    }
+
+    /// The lines before the body of a txt file
+    pub fn txt_header(&self, comment: &str, display_mode: &DisplayMode) -> String {
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            self.metadata.name,
+            comment,
+            self.metadata.file_type,
+            self.metadata.destination,
+            display_mode,
+            self.model.model
+        )
+    }
+
+    /// Swaps the body for new bytes, updating the lengths in the header and metadata and the checksum
+    pub fn replace_body(&mut self, bytes: Vec<u8>, translation: String) {
+        let body_length = bytes.len() as u16;
+        let body_and_checksum_length = body_length + 2;
+        let metadata_and_body_length = (self.metadata.bytes.len() + bytes.len()) as u16;
+
+        // the header ends with the metadata and body length
+        self.header
+            .bytes
+            .splice(53..55, metadata_and_body_length.to_le_bytes());
+        self.header.metadata_and_body_length = metadata_and_body_length;
+
+        // the metadata starts with the body and checksum length and ends with a copy of it and the body length
+        let metadata_length = self.metadata.bytes.len();
+        self.metadata
+            .bytes
+            .splice(2..4, body_and_checksum_length.to_le_bytes());
+        self.metadata.bytes.splice(
+            metadata_length - 4..metadata_length - 2,
+            body_and_checksum_length.to_le_bytes(),
+        );
+        self.metadata.bytes.splice(
+            metadata_length - 2..metadata_length,
+            body_length.to_le_bytes(),
+        );
+        self.metadata.body_and_checksum_length = body_and_checksum_length;
+        self.metadata.body_and_checksum_length_copy = body_and_checksum_length;
+        self.metadata.body_length = body_length;
+
+        let checksum = self
+            .metadata
+            .bytes
+            .iter()
+            .chain(bytes.iter())
+            .map(|&byte| byte as u32)
+            .sum::<u32>() as u16;
+        self.checksum = Checksum::new(checksum.to_le_bytes().to_vec(), checksum);
+
+        self.body = Body::new(bytes, translation);
+    }
+
+    pub fn save_to(&self, path: &PathBuf, overwrite: &OverwritePolicy) -> Result<(), TiToolsError> {
+        let file_type = get_file_type(path)?;
+        let bytes = self.to_bytes(&file_type)?;
+
+        clear_output_path(path, overwrite)?;
+
+        write_to_file(path, bytes)
+    }
+
+    /// The content of the program saved as the given type
+    pub fn to_bytes(&self, file_type: &ProgramFileType) -> Result<Vec<u8>, TiToolsError> {
         match file_type {
             ProgramFileType::XP | ProgramFileType::XPThree | ProgramFileType::XPTwo => {
                 let mut output_bytes = Vec::new();
@@ -172,52 +344,66 @@ impl Program {
                 output_bytes.extend(self.body.bytes.to_vec());
                 output_bytes.extend(self.checksum.bytes.to_vec());
 
-                write_to_file(path, output_bytes)?
+                Ok(output_bytes)
             }
             ProgramFileType::TXT => {
+                // machine code has no tokens to write, and the hex dump would not encode back
+                if self
+                    .assembly()
+                    .is_some_and(|assembly| assembly.form == AssemblyForm::Compiled)
+                {
+                    return Err(TiToolsError::CompiledAssembly(
+                        self.metadata.name.to_string(),
+                    ));
+                }
+
                 let output_string = format!(
-                    "{}\n{}\n{}\n{}\n{}\n{}\n{}",
-                    self.metadata.name,
-                    self.header.comment,
-                    self.metadata.file_type.to_string(),
-                    self.metadata.destination.to_string(),
-                    self.display_mode.to_string(),
-                    self.model.model.to_string(),
+                    "{}\n{}",
+                    self.txt_header(&self.header.comment, &self.display_mode),
                     &self.body.translation
                 );
-                write_to_file(path, output_string)?
-            }
-            ProgramFileType::JSON => {
-                let output_string = match serde_json::to_string_pretty(&self) {
-                    Ok(output_string) => output_string,
-                    Err(err) => {
-                        return Err(TiToolsError::FailedToSerializeJson(err.to_string()));
-                    }
-                };
 
-                write_to_file(path, output_string)?
+                Ok(output_string.into_bytes())
             }
+            ProgramFileType::JSON => match serde_json::to_string_pretty(&self) {
+                Ok(output_string) => Ok(output_string.into_bytes()),
+                Err(err) => Err(TiToolsError::FailedToSerializeJson(err.to_string())),
+            },
         }
-
-        Ok(())
     }
+}
 
-    pub fn to_string(&self) -> String {
-        format!(
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
             "{}\n{}\nTotal Size: {} bytes\nBody Size: {} bytes\n----- Status -----\nmodel: {}\nlanguage: {}\nDestination: {}\nFile Type: {}\n----- Program -----\n{}",
             self.metadata.name,
             self.header.comment,
             self.header.bytes.len() + self.metadata.bytes.len() + self.body.bytes.len() + 2,
             self.body.bytes.len(),
-            self.model.model.to_string(),
+            self.model.model,
             self.model.language,
-            self.metadata.destination.to_string(),
-            self.metadata.file_type.to_string(),
+            self.metadata.destination,
+            self.metadata.file_type,
             self.body.translation
         )
     }
 }
 
+/// What `Program::save_to` should do when a file already exists at the output path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Ask on stdin before replacing the file
+    Prompt,
+    /// Replace the file without asking
+    Force,
+    /// Leave the file untouched and fail with `TiToolsError::FileAlreadyExists`
+    NoClobber,
+    /// Move the file to `<path>.bak` before writing
+    Backup,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Header {
     pub bytes: Vec<u8>,
@@ -280,6 +466,7 @@ pub struct Metadata {
 }
 
 impl Metadata {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         bytes: Vec<u8>,
         flag: u8,
@@ -392,7 +579,7 @@ impl Checksum {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum ProgramFileType {
     XP,
     TXT,
@@ -402,6 +589,17 @@ pub enum ProgramFileType {
 }
 
 impl ProgramFileType {
+    pub fn from_string(extension: &str) -> Result<ProgramFileType, TiToolsError> {
+        match extension {
+            "8xp" => Ok(ProgramFileType::XP),
+            "83p" => Ok(ProgramFileType::XPThree),
+            "82p" => Ok(ProgramFileType::XPTwo),
+            "txt" => Ok(ProgramFileType::TXT),
+            "json" => Ok(ProgramFileType::JSON),
+            _ => Err(TiToolsError::InvalidExtension(extension.to_string())),
+        }
+    }
+
     pub fn is_8xp(&self) -> bool {
         !matches!(self, ProgramFileType::TXT)
     }
@@ -413,34 +611,49 @@ impl ProgramFileType {
     pub fn is_json(&self) -> bool {
         matches!(self, ProgramFileType::JSON)
     }
+}
 
-    pub fn to_string(&self) -> String {
-        match self {
-            ProgramFileType::XP => "8xp".to_string(),
-            ProgramFileType::TXT => "txt".to_string(),
-            ProgramFileType::XPTwo => "82p".to_string(),
-            ProgramFileType::XPThree => "83p".to_string(),
-            ProgramFileType::JSON => "json".to_string(),
-        }
+impl fmt::Display for ProgramFileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            ProgramFileType::XP => "8xp",
+            ProgramFileType::TXT => "txt",
+            ProgramFileType::XPTwo => "82p",
+            ProgramFileType::XPThree => "83p",
+            ProgramFileType::JSON => "json",
+        };
+
+        write!(f, "{}", string)
     }
 }
 
 pub fn get_file_type(path: &Path) -> Result<ProgramFileType, TiToolsError> {
     match path.extension() {
-        Some(ext) => match ext.to_str() {
-            Some("8xp") => Ok(ProgramFileType::XP),
-            Some("83p") => Ok(ProgramFileType::XPThree),
-            Some("82p") => Ok(ProgramFileType::XPTwo),
-            Some("txt") => Ok(ProgramFileType::TXT),
-            Some("json") => Ok(ProgramFileType::JSON),
-            _ => Err(TiToolsError::InvalidExtension(
-                ext.to_str().unwrap().to_string(),
-            )),
-        },
+        Some(ext) => ProgramFileType::from_string(&ext.to_string_lossy()),
         None => Err(TiToolsError::MissingExtension),
     }
 }
 
+/// Like `get_file_type`, but files without an extension are recognized by their content
+pub fn detect_file_type(path: &Path) -> Result<ProgramFileType, TiToolsError> {
+    if path.extension().is_some() {
+        return get_file_type(path);
+    }
+
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) => return Err(TiToolsError::FailedToReadFile(err.to_string())),
+    };
+
+    match bytes.get(0..8) {
+        Some(b"**TI83F*") => Ok(ProgramFileType::XP),
+        Some(b"**TI83**") => Ok(ProgramFileType::XPThree),
+        Some(b"**TI82**") => Ok(ProgramFileType::XPTwo),
+        _ if bytes.trim_ascii_start().starts_with(b"{") => Ok(ProgramFileType::JSON),
+        _ => Ok(ProgramFileType::TXT),
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub enum FileType {
     Program,
@@ -484,14 +697,18 @@ impl FileType {
             )),
         }
     }
+}
 
-    pub fn to_string(&self) -> String {
-        match self {
-            FileType::Program => "Program".to_string(),
-            FileType::LockedProgram => "Locked Program".to_string(),
-            FileType::Group => "Group".to_string(),
-            FileType::FlashApplication => "Flash Application".to_string(),
-        }
+impl fmt::Display for FileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            FileType::Program => "Program",
+            FileType::LockedProgram => "Locked Program",
+            FileType::Group => "Group",
+            FileType::FlashApplication => "Flash Application",
+        };
+
+        write!(f, "{}", string)
     }
 }
 
@@ -530,23 +747,76 @@ impl Destination {
             )),
         }
     }
+}
 
-    pub fn to_string(&self) -> String {
-        match self {
-            Destination::RAM => "RAM".to_string(),
-            Destination::Archive => "Archive".to_string(),
+impl fmt::Display for Destination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            Destination::RAM => "RAM",
+            Destination::Archive => "Archive",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+/// Deals with a file already at the output path as the policy says, failing when it has to stay
+pub fn clear_output_path(path: &Path, overwrite: &OverwritePolicy) -> Result<(), TiToolsError> {
+    if path.exists() {
+        match overwrite {
+            OverwritePolicy::Prompt => {
+                eprintln!("A file already exists at the output path, would you like to delete its content and proceed? [y/N]");
+                let mut input = String::new();
+                eprint!("> ");
+                std::io::stderr().flush().unwrap();
+                std::io::stdin().read_line(&mut input).unwrap();
+                let input = input.trim();
+                if input == "y" || input == "Y" {
+                    eprintln!("Deleting existing file");
+
+                    match std::fs::remove_file(path) {
+                        Ok(_) => {
+                            eprintln!("Deleted existing file");
+                        }
+                        Err(err) => {
+                            return Err(TiToolsError::FailedToDeleteFile(err.to_string()));
+                        }
+                    }
+                } else {
+                    return Err(TiToolsError::Quit(
+                        "User chose not to overwrite file".to_string(),
+                    ));
+                }
+            }
+            OverwritePolicy::Force => {}
+            OverwritePolicy::NoClobber => {
+                return Err(TiToolsError::FileAlreadyExists(path.display().to_string()));
+            }
+            OverwritePolicy::Backup => backup_file(path)?,
         }
     }
+
+    Ok(())
 }
 
 fn write_to_file<T: AsRef<[u8]>>(path: &PathBuf, content: T) -> Result<(), TiToolsError> {
     match std::fs::write(path, content) {
         Ok(_) => Ok(()),
-        Err(err) => {
-            return Err(TiToolsError::FailedToWriteFile(
-                path.to_str().unwrap().to_string(),
-                err.to_string(),
-            ))
-        }
+        Err(err) => Err(TiToolsError::FailedToWriteFile(
+            path.to_str().unwrap().to_string(),
+            err.to_string(),
+        )),
+    }
+}
+
+fn backup_file(path: &Path) -> Result<(), TiToolsError> {
+    let backup_path = PathBuf::from(format!("{}.bak", path.display()));
+
+    match std::fs::rename(path, &backup_path) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(TiToolsError::FailedToBackupFile(
+            path.display().to_string(),
+            err.to_string(),
+        )),
     }
 }
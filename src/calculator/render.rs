@@ -0,0 +1,217 @@
+//! Syntax highlighting of decoded programs, and rendering them as standalone HTML
+
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::file::DecodedToken;
+use crate::calculator::program::Program;
+use crate::calculator::tokens::bytes::{ANS, COLON, CUSTOM_LIST, NEWLINE, QUOTE, STORE};
+use crate::calculator::tokens::{definition_key, load_token_definitions};
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Highlight {
+    /// The token that starts a statement, like `Disp` or `For(`
+    Command,
+    /// Any other named token, which gives back a value, like `abs(` or `rand`
+    Function,
+    Variable,
+    String,
+    Number,
+    /// A statement that is only a string, which TI-BASIC programs use as comments
+    Comment,
+    /// Symbols, operators and the newlines and colons between statements
+    Operator,
+}
+
+impl fmt::Display for Highlight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            Highlight::Command => "command",
+            Highlight::Function => "function",
+            Highlight::Variable => "variable",
+            Highlight::String => "string",
+            Highlight::Number => "number",
+            Highlight::Comment => "comment",
+            Highlight::Operator => "operator",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+/// The highlight of every token, in the same order as the tokens
+pub fn highlight(tokens: &[DecodedToken]) -> Vec<Highlight> {
+    let mut highlights = Vec::with_capacity(tokens.len());
+    let mut starts_statement = true;
+    let mut in_string = false;
+    let mut in_comment = false;
+
+    for (index, token) in tokens.iter().enumerate() {
+        let bytes = token.bytes.as_slice();
+        let ends_statement = bytes == [NEWLINE] || (bytes == [COLON] && !in_string);
+
+        let highlight = if ends_statement {
+            in_string = false;
+            in_comment = false;
+            Highlight::Operator
+        } else if in_string && bytes != [STORE] {
+            in_string = bytes != [QUOTE];
+            string_or_comment(in_comment)
+        } else if bytes == [QUOTE] {
+            in_string = true;
+            in_comment = starts_statement && is_comment(&tokens[index + 1..]);
+            string_or_comment(in_comment)
+        } else {
+            // a store closes any string left open
+            in_string = false;
+            classify(token, starts_statement && !is_assignment(&tokens[index..]))
+        };
+
+        highlights.push(highlight);
+        starts_statement = ends_statement;
+    }
+
+    highlights
+}
+
+fn string_or_comment(in_comment: bool) -> Highlight {
+    if in_comment {
+        Highlight::Comment
+    } else {
+        Highlight::String
+    }
+}
+
+/// A statement starting with a quote is a comment when nothing but the string is in it
+fn is_comment(tokens: &[DecodedToken]) -> bool {
+    let mut closed = false;
+
+    for token in tokens {
+        match token.bytes.as_slice() {
+            [NEWLINE] => return true,
+            [COLON] if closed => return true,
+            [STORE] => return false,
+            [QUOTE] => closed = true,
+            _ if closed => return false,
+            _ => {}
+        }
+    }
+
+    true
+}
+
+/// A statement storing a value starts with an expression, even when it starts with a function
+fn is_assignment(tokens: &[DecodedToken]) -> bool {
+    let mut in_string = false;
+
+    for token in tokens {
+        match token.bytes.as_slice() {
+            [STORE] => return true,
+            [QUOTE] => in_string = !in_string,
+            [COLON] if !in_string => return false,
+            [NEWLINE] => return false,
+            _ => {}
+        }
+    }
+
+    false
+}
+
+fn classify(token: &DecodedToken, starts_statement: bool) -> Highlight {
+    match token.bytes.as_slice() {
+        // 0-9, . and ᴇ
+        [0x30..=0x3B] => return Highlight::Number,
+        // A-Z, θ, Ans and ʟ
+        [0x41..=0x5B] | [ANS] | [CUSTOM_LIST] => return Highlight::Variable,
+        // matrices, lists, equations, pictures, graph databases, statistics, window and strings
+        [0x5C | 0x5D | 0x5E | 0x60 | 0x61 | 0x62 | 0x63 | 0xAA, _] => return Highlight::Variable,
+        _ => {}
+    }
+
+    let text = &token.accessible;
+
+    // and, or, xor, nPr and nCr are spelled with spaces around them
+    let named = text.chars().any(|c| c.is_ascii_alphabetic());
+    let infix = text.len() > 1 && text.starts_with(' ') && text.ends_with(' ');
+
+    if !named || infix {
+        Highlight::Operator
+    } else if starts_statement {
+        Highlight::Command
+    } else {
+        Highlight::Function
+    }
+}
+
+/// A standalone HTML page of the program, hovering a token shows its bytes and definitions
+pub fn render_html(program: &Program, tokens: &[DecodedToken]) -> Result<String, TiToolsError> {
+    let definitions = load_token_definitions()?;
+    let highlights = highlight(tokens);
+
+    let mut code = String::new();
+
+    for (token, highlight) in tokens.iter().zip(&highlights) {
+        if token.bytes == [NEWLINE] {
+            code.push('\n');
+            continue;
+        }
+
+        let mut title = token.key.to_string();
+
+        if let Some(definitions) = definitions.get(&definition_key(&token.key)) {
+            for definition in definitions {
+                title.push_str(&format!(
+                    "\n\n{}\n{}",
+                    definition.syntax, definition.description
+                ));
+            }
+        }
+
+        code.push_str(&format!(
+            "<span class=\"{}\" title=\"{}\">{}</span>",
+            highlight,
+            escape_html(&title),
+            escape_html(&token.text)
+        ));
+    }
+
+    let name = escape_html(&program.metadata.name);
+
+    Ok(format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{name}</title>
+<style>
+body {{ background: #1e1e1e; color: #d4d4d4; font-family: sans-serif; }}
+pre {{ font-family: monospace; font-size: 1.1em; line-height: 1.4; }}
+.command {{ color: #569cd6; }}
+.function {{ color: #4ec9b0; }}
+.variable {{ color: #dcdcaa; }}
+.string {{ color: #ce9178; }}
+.number {{ color: #b5cea8; }}
+.comment {{ color: #6a9955; font-style: italic; }}
+.operator {{ color: #d4d4d4; }}
+</style>
+</head>
+<body>
+<h1>{name}</h1>
+<p>{comment}</p>
+<pre><code>{code}</code></pre>
+</body>
+</html>
+"#,
+        name = name,
+        comment = escape_html(&program.header.comment),
+        code = code
+    ))
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
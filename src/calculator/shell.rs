@@ -0,0 +1,498 @@
+//! The headers shells like Ion, MirageOS, DoorsCS and Cesium read a description and icon from
+
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::tokens::bytes::{ASM_84CE_COMPILED, ASM_COMPILED, COLON, NEWLINE, QUOTE};
+use serde::Serialize;
+use std::fmt;
+
+/// Where the Z80 calculators run programs from, pointers in DoorsCS headers are relative to it
+const Z80_USER_MEM: usize = 0x9D95;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ShellKind {
+    Ion,
+    MirageOS,
+    DoorsCS,
+    Cesium,
+}
+
+impl fmt::Display for ShellKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            ShellKind::Ion => "Ion",
+            ShellKind::MirageOS => "MirageOS",
+            ShellKind::DoorsCS => "DoorsCS",
+            ShellKind::Cesium => "Cesium",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Icon {
+    pub width: usize,
+    pub height: usize,
+    /// Cesium icons are palette colors, the others are black and white
+    pub color: bool,
+    /// One per pixel, row by row, 0 or 1 for black and white icons
+    pub pixels: Vec<u8>,
+}
+
+impl Icon {
+    /// Black and white icons keep each row in whole bytes, the leftmost pixel in the highest bit
+    fn from_monochrome(bytes: &[u8], width: usize, height: usize) -> Icon {
+        let row_length = width.div_ceil(8);
+        let mut pixels = Vec::with_capacity(width * height);
+
+        for row in bytes.chunks(row_length).take(height) {
+            for x in 0..width {
+                pixels.push((row[x / 8] >> (7 - x % 8)) & 1);
+            }
+        }
+
+        Icon {
+            width,
+            height,
+            color: false,
+            pixels,
+        }
+    }
+
+    /// The icon as stored in the header
+    fn to_bytes(&self) -> Vec<u8> {
+        if self.color {
+            return self.pixels.clone();
+        }
+
+        let row_length = self.width.div_ceil(8);
+        let mut bytes = vec![0u8; row_length * self.height];
+
+        for (index, pixel) in self.pixels.iter().enumerate() {
+            let (x, y) = (index % self.width, index / self.width);
+
+            if *pixel != 0 {
+                bytes[y * row_length + x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+
+        bytes
+    }
+
+    /// The bytes of the icon as hex digits, how TI-BASIC programs write it
+    pub fn to_hex(&self) -> String {
+        self.to_bytes()
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect()
+    }
+
+    /// A line per row, `#` for black and `.` for white, or two hex digits per pixel in color icons
+    pub fn to_ascii(&self) -> String {
+        self.pixels
+            .chunks(self.width)
+            .map(|row| {
+                row.iter()
+                    .map(|pixel| match (self.color, pixel) {
+                        (true, pixel) => format!("{:02X}", pixel),
+                        (false, 0) => ".".to_string(),
+                        (false, _) => "#".to_string(),
+                    })
+                    .collect::<String>()
+                    + "\n"
+            })
+            .collect()
+    }
+
+    /// Reads an icon written like `to_ascii` writes it, the size and colors of `like`
+    pub fn from_ascii(text: &str, like: &Icon) -> Result<Icon, TiToolsError> {
+        let rows: Vec<&str> = text
+            .lines()
+            .map(str::trim)
+            .filter(|row| !row.is_empty())
+            .collect();
+
+        if rows.len() != like.height {
+            return Err(TiToolsError::InvalidIcon(format!(
+                "{} rows instead of {}",
+                rows.len(),
+                like.height
+            )));
+        }
+
+        let mut pixels = Vec::with_capacity(like.width * like.height);
+
+        for row in rows {
+            let row_pixels = if like.color {
+                let digits: Vec<char> = row.chars().collect();
+
+                digits
+                    .chunks(2)
+                    .map(|pair| {
+                        let pair = pair.iter().collect::<String>();
+                        u8::from_str_radix(&pair, 16).map_err(|_| TiToolsError::InvalidHex(pair))
+                    })
+                    .collect::<Result<Vec<u8>, TiToolsError>>()?
+            } else {
+                row.chars()
+                    .map(|pixel| match pixel {
+                        '#' | '1' => Ok(1),
+                        '.' | '0' => Ok(0),
+                        _ => Err(TiToolsError::InvalidIcon(format!(
+                            "{} is not a pixel, use # for black and . for white",
+                            pixel
+                        ))),
+                    })
+                    .collect::<Result<Vec<u8>, TiToolsError>>()?
+            };
+
+            if row_pixels.len() != like.width {
+                return Err(TiToolsError::InvalidIcon(format!(
+                    "a row of {} pixels instead of {}",
+                    row_pixels.len(),
+                    like.width
+                )));
+            }
+
+            pixels.extend(row_pixels);
+        }
+
+        Ok(Icon {
+            width: like.width,
+            height: like.height,
+            color: like.color,
+            pixels,
+        })
+    }
+
+    /// A grayscale PNG of a black and white icon
+    pub fn to_png(&self) -> Result<Vec<u8>, TiToolsError> {
+        if self.color {
+            return Err(TiToolsError::InvalidIcon(
+                "only black and white icons can be written as PNG, print color icons instead"
+                    .to_string(),
+            ));
+        }
+
+        let mut header = Vec::new();
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // 8 bit grayscale, the default compression, filtering and no interlacing
+        header.extend([8, 0, 0, 0, 0]);
+
+        let mut scanlines = Vec::new();
+
+        for row in self.pixels.chunks(self.width) {
+            // no filter
+            scanlines.push(0);
+            scanlines.extend(
+                row.iter()
+                    .map(|pixel| if *pixel == 0 { 0xFF } else { 0x00 }),
+            );
+        }
+
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        png.extend(png_chunk(b"IHDR", &header));
+        png.extend(png_chunk(b"IDAT", &zlib_stored(&scanlines)));
+        png.extend(png_chunk(b"IEND", &[]));
+
+        Ok(png)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShellHeader {
+    pub kind: ShellKind,
+    pub description: Option<String>,
+    pub icon: Option<Icon>,
+    /// Where the description starts in the body and the characters there is room for
+    description_space: Option<(usize, usize)>,
+    /// Where the icon starts in the body
+    icon_offset: usize,
+    /// TI-BASIC programs write their icon as hex digit tokens
+    hex_icon: bool,
+}
+
+impl ShellHeader {
+    /// `None` when the body starts with no header any of the shells read
+    pub fn detect(body: &[u8]) -> Option<ShellHeader> {
+        match body.get(0..2)? {
+            prefix if prefix == ASM_COMPILED => detect_z80(body),
+            prefix if prefix == ASM_84CE_COMPILED => detect_cesium(body),
+            _ => detect_basic(body),
+        }
+    }
+
+    /// Writes a new description over the old one, code after it would move with a longer one.
+    /// Only the jump in an Ion header tells how long the old description was, the other headers
+    /// end it at the first zero, so shortening one there cannot be undone
+    pub fn write_description(
+        &self,
+        body: &mut [u8],
+        description: &str,
+    ) -> Result<(), TiToolsError> {
+        let (start, room) = self
+            .description_space
+            .ok_or_else(|| TiToolsError::NoShellDescription(self.kind.to_string()))?;
+
+        if !description
+            .chars()
+            .all(|c| c.is_ascii_graphic() || c == ' ')
+        {
+            return Err(TiToolsError::InvalidDescriptionCharacters);
+        }
+
+        if description.len() > room {
+            return Err(TiToolsError::ShellDescriptionTooLong(room));
+        }
+
+        // the rest of the old description is cleared along with its terminator
+        let mut bytes = description.as_bytes().to_vec();
+        bytes.resize(room + 1, 0);
+        body[start..start + room + 1].copy_from_slice(&bytes);
+
+        Ok(())
+    }
+
+    /// Writes a new icon over the old one, it must be the same size
+    pub fn write_icon(&self, body: &mut [u8], icon: &Icon) -> Result<(), TiToolsError> {
+        let old = self
+            .icon
+            .as_ref()
+            .ok_or_else(|| TiToolsError::NoShellIcon(self.kind.to_string()))?;
+
+        if (icon.width, icon.height, icon.color) != (old.width, old.height, old.color) {
+            return Err(TiToolsError::InvalidIcon(format!(
+                "the {} header holds a {}x{} icon",
+                self.kind, old.width, old.height
+            )));
+        }
+
+        let mut bytes = icon.to_bytes();
+
+        if self.hex_icon {
+            bytes = icon.to_hex().into_bytes();
+        }
+
+        body[self.icon_offset..self.icon_offset + bytes.len()].copy_from_slice(&bytes);
+
+        Ok(())
+    }
+}
+
+/// Ion and MirageOS headers start with `ret`, DoorsCS ones with `xor d` and `ret`
+fn detect_z80(body: &[u8]) -> Option<ShellHeader> {
+    match body.get(2..5)? {
+        // ret, jr nc,start, then the description
+        [0xC9, 0x30, jump] => {
+            let mut header = shell_header(body, ShellKind::Ion, Some(5), None)?;
+
+            // the jump past the description shows all the room there is after shortening it,
+            // unless it lands outside the program
+            header.description_space = header.description_space.map(|(start, room)| {
+                let jumped = (*jump as usize).saturating_sub(1);
+
+                if start + jumped < body.len() {
+                    (start, room.max(jumped))
+                } else {
+                    (start, room)
+                }
+            });
+
+            Some(header)
+        }
+        // ret, 1, a 15x15 icon and the description
+        [0xC9, 0x01, _] => {
+            let icon = Icon::from_monochrome(body.get(4..34)?, 15, 15);
+            shell_header(body, ShellKind::MirageOS, Some(34), Some((icon, 4)))
+        }
+        // xor d, ret, jr start, then pointers to the description and a 16x16 icon
+        [0xAA, 0xC9, 0x18] => {
+            let description = pointer(body, 6)?;
+            let icon = match pointer(body, 10)? {
+                Some(offset) => Some((
+                    Icon::from_monochrome(body.get(offset..offset + 32)?, 16, 16),
+                    offset,
+                )),
+                None => None,
+            };
+
+            shell_header(body, ShellKind::DoorsCS, description, icon)
+        }
+        _ => None,
+    }
+}
+
+/// A jump over the header, then 1 with an icon and description or 2 with only a description
+fn detect_cesium(body: &[u8]) -> Option<ShellHeader> {
+    if *body.get(2)? != 0xC3 {
+        return None;
+    }
+
+    match body.get(6)? {
+        1 => {
+            let (width, height) = (*body.get(7)? as usize, *body.get(8)? as usize);
+
+            // an icon with no pixels is no icon at all, and could not be drawn row by row
+            if width == 0 || height == 0 {
+                return shell_header(body, ShellKind::Cesium, Some(9), None);
+            }
+
+            let pixels = body.get(9..9 + width * height)?.to_vec();
+            let icon = Icon {
+                width,
+                height,
+                color: true,
+                pixels,
+            };
+
+            shell_header(
+                body,
+                ShellKind::Cesium,
+                Some(9 + width * height),
+                Some((icon, 9)),
+            )
+        }
+        2 => shell_header(body, ShellKind::Cesium, Some(7), None),
+        _ => None,
+    }
+}
+
+/// DoorsCS TI-BASIC programs start with a `:DCS` line and a line of a quote and 64 hex digits
+fn detect_basic(body: &[u8]) -> Option<ShellHeader> {
+    let first_line = body.split(|byte| *byte == NEWLINE).next()?;
+    let first_line: Vec<u8> = first_line
+        .iter()
+        .copied()
+        .skip_while(|byte| *byte == COLON)
+        .collect();
+
+    if !first_line.starts_with(b"DCS") || !first_line[3..].iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+
+    let icon_offset = body.iter().position(|byte| *byte == NEWLINE)? + 2;
+
+    if body.get(icon_offset - 1) != Some(&QUOTE) {
+        return None;
+    }
+
+    let digits = body.get(icon_offset..icon_offset + 64)?;
+
+    if !digits
+        .iter()
+        .all(|digit| digit.is_ascii_digit() || (b'A'..=b'F').contains(digit))
+    {
+        return None;
+    }
+
+    let bytes: Vec<u8> = digits
+        .chunks(2)
+        .filter_map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect();
+
+    Some(ShellHeader {
+        kind: ShellKind::DoorsCS,
+        description: None,
+        icon: Some(Icon::from_monochrome(&bytes, 16, 16)),
+        description_space: None,
+        icon_offset,
+        hex_icon: true,
+    })
+}
+
+/// A header of compiled code, `None` when the description does not end inside the program
+fn shell_header(
+    body: &[u8],
+    kind: ShellKind,
+    description_start: Option<usize>,
+    icon: Option<(Icon, usize)>,
+) -> Option<ShellHeader> {
+    let (description, description_space) = match description_start {
+        Some(start) => {
+            let room = body.get(start..)?.iter().position(|byte| *byte == 0)?;
+            let description = String::from_utf8_lossy(&body[start..start + room]).to_string();
+
+            (Some(description), Some((start, room)))
+        }
+        None => (None, None),
+    };
+
+    let (icon, icon_offset) = match icon {
+        Some((icon, offset)) => (Some(icon), offset),
+        None => (None, 0),
+    };
+
+    Some(ShellHeader {
+        kind,
+        description,
+        icon,
+        description_space,
+        icon_offset,
+        hex_icon: false,
+    })
+}
+
+/// A DoorsCS pointer as an index into the body, `Some(None)` for a null pointer
+fn pointer(body: &[u8], index: usize) -> Option<Option<usize>> {
+    let address = u16::from_le_bytes([*body.get(index)?, *body.get(index + 1)?]) as usize;
+
+    if address == 0 {
+        return Some(None);
+    }
+
+    // the body starts with the two prefix bytes before userMem
+    address
+        .checked_sub(Z80_USER_MEM)
+        .map(|offset| Some(offset + 2))
+}
+
+fn png_chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+    chunk.extend(kind);
+    chunk.extend(data);
+    chunk.extend(crc32(&chunk[4..]).to_be_bytes());
+
+    chunk
+}
+
+/// Zlib data in uncompressed blocks, icons are too small for compression to matter
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut zlib = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = data.chunks(0xFFFF).collect();
+
+    for (index, block) in blocks.iter().enumerate() {
+        let length = block.len() as u16;
+
+        zlib.push(u8::from(index == blocks.len() - 1));
+        zlib.extend(length.to_le_bytes());
+        zlib.extend((!length).to_le_bytes());
+        zlib.extend(*block);
+    }
+
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + *byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    zlib.extend(((b << 16) | a).to_be_bytes());
+
+    zlib
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFFFFFFu32;
+
+    for byte in data {
+        crc ^= *byte as u32;
+
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
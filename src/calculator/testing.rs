@@ -0,0 +1,169 @@
+//! Test suites for TI-BASIC programs, run with the interpreter and checked against expected output
+
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::file::load_program_tokens;
+use crate::calculator::interpreter::{
+    parse_variable, run_program, RunOptions, RuntimeError, Value,
+};
+use crate::calculator::program::Program;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The default for `max-steps`, matching the `run` command
+pub const DEFAULT_MAX_STEPS: usize = 100000;
+
+/// A test file, naming the program under test relative to itself
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TestSuite {
+    pub program: String,
+    pub tests: Vec<TestCase>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TestCase {
+    pub name: String,
+    /// Answers to `Input` and `Prompt`, in order
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    /// Key codes returned by `getKey`
+    #[serde(default)]
+    pub keys: Vec<u16>,
+    /// Variables set before the program starts
+    #[serde(default)]
+    pub variables: BTreeMap<String, Value>,
+    pub max_steps: Option<usize>,
+    #[serde(default)]
+    pub expect: Expectation,
+}
+
+/// Everything left out is not checked, except that the program must not fail unless `error` is given
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Expectation {
+    /// Every line written by `Disp`, `Pause`, `Input` and `Prompt`
+    pub output: Option<Vec<String>>,
+    /// Values of variables once the program stops
+    #[serde(default)]
+    pub variables: BTreeMap<String, Value>,
+    /// Text the error message must contain, like `DIVIDE BY 0`
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TestResult {
+    pub name: String,
+    pub passed: bool,
+    pub failures: Vec<String>,
+    pub steps: usize,
+    /// Where the program stopped when it failed, expected or not
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RuntimeError>,
+}
+
+impl TestSuite {
+    pub fn from_string(json: &str) -> Result<TestSuite, TiToolsError> {
+        serde_json::from_str(json).map_err(|err| TiToolsError::Json(err.to_string()))
+    }
+}
+
+/// Runs one test case against a program, collecting every expectation it misses
+pub fn run_test(program: &Program, test: &TestCase) -> Result<TestResult, TiToolsError> {
+    let options = RunOptions {
+        inputs: test.inputs.clone(),
+        keys: test.keys.clone(),
+        max_steps: test.max_steps.unwrap_or(DEFAULT_MAX_STEPS),
+        variables: test.variables.clone(),
+    };
+
+    let execution = run_program(program, options)?;
+    let map = load_program_tokens(program)?;
+    let mut failures = Vec::new();
+
+    match (&test.expect.error, &execution.error) {
+        (None, Some(error)) => failures.push(format!("unexpected ERR:{}", error.message)),
+        (Some(expected), None) => failures.push(format!(
+            "expected ERR:{}, but it ran without errors",
+            expected
+        )),
+        (Some(expected), Some(error)) if !error.message.contains(expected.as_str()) => failures
+            .push(format!(
+                "expected ERR:{}, got ERR:{}",
+                expected, error.message
+            )),
+        _ => {}
+    }
+
+    if let Some(expected) = &test.expect.output {
+        for line in 0..expected.len().max(execution.output.len()) {
+            match (expected.get(line), execution.output.get(line)) {
+                (Some(expected), Some(actual)) if expected != actual => failures.push(format!(
+                    "output line {}: expected \"{}\", got \"{}\"",
+                    line + 1,
+                    expected,
+                    actual
+                )),
+                (Some(expected), None) => failures.push(format!(
+                    "output line {}: expected \"{}\", got nothing",
+                    line + 1,
+                    expected
+                )),
+                (None, Some(actual)) => failures.push(format!(
+                    "output line {}: expected nothing, got \"{}\"",
+                    line + 1,
+                    actual
+                )),
+                _ => {}
+            }
+        }
+    }
+
+    for (name, expected) in &test.expect.variables {
+        let variable = match parse_variable(&map, name) {
+            Some(variable) => variable,
+            None => {
+                failures.push(format!("{} is not a real, list or string variable", name));
+                continue;
+            }
+        };
+
+        match execution.variables.get(&variable.name) {
+            Some(actual) if values_match(expected, actual) => {}
+            Some(actual) => {
+                failures.push(format!("{}: expected {}, got {}", name, expected, actual))
+            }
+            None => failures.push(format!(
+                "{}: expected {}, but it was never set",
+                name, expected
+            )),
+        }
+    }
+
+    Ok(TestResult {
+        name: test.name.to_string(),
+        passed: failures.is_empty(),
+        failures,
+        steps: execution.steps,
+        error: execution.error,
+    })
+}
+
+/// Numbers only need to agree to the precision the calculator shows
+fn values_match(expected: &Value, actual: &Value) -> bool {
+    let close = |expected: f64, actual: f64| {
+        (expected - actual).abs() <= 1e-9 * expected.abs().max(actual.abs()).max(1.0)
+    };
+
+    match (expected, actual) {
+        (Value::Number(expected), Value::Number(actual)) => close(*expected, *actual),
+        (Value::List(expected), Value::List(actual)) => {
+            expected.len() == actual.len()
+                && expected
+                    .iter()
+                    .zip(actual)
+                    .all(|(expected, actual)| close(*expected, *actual))
+        }
+        _ => expected == actual,
+    }
+}
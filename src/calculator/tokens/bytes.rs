@@ -0,0 +1,79 @@
+//! Bytes of the tokens that the analysis tools look for, two byte tokens start with their prefix
+
+pub const STORE: u8 = 0x04;
+pub const CLOSE_BRACKET: u8 = 0x07;
+pub const OPEN_BRACE: u8 = 0x08;
+pub const CLOSE_BRACE: u8 = 0x09;
+pub const INVERSE: u8 = 0x0C;
+pub const SQUARE: u8 = 0x0D;
+pub const OPEN_PARENTHESIS: u8 = 0x10;
+pub const CLOSE_PARENTHESIS: u8 = 0x11;
+pub const SPACE: u8 = 0x29;
+pub const QUOTE: u8 = 0x2A;
+pub const COMMA: u8 = 0x2B;
+pub const FACTORIAL: u8 = 0x2D;
+pub const ZERO: u8 = 0x30;
+pub const DECIMAL_POINT: u8 = 0x3A;
+pub const EXPONENT: u8 = 0x3B;
+pub const OR: u8 = 0x3C;
+pub const XOR: u8 = 0x3D;
+pub const COLON: u8 = 0x3E;
+pub const NEWLINE: u8 = 0x3F;
+pub const AND: u8 = 0x40;
+pub const THETA: u8 = 0x5B;
+pub const MATRIX: u8 = 0x5C;
+pub const LIST: u8 = 0x5D;
+pub const PRGM: u8 = 0x5F;
+pub const EQUAL: u8 = 0x6A;
+pub const LESS: u8 = 0x6B;
+pub const GREATER: u8 = 0x6C;
+pub const LESS_OR_EQUAL: u8 = 0x6D;
+pub const GREATER_OR_EQUAL: u8 = 0x6E;
+pub const NOT_EQUAL: u8 = 0x6F;
+pub const PLUS: u8 = 0x70;
+pub const MINUS: u8 = 0x71;
+pub const ANS: u8 = 0x72;
+pub const MULTIPLY: u8 = 0x82;
+pub const DIVIDE: u8 = 0x83;
+pub const PERMUTATIONS: u8 = 0x94;
+pub const COMBINATIONS: u8 = 0x95;
+pub const STRING: u8 = 0xAA;
+pub const RAND: u8 = 0xAB;
+pub const PI: u8 = 0xAC;
+pub const GET_KEY: u8 = 0xAD;
+pub const NEGATIVE: u8 = 0xB0;
+pub const DIM: u8 = 0xB5;
+pub const NOT: u8 = 0xB8;
+pub const IF: u8 = 0xCE;
+pub const THEN: u8 = 0xCF;
+pub const ELSE: u8 = 0xD0;
+pub const WHILE: u8 = 0xD1;
+pub const REPEAT: u8 = 0xD2;
+pub const FOR: u8 = 0xD3;
+pub const END: u8 = 0xD4;
+pub const RETURN: u8 = 0xD5;
+pub const LBL: u8 = 0xD6;
+pub const GOTO: u8 = 0xD7;
+pub const PAUSE: u8 = 0xD8;
+pub const STOP: u8 = 0xD9;
+pub const IS_GREATER: u8 = 0xDA;
+pub const DS_LESS: u8 = 0xDB;
+pub const INPUT: u8 = 0xDC;
+pub const PROMPT: u8 = 0xDD;
+pub const DISP: u8 = 0xDE;
+pub const OUTPUT: u8 = 0xE0;
+pub const CLR_HOME: u8 = 0xE1;
+pub const MENU: u8 = 0xE6;
+pub const CUSTOM_LIST: u8 = 0xEB;
+pub const POWER: u8 = 0xF0;
+
+pub const EULER: [u8; 2] = [0xBB, 0x31];
+pub const DEL_VAR: [u8; 2] = [0xBB, 0x54];
+
+/// Assembly programs start with one of these, the programs typed as hex digits with the `Prgm` ones
+pub const ASM_PRGM: [u8; 2] = [0xBB, 0x6C];
+pub const ASM_COMPILED: [u8; 2] = [0xBB, 0x6D];
+pub const ASM_84C_PRGM: [u8; 2] = [0xEF, 0x68];
+pub const ASM_84C_COMPILED: [u8; 2] = [0xEF, 0x69];
+pub const ASM_84CE_PRGM: [u8; 2] = [0xEF, 0x7A];
+pub const ASM_84CE_COMPILED: [u8; 2] = [0xEF, 0x7B];
@@ -1,9 +1,13 @@
 use crate::calculator::errors::TiToolsError;
 use crate::calculator::models::Model;
 use crate::calculator::DisplayMode;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt;
+
+pub mod bytes;
+pub mod search;
 
 #[derive(Debug, PartialEq, Eq, Deserialize)]
 pub struct OsVersion {
@@ -68,7 +72,7 @@ impl PartialOrd for OsVersion {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Translation {
     #[serde(rename = "ti-ascii")]
     pub ti_ascii: String,
@@ -94,6 +98,12 @@ pub struct Map {
     pub map: HashMap<String, Translation>,
 }
 
+impl Default for Map {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Map {
     pub fn new() -> Map {
         Map {
@@ -170,10 +180,66 @@ impl Map {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TokenDefinition {
     pub syntax: String,
     pub description: String,
+    /// The arguments in the order of the syntax
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arguments: Vec<TokenArgument>,
+    /// The type of value the token gives back, missing for commands
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub returns: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+    /// The models this form of the token works on, every model with the token when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenArgument {
+    pub name: String,
+    /// Like `number`, `list`, `string`, `variable`, `label` or `condition`
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub description: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+}
+
+impl TokenDefinition {
+    /// The syntax as a code block followed by the description and whatever else is known
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("```\n{}\n```\n{}", self.syntax, self.description);
+
+        if !self.arguments.is_empty() {
+            markdown.push_str("\n\n**Arguments**\n");
+
+            for argument in &self.arguments {
+                let optional = if argument.optional { ", optional" } else { "" };
+
+                markdown.push_str(&format!(
+                    "\n- `{}` ({}{}): {}",
+                    argument.name, argument.kind, optional, argument.description
+                ));
+            }
+        }
+
+        if let Some(returns) = &self.returns {
+            markdown.push_str(&format!("\n\n**Returns** {}", returns));
+        }
+
+        if !self.models.is_empty() {
+            markdown.push_str(&format!("\n\n**Models** {}", self.models.join(", ")));
+        }
+
+        for example in &self.examples {
+            markdown.push_str(&format!("\n\n```\n{}\n```", example));
+        }
+
+        markdown
+    }
 }
 
 pub fn load_token_definitions() -> Result<HashMap<String, Vec<TokenDefinition>>, TiToolsError> {
@@ -188,7 +254,69 @@ pub fn load_token_definitions() -> Result<HashMap<String, Vec<TokenDefinition>>,
     Ok(token_definitions)
 }
 
+/// `$BB$6D` in the token map is `0xBB6D` in the token definitions
+pub fn definition_key(key: &str) -> String {
+    format!("0x{}", key.replace('$', ""))
+}
+
+#[derive(Debug, Serialize)]
+pub struct DefinitionCoverage {
+    /// Keys of the tokens without a definition, like `$EF$79`
+    pub undefined: Vec<String>,
+    /// Keys of the definitions without a token, like `0xBB6D`
+    pub orphaned: Vec<String>,
+}
+
+/// Compares the token table against the token definitions, both ways
+pub fn check_definitions(
+    table: &[TokenEntry],
+    definitions: &HashMap<String, Vec<TokenDefinition>>,
+) -> DefinitionCoverage {
+    let undefined = table
+        .iter()
+        .filter(|entry| !definitions.contains_key(&definition_key(&entry.key)))
+        .map(|entry| entry.key.to_string())
+        .collect();
+
+    let keys: Vec<String> = table
+        .iter()
+        .map(|entry| definition_key(&entry.key))
+        .collect();
+
+    let mut orphaned: Vec<String> = definitions
+        .keys()
+        .filter(|key| !keys.contains(key))
+        .map(|key| key.to_string())
+        .collect();
+    orphaned.sort();
+
+    DefinitionCoverage {
+        undefined,
+        orphaned,
+    }
+}
+
 pub fn load_tokens(target: &OsVersion) -> Result<Map, TiToolsError> {
+    let mut map = Map::new();
+
+    for (key, tokens) in parse_tokens()? {
+        let new_tokens = tokens
+            .into_iter()
+            .filter(|token| token.since <= *target)
+            .filter(|token| token.until.is_none() || token.until.as_ref().unwrap() >= target);
+
+        for token in new_tokens {
+            for (lang, translation) in token.langs {
+                map.insert(format!("{} {}", key, lang), translation);
+            }
+        }
+    }
+
+    Ok(map)
+}
+
+/// Every version of every token, with the keys of two byte tokens joined like `$BB$6D`
+fn parse_tokens() -> Result<Vec<(String, Vec<Token>)>, TiToolsError> {
     let json_data = include_str!("./standard_tokens/8X.json");
 
     let tokens: std::collections::BTreeMap<String, TokenData> =
@@ -197,44 +325,168 @@ pub fn load_tokens(target: &OsVersion) -> Result<Map, TiToolsError> {
             Err(err) => return Err(TiToolsError::Json(err.to_string())),
         };
 
-    let mut map = Map::new();
+    let mut flat_tokens = Vec::new();
 
     for (key, token_data) in tokens {
         match token_data {
-            TokenData::Single(tokens) => {
-                let new_tokens: Vec<Token> = tokens
-                    .into_iter()
-                    .filter(|token| token.since <= *target)
-                    .filter(|token| {
-                        token.until.is_none() || token.until.as_ref().unwrap() >= target
-                    })
-                    .collect();
-
-                for token in new_tokens {
-                    for (lang, translation) in token.langs {
-                        map.insert(format!("{} {}", key, lang), translation);
-                    }
-                }
-            }
+            TokenData::Single(tokens) => flat_tokens.push((key, tokens)),
             TokenData::Nested(nested_tokens) => {
                 for (sub_key, tokens) in nested_tokens {
-                    let full_key = format!("{}{}", key, sub_key);
-                    let new_tokens: Vec<Token> = tokens
-                        .into_iter()
-                        .filter(|token| token.since <= *target)
-                        .filter(|token| {
-                            token.until.is_none() || token.until.as_ref().unwrap() >= target
-                        })
-                        .collect();
-                    for token in new_tokens {
-                        for (lang, translation) in token.langs {
-                            map.insert(format!("{} {}", full_key, lang), translation);
-                        }
-                    }
+                    flat_tokens.push((format!("{}{}", key, sub_key), tokens));
                 }
             }
         }
     }
 
-    Ok(map)
+    Ok(flat_tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenCategory {
+    ControlFlow,
+    Io,
+    Math,
+    Probability,
+    Lists,
+    Matrices,
+    Statistics,
+    Finance,
+    Draw,
+    Graph,
+    Strings,
+    Variables,
+    SystemVariables,
+    Settings,
+    Operators,
+    Numbers,
+    Syntax,
+    Memory,
+    Clock,
+    Assembly,
+    Characters,
+    Editor,
+}
+
+impl TokenCategory {
+    pub fn all() -> Vec<TokenCategory> {
+        vec![
+            TokenCategory::ControlFlow,
+            TokenCategory::Io,
+            TokenCategory::Math,
+            TokenCategory::Probability,
+            TokenCategory::Lists,
+            TokenCategory::Matrices,
+            TokenCategory::Statistics,
+            TokenCategory::Finance,
+            TokenCategory::Draw,
+            TokenCategory::Graph,
+            TokenCategory::Strings,
+            TokenCategory::Variables,
+            TokenCategory::SystemVariables,
+            TokenCategory::Settings,
+            TokenCategory::Operators,
+            TokenCategory::Numbers,
+            TokenCategory::Syntax,
+            TokenCategory::Memory,
+            TokenCategory::Clock,
+            TokenCategory::Assembly,
+            TokenCategory::Characters,
+            TokenCategory::Editor,
+        ]
+    }
+
+    pub fn from_string(category: &str) -> Result<TokenCategory, TiToolsError> {
+        TokenCategory::all()
+            .into_iter()
+            .find(|known| known.to_string() == category)
+            .ok_or_else(|| TiToolsError::Match(category.to_string(), "TokenCategory".to_string()))
+    }
+}
+
+impl fmt::Display for TokenCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            TokenCategory::ControlFlow => "control-flow",
+            TokenCategory::Io => "io",
+            TokenCategory::Math => "math",
+            TokenCategory::Probability => "probability",
+            TokenCategory::Lists => "lists",
+            TokenCategory::Matrices => "matrices",
+            TokenCategory::Statistics => "statistics",
+            TokenCategory::Finance => "finance",
+            TokenCategory::Draw => "draw",
+            TokenCategory::Graph => "graph",
+            TokenCategory::Strings => "strings",
+            TokenCategory::Variables => "variables",
+            TokenCategory::SystemVariables => "system-variables",
+            TokenCategory::Settings => "settings",
+            TokenCategory::Operators => "operators",
+            TokenCategory::Numbers => "numbers",
+            TokenCategory::Syntax => "syntax",
+            TokenCategory::Memory => "memory",
+            TokenCategory::Clock => "clock",
+            TokenCategory::Assembly => "assembly",
+            TokenCategory::Characters => "characters",
+            TokenCategory::Editor => "editor",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+/// The category of every token, keyed like the token map without the language, `$BB$6D`
+pub fn load_token_categories() -> Result<HashMap<String, TokenCategory>, TiToolsError> {
+    let json_data = include_str!("./token_categories.json");
+
+    serde_json::from_str(json_data).map_err(|err| TiToolsError::Json(err.to_string()))
+}
+
+/// A token with every version of it, for listing tokens rather than encoding with them
+#[derive(Debug)]
+pub struct TokenEntry {
+    /// The key of the token in the token map, like `$BB$6D`
+    pub key: String,
+    pub bytes: Vec<u8>,
+    pub category: TokenCategory,
+    /// The versions of the token from oldest to newest, each with the models it is on
+    pub versions: Vec<Token>,
+}
+
+impl TokenEntry {
+    /// The version of the token on a model, `None` when the model does not have it
+    pub fn version_for(&self, target: &OsVersion) -> Option<&Token> {
+        self.versions.iter().rev().find(|token| {
+            token.since <= *target
+                && (token.until.is_none() || token.until.as_ref().unwrap() >= target)
+        })
+    }
+}
+
+/// Every token of every model in byte order, with its category and versions
+pub fn load_token_table() -> Result<Vec<TokenEntry>, TiToolsError> {
+    let categories = load_token_categories()?;
+    let mut table = Vec::new();
+
+    for (key, versions) in parse_tokens()? {
+        let category = match categories.get(&key) {
+            Some(category) => *category,
+            None => return Err(TiToolsError::Match(key, "token categories".to_string())),
+        };
+
+        let bytes = key
+            .split('$')
+            .skip(1)
+            .filter_map(|byte| u8::from_str_radix(byte, 16).ok())
+            .collect();
+
+        table.push(TokenEntry {
+            key,
+            bytes,
+            category,
+            versions,
+        });
+    }
+
+    Ok(table)
 }
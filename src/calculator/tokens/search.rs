@@ -0,0 +1,222 @@
+//! Ranked token search, matching every spelling of a token loosely and the words of its definitions
+
+use super::{definition_key, Map, TokenDefinition, Translation};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchKind {
+    Exact,
+    Prefix,
+    Contains,
+    Fuzzy,
+    Description,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchMatch {
+    /// The bytes of the token like `0xBB0A`, the same keys as the token definitions
+    pub byte: String,
+    /// The spelling of the token that matched best
+    pub token: String,
+    #[serde(rename = "match")]
+    pub kind: MatchKind,
+    /// Higher is better, exact matches score 100
+    pub score: u32,
+}
+
+/// Finds the tokens closest to `query`, best first, at most `limit` of them
+pub fn search_tokens(
+    map: &Map,
+    definitions: &HashMap<String, Vec<TokenDefinition>>,
+    query: &str,
+    limit: usize,
+) -> Vec<SearchMatch> {
+    let query = query.trim().to_lowercase();
+    let mut matches: Vec<SearchMatch> = Vec::new();
+
+    if query.is_empty() {
+        return matches;
+    }
+
+    for (key, translation) in &map.map {
+        let Some(key) = key.strip_suffix(" en") else {
+            continue;
+        };
+
+        let byte = definition_key(key);
+
+        let spelling_match = spellings(translation)
+            .into_iter()
+            .filter_map(|spelling| {
+                score_spelling(&query, &spelling.to_lowercase())
+                    .map(|(kind, score)| (kind, score, spelling))
+            })
+            .max_by_key(|(_, score, _)| *score);
+
+        let description_match = definitions
+            .get(&byte)
+            .and_then(|definitions| score_definitions(&query, definitions))
+            .map(|score| {
+                (
+                    MatchKind::Description,
+                    score,
+                    translation.accessible.to_string(),
+                )
+            });
+
+        let best = match (spelling_match, description_match) {
+            (Some(spelling), Some(description)) if description.1 > spelling.1 => Some(description),
+            (Some(spelling), _) => Some(spelling),
+            (None, description) => description,
+        };
+
+        if let Some((kind, score, token)) = best {
+            matches.push(SearchMatch {
+                byte,
+                token,
+                kind,
+                score,
+            });
+        }
+    }
+
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(a.token.len().cmp(&b.token.len()))
+            .then(a.byte.cmp(&b.byte))
+    });
+    matches.truncate(limit);
+
+    matches
+}
+
+/// The display, accessible and ti-ascii spellings, the last is stored as hex in the token data
+fn spellings(translation: &Translation) -> Vec<String> {
+    let ti_ascii_bytes: Vec<u8> = (0..translation.ti_ascii.len())
+        .step_by(2)
+        .filter_map(|index| translation.ti_ascii.get(index..index + 2))
+        .filter_map(|byte| u8::from_str_radix(byte, 16).ok())
+        .collect();
+
+    let mut spellings = vec![
+        translation.display.trim().to_string(),
+        translation.accessible.trim().to_string(),
+    ];
+
+    // ti-ascii spellings with calculator only characters like ⁻¹ cannot be typed anyway
+    if ti_ascii_bytes
+        .iter()
+        .all(|byte| byte.is_ascii_graphic() || *byte == b' ')
+    {
+        spellings.push(String::from_utf8_lossy(&ti_ascii_bytes).trim().to_string());
+    }
+
+    spellings.retain(|spelling| !spelling.is_empty());
+    spellings.dedup();
+
+    spellings
+}
+
+fn score_spelling(query: &str, spelling: &str) -> Option<(MatchKind, u32)> {
+    // most function tokens end with a parenthesis that nobody types when searching
+    let bare = spelling.trim_end_matches('(');
+
+    if spelling == query || bare == query {
+        return Some((MatchKind::Exact, 100));
+    }
+
+    let length_difference = bare.chars().count().abs_diff(query.chars().count()) as u32;
+
+    if bare.starts_with(query) {
+        return Some((MatchKind::Prefix, 90 - length_difference.min(30)));
+    }
+
+    if bare.contains(query) {
+        return Some((MatchKind::Contains, 60 - length_difference.min(30)));
+    }
+
+    // a couple of typos are allowed, more for longer queries, none in queries too short to tell
+    let query_length = query.chars().count();
+    let distance = edit_distance(query, bare);
+
+    if query_length >= 3 && distance <= (query_length / 3).max(1) {
+        return Some((MatchKind::Fuzzy, 70 - 10 * distance as u32));
+    }
+
+    None
+}
+
+/// The share of the words in the query found in a definition, when at least half of them are
+fn score_definitions(query: &str, definitions: &[TokenDefinition]) -> Option<u32> {
+    let words: Vec<&str> = query
+        .split_whitespace()
+        .filter(|word| word.len() > 2)
+        .collect();
+
+    if words.is_empty() {
+        return None;
+    }
+
+    definitions
+        .iter()
+        .filter_map(|definition| {
+            let text = format!("{} {}", definition.syntax, definition.description).to_lowercase();
+            let text_words: Vec<&str> = text
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .collect();
+
+            // short words only match whole words, so sin does not find every single
+            let found = words
+                .iter()
+                .filter(|word| {
+                    text_words.iter().any(|text_word| {
+                        text_word == *word || (word.len() > 3 && text_word.starts_with(*word))
+                    })
+                })
+                .count();
+
+            if found * 2 < words.len() {
+                return None;
+            }
+
+            Some(20 + (30 * found / words.len()) as u32)
+        })
+        .max()
+}
+
+/// The fewest single character edits that turn one string into the other, swapping two
+/// neighbouring characters counts as one edit since it is the most common typo
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = distances[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = substitution
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
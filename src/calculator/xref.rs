@@ -0,0 +1,298 @@
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::file::{decode_program, DecodedToken};
+use crate::calculator::lint::{menu_labels, split_statements, Statement};
+use crate::calculator::program::Program;
+use crate::calculator::tokens::bytes::{
+    CUSTOM_LIST, DEL_VAR, DS_LESS, FOR, GOTO, INPUT, IS_GREATER, LBL, LIST, MATRIX, MENU, PRGM,
+    PROMPT, QUOTE, STORE, STRING, THETA,
+};
+use crate::calculator::DisplayMode;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableKind {
+    Real,
+    List,
+    Matrix,
+    String,
+}
+
+impl fmt::Display for VariableKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            VariableKind::Real => "real",
+            VariableKind::List => "list",
+            VariableKind::Matrix => "matrix",
+            VariableKind::String => "string",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LabelReference {
+    pub command: String,
+    pub line: usize,
+}
+
+/// A label, the lines it is defined on and the `Goto` and `Menu(` statements that jump to it
+#[derive(Debug, Serialize)]
+pub struct LabelXref {
+    pub name: String,
+    pub defined: Vec<usize>,
+    pub references: Vec<LabelReference>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CallXref {
+    pub name: String,
+    pub lines: Vec<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VariableXref {
+    pub name: String,
+    pub kind: VariableKind,
+    pub reads: Vec<usize>,
+    pub writes: Vec<usize>,
+}
+
+/// Where the labels, called programs and variables of a program are used,
+/// lines start at 1 with the first line of the program body
+#[derive(Debug, Serialize)]
+pub struct Xref {
+    pub labels: Vec<LabelXref>,
+    pub calls: Vec<CallXref>,
+    pub variables: Vec<VariableXref>,
+}
+
+impl Xref {
+    /// Moves every line by `lines`, for files where the program body does not start on the first line
+    pub fn shift_lines(&mut self, lines: usize) {
+        for label in &mut self.labels {
+            label.defined.iter_mut().for_each(|line| *line += lines);
+            label
+                .references
+                .iter_mut()
+                .for_each(|reference| reference.line += lines);
+        }
+
+        for call in &mut self.calls {
+            call.lines.iter_mut().for_each(|line| *line += lines);
+        }
+
+        for variable in &mut self.variables {
+            variable.reads.iter_mut().for_each(|line| *line += lines);
+            variable.writes.iter_mut().for_each(|line| *line += lines);
+        }
+    }
+}
+
+/// Cross-references the body of a program, names are spelled the way `display_mode` does
+pub fn xref_program(program: &Program, display_mode: &DisplayMode) -> Result<Xref, TiToolsError> {
+    let tokens = decode_program(program, display_mode)?;
+
+    Ok(xref(&tokens))
+}
+
+pub(crate) fn xref(tokens: &[DecodedToken]) -> Xref {
+    let statements = split_statements(tokens);
+
+    let mut labels: BTreeMap<String, LabelXref> = BTreeMap::new();
+    let mut calls: BTreeMap<String, CallXref> = BTreeMap::new();
+    let mut variables: BTreeMap<(VariableKind, String), VariableXref> = BTreeMap::new();
+
+    for statement in &statements {
+        match statement.command() {
+            Some(LBL) => label_entry(&mut labels, statement.argument())
+                .defined
+                .push(statement.line),
+            Some(GOTO) => label_entry(&mut labels, statement.argument())
+                .references
+                .push(LabelReference {
+                    command: "Goto".to_string(),
+                    line: statement.line,
+                }),
+            Some(MENU) => {
+                for name in menu_labels(statement) {
+                    label_entry(&mut labels, name)
+                        .references
+                        .push(LabelReference {
+                            command: "Menu(".to_string(),
+                            line: statement.line,
+                        });
+                }
+            }
+            _ => {
+                for (name, line) in statement_calls(statement) {
+                    let call = calls.entry(name.clone()).or_insert(CallXref {
+                        name,
+                        lines: Vec::new(),
+                    });
+                    push_line(&mut call.lines, line);
+                }
+
+                for access in statement_variables(statement) {
+                    let variable = variables
+                        .entry((access.kind, access.name.clone()))
+                        .or_insert(VariableXref {
+                            name: access.name,
+                            kind: access.kind,
+                            reads: Vec::new(),
+                            writes: Vec::new(),
+                        });
+
+                    if access.read {
+                        push_line(&mut variable.reads, statement.line);
+                    }
+
+                    if access.write {
+                        push_line(&mut variable.writes, statement.line);
+                    }
+                }
+            }
+        }
+    }
+
+    Xref {
+        labels: labels.into_values().collect(),
+        calls: calls.into_values().collect(),
+        variables: variables.into_values().collect(),
+    }
+}
+
+fn label_entry(labels: &mut BTreeMap<String, LabelXref>, name: String) -> &mut LabelXref {
+    labels.entry(name.clone()).or_insert(LabelXref {
+        name,
+        defined: Vec::new(),
+        references: Vec::new(),
+    })
+}
+
+fn push_line(lines: &mut Vec<usize>, line: usize) {
+    if lines.last() != Some(&line) {
+        lines.push(line);
+    }
+}
+
+struct VariableAccess {
+    name: String,
+    kind: VariableKind,
+    read: bool,
+    write: bool,
+}
+
+/// Program and custom list names are made of A-Z, θ and 0-9
+fn is_name_token(token: &DecodedToken) -> bool {
+    matches!(token.bytes.as_slice(), [0x30..=0x39 | 0x41..=THETA])
+}
+
+fn statement_calls(statement: &Statement) -> Vec<(String, usize)> {
+    let tokens = statement.tokens;
+    let mut calls = Vec::new();
+
+    for (index, token) in tokens.iter().enumerate() {
+        if token.bytes != [PRGM] {
+            continue;
+        }
+
+        let name = tokens[index + 1..]
+            .iter()
+            .take_while(|token| is_name_token(token))
+            .map(|token| token.text.as_str())
+            .collect::<String>();
+
+        if !name.is_empty() {
+            calls.push((name, token.line));
+        }
+    }
+
+    calls
+}
+
+/// The variables used by a statement, outside of strings and program names
+fn statement_variables(statement: &Statement) -> Vec<VariableAccess> {
+    let tokens = statement.tokens;
+    let command = statement.command();
+    let mut accesses = Vec::new();
+    let mut in_string = false;
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let token = &tokens[index];
+
+        match token.bytes.as_slice() {
+            [QUOTE] => {
+                in_string = !in_string;
+                index += 1;
+                continue;
+            }
+            [STORE] => in_string = false,
+            [PRGM] if !in_string => {
+                index += 1 + tokens[index + 1..]
+                    .iter()
+                    .take_while(|token| is_name_token(token))
+                    .count();
+                continue;
+            }
+            _ => {}
+        }
+
+        if in_string {
+            index += 1;
+            continue;
+        }
+
+        let (kind, length) = match token.bytes.as_slice() {
+            [0x41..=THETA] => (VariableKind::Real, 1),
+            [LIST, _] => (VariableKind::List, 1),
+            [MATRIX, _] => (VariableKind::Matrix, 1),
+            [STRING, _] => (VariableKind::String, 1),
+            [CUSTOM_LIST] => {
+                let length = tokens[index + 1..]
+                    .iter()
+                    .take(5)
+                    .take_while(|token| is_name_token(token))
+                    .count();
+
+                (VariableKind::List, 1 + length)
+            }
+            _ => {
+                index += 1;
+                continue;
+            }
+        };
+
+        let previous = index
+            .checked_sub(1)
+            .map(|index| tokens[index].bytes.as_slice());
+
+        let write = match previous {
+            Some([STORE]) => true,
+            Some(bytes) if bytes == DEL_VAR => true,
+            // the loop variable of For( and the variable of IS>( and DS<( come first
+            Some([FOR | IS_GREATER | DS_LESS]) => index == 1,
+            _ => matches!(command, Some(INPUT | PROMPT)),
+        };
+
+        let read = !write || matches!(command, Some(IS_GREATER | DS_LESS)) && index == 1;
+
+        accesses.push(VariableAccess {
+            name: tokens[index..index + length]
+                .iter()
+                .map(|token| token.text.as_str())
+                .collect(),
+            kind,
+            read,
+            write,
+        });
+
+        index += length;
+    }
+
+    accesses
+}
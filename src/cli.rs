@@ -9,9 +9,16 @@ pub struct Cli {
     pub github: String,
     pub version: String,
     pub commands: Vec<Command>,
+    pub global_args: Vec<Arg>,
     pub default_command: Option<String>,
 }
 
+impl Default for Cli {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Cli {
     pub fn new() -> Cli {
         Cli {
@@ -22,6 +29,7 @@ impl Cli {
             github: env!("CARGO_PKG_REPOSITORY").to_string(),
             version: env!("CARGO_PKG_VERSION").to_string(),
             commands: Vec::new(),
+            global_args: Vec::new(),
             default_command: None,
         }
     }
@@ -64,10 +72,21 @@ impl Cli {
 
     /// Add a command to the cli
     pub fn with_command(mut self, command: Command) -> Cli {
+        let command = command.with_args(self.global_args.to_vec());
         self.commands.push(command);
         self
     }
 
+    /// Add an argument that is accepted by every command
+    pub fn with_global_arg(mut self, mut arg: Arg) -> Cli {
+        arg.global = true;
+        for command in &mut self.commands {
+            command.args.push(arg.clone());
+        }
+        self.global_args.push(arg);
+        self
+    }
+
     /// Set the default command to be run if no command is specified, if no default command is set the help command will be run
     pub fn with_default_command(mut self, default_command: &str) -> Cli {
         self.default_command = Some(default_command.to_string());
@@ -77,7 +96,6 @@ impl Cli {
     /// Match the command being passed in the arguments
     pub fn match_commands(&self) -> &Command {
         let args: Vec<String> = env::args().collect();
-        if args.contains(&"--help".to_string()) || args.contains(&"-h".to_string()) {}
 
         if args.len() <= 1 {
             if self.default_command.is_some() {
@@ -153,9 +171,15 @@ impl Command {
         self
     }
 
-    fn check_if_required_args_are_present(&self, env_args: &Vec<String>, arg: &Arg) {
-        if arg.requires.is_some() {
-            for required in arg.requires.as_ref().unwrap() {
+    /// Add several arguments to the command
+    pub fn with_args(mut self, args: Vec<Arg>) -> Command {
+        self.args.extend(args);
+        self
+    }
+
+    fn check_if_required_args_are_present(&self, env_args: &[String], arg: &Arg) {
+        if let Some(requires) = &arg.requires {
+            for required in requires {
                 let required_arg = self.find_arg(required).unwrap();
                 if !env_args
                     .iter()
@@ -174,6 +198,24 @@ impl Command {
         }
     }
 
+    fn check_if_conflicting_args_are_present(&self, env_args: &[String], arg: &Arg) {
+        if let Some(conflicts) = &arg.conflicts {
+            for conflict in conflicts {
+                let conflicting_arg = self.find_arg(conflict).unwrap();
+                if env_args.iter().any(|s| {
+                    *s == format!("-{}", conflicting_arg.short)
+                        || *s == format!("--{}", conflicting_arg.long)
+                }) {
+                    eprintln!(
+                        "The argument \"{}\" cannot be used with the argument \"{}\"",
+                        arg.name, conflict
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
     fn find_arg(&self, arg_name: &str) -> Option<&Arg> {
         self.args.iter().find(|&arg| arg.name == arg_name)
     }
@@ -191,6 +233,7 @@ impl Command {
 
                 if found {
                     self.check_if_required_args_are_present(&args, arg);
+                    self.check_if_conflicting_args_are_present(&args, arg);
                 }
 
                 found
@@ -204,26 +247,26 @@ impl Command {
             .iter()
             .enumerate()
             .find(|(_, option)| option.name == option_name)
-            .and_then(|(index, option)| {
+            .map(|(index, option)| {
                 let args: Vec<String> = env::args().collect();
                 if args.len() <= 2 + (index) {
-                    return Some(Value::Missing(format!(
+                    return Value::Missing(format!(
                         "{} could not be found in its location ({})",
                         option_name,
                         index + 1
-                    )));
+                    ));
                 }
 
                 let value = args[1 + (index + 1)].to_string();
 
                 if value == "--" && option.required {
-                    return Some(Value::Missing(format!(
+                    return Value::Missing(format!(
                         "{} is a required value and must be specified",
                         option_name
-                    )));
+                    ));
                 }
 
-                return Some(Value::Present(value));
+                Value::Present(value)
             })
             .unwrap()
     }
@@ -239,12 +282,15 @@ impl Command {
                     *s == format!("-{}", arg.short) || *s == format!("--{}", arg.long)
                 });
 
+                if arg_index.is_some() {
+                    self.check_if_required_args_are_present(&args, arg);
+                    self.check_if_conflicting_args_are_present(&args, arg);
+                }
+
                 let value = arg_index.and_then(|index| args.get(index + 1));
-                value
-                    .or_else(|| arg.default_value.as_ref())
-                    .map(|s| s.to_string())
+                value.or(arg.default_value.as_ref()).map(|s| s.to_string())
             })
-            .map(|value| Value::Present(value))
+            .map(Value::Present)
             .unwrap_or(Value::Missing(format!("{} could not be found", arg_name)))
     }
 }
@@ -272,6 +318,7 @@ impl CmdOption {
     }
 }
 
+#[derive(Clone)]
 pub struct Arg {
     pub name: String,
     pub description: String,
@@ -280,7 +327,9 @@ pub struct Arg {
     pub value_name: Option<String>,
     pub default_value: Option<String>,
     pub requires: Option<Vec<String>>,
+    pub conflicts: Option<Vec<String>>,
     pub required: bool,
+    pub global: bool,
 }
 
 impl Arg {
@@ -293,7 +342,9 @@ impl Arg {
             value_name: None,
             default_value: None,
             requires: None,
+            conflicts: None,
             required: false,
+            global: false,
         }
     }
 
@@ -311,11 +362,17 @@ impl Arg {
 
     /// Set the argument to require another argument to be present
     pub fn requires(mut self, requires: &str) -> Arg {
-        if self.requires.is_none() {
-            self.requires = Some(vec![requires.to_string()])
-        } else {
-            self.requires.as_mut().unwrap().push(requires.to_string());
-        }
+        self.requires
+            .get_or_insert_with(Vec::new)
+            .push(requires.to_string());
+        self
+    }
+
+    /// Set the argument to conflict with another argument
+    pub fn conflicts_with(mut self, conflicts: &str) -> Arg {
+        self.conflicts
+            .get_or_insert_with(Vec::new)
+            .push(conflicts.to_string());
         self
     }
 
@@ -352,7 +409,23 @@ impl Value {
 }
 
 fn cli_help(cli: &Cli, command_name: Option<&str>) {
-    if command_name.is_none() {
+    if let Some(command_name) = command_name {
+        let command_name: Vec<&str> = command_name.split(":").collect();
+
+        let command = cli
+            .commands
+            .iter()
+            .find(|command| command.name == command_name[0]);
+
+        if command.is_none() {
+            cli.help(None);
+            return;
+        }
+
+        let command = command.unwrap();
+
+        command_help(command, 0, true)
+    } else {
         println!("{} {}", cli.name, cli.version);
         println!("{}", cli.description);
         println!("Author: {}", cli.author);
@@ -364,28 +437,22 @@ fn cli_help(cli: &Cli, command_name: Option<&str>) {
         prints!("[style:bold]COMMANDS:");
 
         for command in &cli.commands {
-            command_help(command, 1);
+            command_help(command, 1, false);
         }
-    } else {
-        let command_name: Vec<&str> = command_name.unwrap().split(":").collect();
 
-        let command = cli
-            .commands
-            .iter()
-            .find(|command| command.name == command_name[0]);
+        if !cli.global_args.is_empty() {
+            prints!("[style:bold]GLOBAL OPTIONS:");
 
-        if command.is_none() {
-            cli.help(None);
-            return;
-        }
-
-        let command = command.unwrap();
+            for arg in &cli.global_args {
+                arg_help(arg, 0);
+            }
 
-        command_help(command, 0)
+            println!();
+        }
     }
 }
 
-fn command_help(command: &Command, indent: u8) {
+fn command_help(command: &Command, indent: u8, show_global: bool) {
     prints!(
         "[style:bold]{}{} - {}",
         "    ".repeat(indent as usize),
@@ -406,21 +473,24 @@ fn command_help(command: &Command, indent: u8) {
             option.description,
         )
     }
-    for arg in &command.args {
-        println!(
-            "    {}-{:<12} --{:<12} {:<14}{:<12}{}",
-            "    ".repeat((indent) as usize),
-            arg.short,
-            arg.long,
-            if arg.value_name.is_some() {
-                format!("<{}>", arg.value_name.as_ref().unwrap())
-            } else {
-                "".to_string()
-            },
-            if arg.required { " (required)" } else { "" },
-            arg.description,
-        );
+    for arg in command.args.iter().filter(|arg| show_global || !arg.global) {
+        arg_help(arg, indent);
     }
 
     println!();
 }
+
+fn arg_help(arg: &Arg, indent: u8) {
+    println!(
+        "    {}-{:<12} --{:<12} {:<14}{:<12}{}",
+        "    ".repeat((indent) as usize),
+        arg.short,
+        arg.long,
+        match &arg.value_name {
+            Some(value_name) => format!("<{}>", value_name),
+            None => "".to_string(),
+        },
+        if arg.required { " (required)" } else { "" },
+        arg.description,
+    );
+}
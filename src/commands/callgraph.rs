@@ -0,0 +1,168 @@
+use crate::calculator::callgraph::{CallGraph, ProgramCalls};
+use crate::calculator::lint::program_calls;
+use crate::calculator::program::{detect_file_type, Program};
+use crate::calculator::{DisplayMode, EncodeMode};
+use crate::commands::output::{print_json, OutputFormat};
+use crate::commands::walk::{collect_program_files, FileFilter};
+use crate::prints;
+use serde::Serialize;
+use std::path::Path;
+
+pub struct CallGraphOptions {
+    pub encode_mode: String,
+    pub recursive: bool,
+    pub dot: bool,
+}
+
+#[derive(Serialize)]
+struct LoadError {
+    file: String,
+    error: String,
+}
+
+#[derive(Serialize)]
+struct CallGraphReport {
+    #[serde(flatten)]
+    graph: CallGraph,
+    errors: Vec<LoadError>,
+}
+
+pub fn callgraph_command(
+    input_path_string: String,
+    options: CallGraphOptions,
+    format: OutputFormat,
+) {
+    let input_path = Path::new(&input_path_string);
+
+    if !input_path.is_dir() {
+        prints!(
+            "[color:bright-red]Error:[color:reset] The input path must be a directory of programs"
+        );
+        std::process::exit(1);
+    }
+
+    let encode_mode = match EncodeMode::from_string(&options.encode_mode) {
+        Ok(encode_mode) => encode_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let filter = FileFilter {
+        recursive: options.recursive,
+        include: Vec::new(),
+        exclude: Vec::new(),
+    };
+
+    let files = match collect_program_files(input_path, &filter) {
+        Ok(files) => files,
+        Err(err) => err.print().exit(),
+    };
+
+    let mut programs = Vec::new();
+    let mut errors = Vec::new();
+
+    for path in files {
+        let calls = detect_file_type(&path)
+            .and_then(|file_type| {
+                Program::load_from_file(
+                    path.to_path_buf(),
+                    file_type,
+                    DisplayMode::Accessible,
+                    &encode_mode,
+                )
+            })
+            .and_then(|program| {
+                program_calls(&program).map(|calls| (program.metadata.name.to_string(), calls))
+            });
+
+        match calls {
+            Ok((name, calls)) => programs.push(ProgramCalls {
+                name,
+                files: vec![path.display().to_string()],
+                calls,
+            }),
+            Err(err) => errors.push(LoadError {
+                file: path.display().to_string(),
+                error: err.to_string(),
+            }),
+        }
+    }
+
+    let report = CallGraphReport {
+        graph: CallGraph::new(programs),
+        errors,
+    };
+
+    if options.dot {
+        print!("{}", report.graph.to_dot());
+    } else if format.is_json() {
+        print_json(&report);
+    } else {
+        print_report(&report);
+    }
+
+    if !report.errors.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+fn print_report(report: &CallGraphReport) {
+    let graph = &report.graph;
+
+    prints!("[color:bright-green]Programs");
+
+    for program in &graph.programs {
+        let calls = program
+            .calls
+            .iter()
+            .map(|call| {
+                if graph.missing.iter().any(|missing| missing.name == *call) {
+                    format!("[color:bright-red]{}[color:reset]", call)
+                } else if graph.in_cycle(&program.name, call) {
+                    format!("[color:bright-yellow]{}[color:reset]", call)
+                } else {
+                    call.to_string()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let calls = if calls.is_empty() {
+            "calls nothing".to_string()
+        } else {
+            format!("calls {}", calls.join(", "))
+        };
+
+        prints!(
+            "    [color:bright-cyan]{:<10}[color:reset] {}",
+            program.name,
+            calls
+        );
+    }
+
+    if !graph.missing.is_empty() {
+        prints!("\n[color:bright-red]Missing programs");
+
+        for missing in &graph.missing {
+            prints!(
+                "    [color:bright-cyan]{:<10}[color:reset] called by {}",
+                missing.name,
+                missing.called_by.join(", ")
+            );
+        }
+    }
+
+    if !graph.cycles.is_empty() {
+        prints!("\n[color:bright-yellow]Cycles");
+
+        for cycle in &graph.cycles {
+            prints!("    {}", cycle.join(", "));
+        }
+    }
+
+    for error in &report.errors {
+        prints!(
+            "\n[color:bright-red]Failed to load[color:reset] [color:bright-cyan]{}[color:reset]: {}",
+            error.file,
+            error.error
+        );
+    }
+}
@@ -1,20 +1,132 @@
+use crate::calculator::assembly::{parse_hex, Assembly};
 use crate::calculator::errors::TiToolsError;
-use crate::calculator::program::{get_file_type, Program, ProgramFileType};
+use crate::calculator::models::{Model, ModelDetails};
+use crate::calculator::program::{
+    clear_output_path, get_file_type, OverwritePolicy, Program, ProgramFileType,
+};
 use crate::calculator::{DisplayMode, EncodeMode};
+use crate::commands::details::ProgramDetails;
+use crate::commands::output::{hex_string, print_json, OutputFormat};
+use crate::commands::policy::WritePolicy;
+use crate::commands::pool::{default_jobs, run_parallel};
+use crate::commands::walk::{collect_program_files, FileFilter};
 use crate::prints;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs;
-use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ConversionStatus {
+    Converted,
+    Skipped,
+    Failed,
+}
+
+#[derive(Serialize)]
+struct ConversionReport {
+    input: String,
+    status: ConversionStatus,
+    outputs: Vec<String>,
+    skipped: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    program: Option<ProgramDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bytes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    translation: Option<String>,
+    warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    duration_ms: f64,
+}
+
+impl ConversionReport {
+    fn new(input_path: &Path) -> ConversionReport {
+        ConversionReport {
+            input: input_path.display().to_string(),
+            status: ConversionStatus::Converted,
+            outputs: Vec::new(),
+            skipped: Vec::new(),
+            program: None,
+            bytes: None,
+            translation: None,
+            warnings: Vec::new(),
+            error: None,
+            duration_ms: 0.0,
+        }
+    }
+
+    fn fail(&mut self, err: &TiToolsError) {
+        self.status = ConversionStatus::Failed;
+        self.error = Some(err.to_string());
+    }
+}
+
+#[derive(Serialize)]
+struct Collision {
+    output: String,
+    inputs: Vec<String>,
+}
+
+#[derive(Serialize, Default)]
+struct ConversionSummary {
+    converted: usize,
+    skipped: usize,
+    failed: usize,
+    duration_ms: f64,
+}
+
+impl ConversionSummary {
+    fn from_results(results: &[ConversionReport], duration_ms: f64) -> ConversionSummary {
+        let count = |status: fn(&ConversionStatus) -> bool| {
+            results
+                .iter()
+                .filter(|report| status(&report.status))
+                .count()
+        };
+
+        ConversionSummary {
+            converted: count(|status| matches!(status, ConversionStatus::Converted)),
+            skipped: count(|status| matches!(status, ConversionStatus::Skipped)),
+            failed: count(|status| matches!(status, ConversionStatus::Failed)),
+            duration_ms,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct MassConversionReport {
+    results: Vec<ConversionReport>,
+    collisions: Vec<Collision>,
+    summary: ConversionSummary,
+}
+
+/// A file queued for saving, the program is missing when it failed to load
+type Conversion = (Option<Program>, Vec<PathBuf>, ConversionReport);
+
+pub struct ConvertOptions {
+    pub output_path_string: Option<String>,
+    pub display_mode: String,
+    pub encode_mode: String,
+    pub content: bool,
+    pub preview: bool,
+    pub mass: bool,
+    pub to: Option<String>,
+    pub jobs: Option<String>,
+    pub filter: FileFilter,
+    pub model: String,
+}
 
 pub fn convert_command(
     input_path_string: String,
-    output_path_string: Option<String>,
-    display_mode_string: String,
-    encode_mode: String,
-    content: bool,
-    preview: bool,
-    mass: bool,
+    options: ConvertOptions,
+    policy: &WritePolicy,
+    format: OutputFormat,
 ) {
+    let mass = options.mass;
     let input_path = Path::new(&input_path_string);
 
     // Validating input path
@@ -36,363 +148,579 @@ pub fn convert_command(
     }
 
     // Validating output
-    if output_path_string.is_none() && !preview && !content {
+    if options.output_path_string.is_none() && !options.preview && !options.content {
         prints!("[color:bright-yellow]Warning:[color:reset] An output path or preview option is required");
         std::process::exit(1);
     }
 
-    // Normal conversion
-    if !mass {
-        let program_file_type = match get_file_type(input_path) {
-            Ok(program_file_type) => program_file_type,
-            Err(err) => err.print().exit(),
-        };
-
-        // Validating args
-        if program_file_type.is_8xp() && DisplayMode::from_string(&display_mode_string).is_err() {
+    // Validating args
+    let display_mode = match DisplayMode::from_string(&options.display_mode) {
+        Ok(display_mode) => display_mode,
+        Err(_) => {
             prints!("[color:bright-red]Error:[color:reset] Display mode is required for converting from 8xp files but could not be determined");
             std::process::exit(1);
         }
+    };
 
-        if program_file_type.is_txt() && EncodeMode::from_string(&encode_mode).is_err() {
-            prints!("[color:bright-red]Error:[color:reset] Encode mode is required for converting from 8xp files but could not be determined");
+    let encode_mode = match EncodeMode::from_string(&options.encode_mode) {
+        Ok(encode_mode) => encode_mode,
+        Err(_) => {
+            prints!("[color:bright-red]Error:[color:reset] Encode mode is required for converting from txt files but could not be determined");
             std::process::exit(1);
         }
+    };
+
+    let jobs = match &options.jobs {
+        Some(jobs) => match jobs.parse::<usize>() {
+            Ok(jobs) if jobs > 0 => jobs,
+            _ => {
+                prints!("[color:bright-red]Error:[color:reset] The number of jobs must be a whole number greater than 0");
+                std::process::exit(1);
+            }
+        },
+        None => default_jobs(),
+    };
 
-        let display_mode = match DisplayMode::from_string(&display_mode_string) {
-            Ok(display_mode) => display_mode,
-            Err(err) => err.print().exit(),
-        };
+    let target_file_type = match &options.to {
+        Some(to) => match ProgramFileType::from_string(to) {
+            Ok(target_file_type) => Some(target_file_type),
+            Err(_) => {
+                prints!("[color:bright-red]Error:[color:reset] The target format must be txt, json, 8xp, 83p or 82p");
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
 
-        let encode_mode = match EncodeMode::from_string(&encode_mode) {
-            Ok(encode_mode) => encode_mode,
-            Err(err) => err.print().exit(),
-        };
+    // Normal conversion
+    if !mass {
+        let mut report = ConversionReport::new(input_path);
+
+        let (program_file_type, program) =
+            match load_input(input_path, &display_mode, &encode_mode, &options.model) {
+                Ok(loaded) => loaded,
+                Err(err) => exit_with_error(report, err, format),
+            };
 
-        let program = convert_program(
+        show_program(
+            &program,
             &program_file_type,
-            input_path,
-            display_mode,
-            encode_mode,
-            content,
-            preview,
+            options.content,
+            options.preview,
+            &mut report,
+            format,
         );
 
-        if output_path_string.is_some() {
-            match program.save_to(&Path::new(&output_path_string.as_ref().unwrap()).to_path_buf()) {
-                Ok(_) => {
-                    if display_mode_string == DisplayMode::Pretty.to_string() {
-                        prints!(
-                            "[color:bright-yellow]Warning:[color:reset] Pretty tokens can't be accurately encoded"
-                        );
-                    }
-
-                    prints!(
-                        "[color:bright-green]Successfully saved[color:reset] [color:bright-cyan]{}[color:reset] to [color:bright-cyan]{}",
-                        program.metadata.name,
-                        output_path_string.as_ref().unwrap()
-                    );
-                }
-                Err(err) => err.print().exit(),
+        if let Some(output_path_string) = &options.output_path_string {
+            let output_path = Path::new(output_path_string).to_path_buf();
+
+            match save_program(
+                &program,
+                &output_path,
+                &display_mode,
+                policy,
+                &mut report,
+                format,
+            ) {
+                Ok(_) => {}
+                Err(err) => exit_with_error(report, err, format),
             }
         }
 
+        if format.is_json() {
+            print_json(&report);
+        }
+
         return;
     }
 
     // Mass conversion
-    if output_path_string.is_some() && !Path::new(&output_path_string.as_ref().unwrap()).exists() {
-        println!("The output directory does not exist. Would you like to create one? [y/N]");
-        let mut input = String::new();
-        print!("> ");
-        input.clear();
-        std::io::stdout().flush().unwrap();
-        std::io::stdin().read_line(&mut input).unwrap();
-        let input = input.trim();
-        if input != "y" && input != "Y" {
-            prints!("[color:bright-red]Error:[color:reset] An output directory must be created for files to be saved");
-            std::process::exit(1)
+    let started = Instant::now();
+
+    let files = match collect_program_files(input_path, &options.filter) {
+        Ok(files) => files,
+        Err(err) => err.print().exit(),
+    };
+
+    let mut mass_report = MassConversionReport {
+        results: Vec::new(),
+        collisions: Vec::new(),
+        summary: ConversionSummary::default(),
+    };
+
+    let loaded = run_parallel(files, jobs, |path| {
+        let started = Instant::now();
+        let mut report = ConversionReport::new(&path);
+
+        let loaded = get_file_type(&path).and_then(|file_type| {
+            Program::load_from_file(
+                path.to_path_buf(),
+                file_type.clone(),
+                display_mode.clone(),
+                &encode_mode,
+            )
+            .map(|program| (file_type, program))
+        });
+
+        report.duration_ms += elapsed_ms(started);
+
+        (path, loaded, report)
+    });
+
+    let mut conversions: Vec<Conversion> = Vec::new();
+
+    // Previews are printed in order, only the loading and saving happen in parallel
+    for (path, loaded, mut report) in loaded {
+        let (file_type, program) = match loaded {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                report_failure(&mut report, err, format);
+                conversions.push((None, Vec::new(), report));
+                continue;
+            }
+        };
+
+        show_program(
+            &program,
+            &file_type,
+            options.content,
+            options.preview,
+            &mut report,
+            format,
+        );
+
+        let output_paths = match &options.output_path_string {
+            Some(output_path_string) => plan_output_paths(
+                &program,
+                &file_type,
+                target_file_type.as_ref(),
+                &path,
+                input_path,
+                output_path_string,
+            ),
+            None => Vec::new(),
+        };
+
+        conversions.push((Some(program), output_paths, report));
+    }
+
+    mass_report.collisions = find_collisions(&conversions);
+
+    if !mass_report.collisions.is_empty() {
+        if format.is_json() {
+            print_json(&mass_report);
+        } else {
+            for collision in &mass_report.collisions {
+                prints!(
+                    "[color:bright-red]Error:[color:reset] [color:bright-cyan]{}[color:reset] would be written by more than one file:",
+                    collision.output
+                );
+                for input in &collision.inputs {
+                    println!("    {}", input);
+                }
+            }
         }
 
-        println!("Creating output directory");
-        fs::create_dir(Path::new(&output_path_string.as_ref().unwrap()))
-            .expect("Failed to create directory")
+        std::process::exit(1);
     }
 
-    for entry in fs::read_dir(input_path).expect("Failed to read directory") {
-        let entry = match entry {
-            Ok(entry) => entry,
-            Err(_) => {
-                prints!("[color:bright-red]Error:[color:reset] Failed to read an entry in the input directory.");
-                println!("Skipping entry...");
-                continue;
+    if let Some(output_path_string) = &options.output_path_string {
+        let output_path = Path::new(output_path_string);
+
+        if !output_path.exists() {
+            if !policy.confirm("The output directory does not exist. Would you like to create one?")
+            {
+                prints!("[color:bright-red]Error:[color:reset] An output directory must be created for files to be saved");
+                std::process::exit(1)
             }
-        };
 
-        let path = entry.path();
-        let file_type = match get_file_type(&path) {
-            Ok(file_type) => file_type,
-            Err(_) => continue,
+            if !format.is_json() {
+                println!("Creating output directory");
+            }
+
+            fs::create_dir_all(output_path).expect("Failed to create directory")
+        }
+    }
+
+    // Overwrite prompts read from stdin, so they are asked one at a time
+    let save_jobs = match policy.overwrite {
+        OverwritePolicy::Prompt => 1,
+        _ => jobs,
+    };
+
+    mass_report.results = run_parallel(conversions, save_jobs, |conversion| {
+        let (program, output_paths, mut report) = conversion;
+
+        let program = match program {
+            Some(program) => program,
+            None => return report,
         };
 
-        // Validating args
-        if file_type.is_8xp() && DisplayMode::from_string(&display_mode_string).is_err() {
-            prints!("[color:bright-red]Error:[color:reset] Display mode is required for converting from 8xp files but could not be determined");
-            std::process::exit(1);
+        let started = Instant::now();
+
+        for output_path in output_paths {
+            let result = create_parent_directory(&output_path).and_then(|_| {
+                save_program(
+                    &program,
+                    &output_path,
+                    &display_mode,
+                    policy,
+                    &mut report,
+                    format,
+                )
+            });
+
+            if let Err(err) = result {
+                report_failure(&mut report, err, format);
+                break;
+            }
         }
 
-        if file_type.is_txt() && EncodeMode::from_string(&encode_mode).is_err() {
-            prints!("[color:bright-red]Error:[color:reset] Encode mode is required for converting from 8xp files but could not be determined");
-            std::process::exit(1);
+        report.duration_ms += elapsed_ms(started);
+
+        report
+    });
+
+    mass_report.summary =
+        ConversionSummary::from_results(&mass_report.results, elapsed_ms(started));
+
+    if format.is_json() {
+        print_json(&mass_report);
+    } else {
+        print_summary(&mass_report);
+    }
+
+    if mass_report.summary.failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn print_summary(mass_report: &MassConversionReport) {
+    let summary = &mass_report.summary;
+
+    prints!(
+        "\n[color:bright-green]Converted:[color:reset] {}, [color:bright-yellow]Skipped:[color:reset] {}, [color:bright-red]Failed:[color:reset] {} in {:.2}ms",
+        summary.converted,
+        summary.skipped,
+        summary.failed,
+        summary.duration_ms
+    );
+
+    for report in &mass_report.results {
+        if let Some(error) = &report.error {
+            prints!(
+                "    [color:bright-cyan]{}[color:reset] ({:.2}ms): {}",
+                report.input,
+                report.duration_ms,
+                error
+            );
         }
+    }
+}
 
-        let display_mode = match DisplayMode::from_string(&display_mode_string) {
-            Ok(display_mode) => display_mode,
-            Err(err) => err.print().exit(),
-        };
+fn elapsed_ms(started: Instant) -> f64 {
+    started.elapsed().as_secs_f64() * 1000.0
+}
 
-        let encode_mode = match EncodeMode::from_string(&encode_mode) {
-            Ok(encode_mode) => encode_mode,
-            Err(err) => err.print().exit(),
-        };
+/// Mirrors the location of the input file inside the output directory, named after the program
+fn plan_output_paths(
+    program: &Program,
+    file_type: &ProgramFileType,
+    target_file_type: Option<&ProgramFileType>,
+    input_file: &Path,
+    input_root: &Path,
+    output_path_string: &str,
+) -> Vec<PathBuf> {
+    let extensions = match (target_file_type, file_type) {
+        (Some(target_file_type), _) => vec![target_file_type.to_string()],
+        (None, ProgramFileType::XP | ProgramFileType::XPThree | ProgramFileType::XPTwo) => {
+            vec!["txt".to_string()]
+        }
+        (None, ProgramFileType::TXT) => vec!["8xp".to_string()],
+        (None, ProgramFileType::JSON) => vec!["txt".to_string(), "8xp".to_string()],
+    };
+
+    let relative_directory = input_file
+        .parent()
+        .and_then(|parent| parent.strip_prefix(input_root).ok())
+        .unwrap_or(Path::new(""));
+
+    extensions
+        .iter()
+        .map(|extension| {
+            Path::new(output_path_string)
+                .join(relative_directory)
+                .join(&program.metadata.name)
+                .with_extension(extension)
+        })
+        .collect()
+}
 
-        let program = convert_program(
-            &file_type,
-            &path,
-            display_mode,
-            encode_mode,
-            content,
-            preview,
-        );
+/// Output paths are compared case insensitively since program names are, and so are some file systems
+fn find_collisions(conversions: &[Conversion]) -> Vec<Collision> {
+    let mut outputs: BTreeMap<String, (String, Vec<String>)> = BTreeMap::new();
+
+    for (_, output_paths, report) in conversions {
+        for output_path in output_paths {
+            let output = output_path.display().to_string();
+            outputs
+                .entry(output.to_lowercase())
+                .or_insert_with(|| (output, Vec::new()))
+                .1
+                .push(report.input.to_string());
+        }
+    }
 
-        if output_path_string.is_some() {
-            let name = program.metadata.name.to_string();
-
-            match file_type {
-                ProgramFileType::XP
-                | ProgramFileType::XPThree
-                | ProgramFileType::XPTwo
-                | ProgramFileType::TXT => {
-                    let output_path = if file_type.is_8xp() {
-                        Path::new(&output_path_string.as_ref().unwrap())
-                            .join(&name)
-                            .with_extension("txt")
-                    } else {
-                        Path::new(&output_path_string.as_ref().unwrap())
-                            .join(&name)
-                            .with_extension("8xp")
-                    };
-
-                    match program.save_to(&output_path) {
-                        Ok(_) => {
-                            if display_mode_string == DisplayMode::Pretty.to_string() {
-                                prints!(
-                                "[color:bright-yellow]Warning:[color:reset] Pretty tokens can't be accurately encoded"
-                            );
-                            }
-
-                            prints!(
-                            "[color:bright-green]Successfully saved[color:reset] [color:bright-cyan]{}[color:reset] to [color:bright-cyan]{}/{}.{}",
-                            program.metadata.name,
-                            output_path_string.as_ref().unwrap(),
-                            program.metadata.name,
-                            output_path.extension().unwrap().to_str().unwrap()
-                        );
-                        }
-                        Err(err) => {
-                            println!("Failed to save file:");
-                            err.print();
-
-                            println!("Would you like to skip this file and continue? [y/N]");
-                            let mut input = String::new();
-                            print!("> ");
-                            input.clear();
-                            std::io::stdout().flush().unwrap();
-                            std::io::stdin().read_line(&mut input).unwrap();
-                            let input = input.trim();
-                            if input != "y" && input != "Y" {
-                                TiToolsError::Quit("User chose to quit".to_string())
-                                    .print()
-                                    .exit()
-                            }
-
-                            continue;
-                        }
-                    }
-                }
-                ProgramFileType::JSON => {
-                    let txt_output_path = Path::new(&output_path_string.as_ref().unwrap())
-                        .join(&name)
-                        .with_extension("txt");
-
-                    let xp_output_path = Path::new(&output_path_string.as_ref().unwrap())
-                        .join(&name)
-                        .with_extension("8xp");
-
-                    match program.save_to(&txt_output_path) {
-                        Ok(_) => {
-                            if display_mode_string == DisplayMode::Pretty.to_string() {
-                                prints!(
-                                    "[color:bright-yellow]Warning:[color:reset] Pretty tokens can't be accurately encoded"
-                                );
-                            }
-
-                            prints!(
-                                "[color:bright-green]Successfully saved[color:reset] [color:bright-cyan]{}[color:reset] to [color:bright-cyan]{}.txt",
-                                program.metadata.name,
-                                output_path_string.as_ref().unwrap()
-                            );
-                        }
-                        Err(err) => {
-                            println!("Failed to save file:");
-                            err.print();
-
-                            println!("Would you like to skip this file and continue? [y/N]");
-                            let mut input = String::new();
-                            print!("> ");
-                            input.clear();
-                            std::io::stdout().flush().unwrap();
-                            std::io::stdin().read_line(&mut input).unwrap();
-                            let input = input.trim();
-                            if input != "y" && input != "Y" {
-                                TiToolsError::Quit("User chose to quit".to_string())
-                                    .print()
-                                    .exit()
-                            }
-
-                            continue;
-                        }
-                    }
+    outputs
+        .into_values()
+        .filter(|(_, inputs)| inputs.len() > 1)
+        .map(|(output, inputs)| Collision { output, inputs })
+        .collect()
+}
 
-                    match program.save_to(&xp_output_path) {
-                        Ok(_) => {
-                            if display_mode_string == DisplayMode::Pretty.to_string() {
-                                prints!(
-                                    "[color:bright-yellow]Warning:[color:reset] Pretty tokens can't be accurately encoded"
-                                );
-                            }
-
-                            prints!(
-                                "[color:bright-green]Successfully saved[color:reset] [color:bright-cyan]{}[color:reset] to [color:bright-cyan]{}.8xp",
-                                program.metadata.name,
-                                output_path_string.as_ref().unwrap()
-                            );
-                        }
-                        Err(err) => {
-                            println!("Failed to save file:");
-                            err.print();
-
-                            println!("Would you like to skip this file and continue? [y/N]");
-                            let mut input = String::new();
-                            print!("> ");
-                            input.clear();
-                            std::io::stdout().flush().unwrap();
-                            std::io::stdin().read_line(&mut input).unwrap();
-                            let input = input.trim();
-                            if input != "y" && input != "Y" {
-                                TiToolsError::Quit("User chose to quit".to_string())
-                                    .print()
-                                    .exit()
-                            }
-
-                            continue;
-                        }
-                    }
-                }
-            }
+fn create_parent_directory(path: &Path) -> Result<(), TiToolsError> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.exists() => {
+            fs::create_dir_all(parent).map_err(|err| {
+                TiToolsError::FailedToWriteFile(path.display().to_string(), err.to_string())
+            })
         }
+        _ => Ok(()),
     }
 }
 
-fn convert_program(
-    program_file_type: &ProgramFileType,
+/// Bin and hex files hold the machine code of an assembly program, built into a program for the model
+fn load_input(
     input_path: &Path,
-    display_mode: DisplayMode,
-    encode_mode: EncodeMode,
+    display_mode: &DisplayMode,
+    encode_mode: &EncodeMode,
+    model: &str,
+) -> Result<(ProgramFileType, Program), TiToolsError> {
+    let code = match input_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+    {
+        Some("bin") => {
+            fs::read(input_path).map_err(|err| TiToolsError::FailedToReadFile(err.to_string()))?
+        }
+        Some("hex") => fs::read_to_string(input_path)
+            .map_err(|err| TiToolsError::FailedToReadFile(err.to_string()))
+            .and_then(|text| parse_hex(&text))?,
+        _ => {
+            let program_file_type = get_file_type(input_path)?;
+            let program = Program::load_from_file(
+                input_path.to_path_buf(),
+                program_file_type.clone(),
+                display_mode.clone(),
+                encode_mode,
+            )?;
+
+            return Ok((program_file_type, program));
+        }
+    };
+
+    // unknown names are read as the latest model
+    let model = match Model::from_string(model) {
+        Model::Latest if model != "latest" => {
+            return Err(TiToolsError::Match(model.to_string(), "Model".to_string()))
+        }
+        model => model,
+    };
+
+    let name = input_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_uppercase())
+        .unwrap_or_default();
+
+    let assembly = Assembly::compiled(code, &model)?;
+    let program = Program::from_assembly(&name, ModelDetails::from_model(&model), &assembly)?;
+
+    Ok((ProgramFileType::XP, program))
+}
+
+/// Assembly programs are written to bin and hex files as their machine code alone
+fn write_output(
+    program: &Program,
+    output_path: &PathBuf,
+    overwrite: &OverwritePolicy,
+) -> Result<(), TiToolsError> {
+    let extension = output_path
+        .extension()
+        .and_then(|extension| extension.to_str());
+
+    if !matches!(extension, Some("bin" | "hex")) {
+        return program.save_to(output_path, overwrite);
+    }
+
+    let assembly = program
+        .assembly()
+        .ok_or_else(|| TiToolsError::NotAssembly(program.metadata.name.to_string()))?;
+
+    let content = match extension {
+        Some("bin") => assembly.code,
+        _ => assembly.to_hex().into_bytes(),
+    };
+
+    clear_output_path(output_path, overwrite)?;
+
+    fs::write(output_path, content).map_err(|err| {
+        TiToolsError::FailedToWriteFile(output_path.display().to_string(), err.to_string())
+    })
+}
+
+fn show_program(
+    program: &Program,
+    program_file_type: &ProgramFileType,
     content: bool,
     preview: bool,
-) -> Program {
+    report: &mut ConversionReport,
+    format: OutputFormat,
+) {
+    let mut bytes: Vec<u8> = Vec::new();
+    bytes.extend(&program.header.bytes);
+    bytes.extend(&program.metadata.bytes);
+    bytes.extend(&program.body.bytes);
+    bytes.extend(&program.checksum.bytes);
+
+    if format.is_json() {
+        report.program = Some(ProgramDetails::from_program(program));
+
+        if content || preview {
+            report.bytes = Some(hex_string(&bytes));
+            report.translation = Some(program.body.translation.to_string());
+        }
+
+        return;
+    }
+
     match program_file_type {
         ProgramFileType::XP | ProgramFileType::XPThree | ProgramFileType::XPTwo => {
-            match Program::load_from_8xp(input_path.to_path_buf(), display_mode) {
-                Ok(program) => {
-                    if content {
-                        let mut bytes: Vec<u8> = Vec::new();
-                        bytes.extend(&program.header.bytes);
-                        bytes.extend(&program.metadata.bytes);
-                        bytes.extend(&program.body.bytes);
-                        bytes.extend(&program.checksum.bytes);
-
-                        print_bytes(bytes);
-                        println!("\n");
-                    }
-
-                    if preview {
-                        println!("{}\n", program.to_string());
-                    }
+            if content {
+                print_bytes(bytes);
+                println!("\n");
+            }
 
-                    program
-                }
-                Err(err) => err.print().exit(),
+            if preview {
+                println!("{}\n", program);
             }
         }
         ProgramFileType::TXT => {
-            match Program::load_from_txt(input_path.to_path_buf(), &encode_mode) {
-                Ok(program) => {
-                    if content {
-                        println!("{}\n", program.to_string());
+            if content {
+                println!("{}\n", program);
+            }
+
+            if preview {
+                print_bytes(bytes);
+                println!("\n");
+            }
+        }
+        ProgramFileType::JSON => {
+            if content {
+                let json_program = match serde_json::to_string_pretty(&program) {
+                    Ok(json_program) => json_program,
+                    Err(err) => {
+                        prints!(
+                            "[color:red]Error:[color:reset] Failed to convert program to json: {}",
+                            err
+                        );
+                        std::process::exit(1);
                     }
+                };
 
-                    if preview {
-                        let mut bytes: Vec<u8> = Vec::new();
-                        bytes.extend(&program.header.bytes);
-                        bytes.extend(&program.metadata.bytes);
-                        bytes.extend(&program.body.bytes);
-                        bytes.extend(&program.checksum.bytes);
+                println!("{}\n", json_program);
+            }
 
-                        print_bytes(bytes);
-                        println!("\n");
-                    }
+            if preview {
+                print_bytes(bytes);
+                println!("\n");
 
-                    program
-                }
-                Err(err) => err.print().exit(),
+                println!("{}\n", program);
             }
         }
-        ProgramFileType::JSON => match Program::load_from_json(input_path.to_path_buf()) {
-            Ok(program) => {
-                if content {
-                    let json_program = match serde_json::to_string_pretty(&program) {
-                        Ok(json_program) => json_program,
-                        Err(err) => {
-                            prints!("[color:red]Error:[color:reset] Failed to convert program to json: {}", err);
-                            std::process::exit(1);
-                        }
-                    };
-
-                    println!("{}\n", json_program);
+    }
+}
+
+fn save_program(
+    program: &Program,
+    output_path: &PathBuf,
+    display_mode: &DisplayMode,
+    policy: &WritePolicy,
+    report: &mut ConversionReport,
+    format: OutputFormat,
+) -> Result<(), TiToolsError> {
+    match write_output(program, output_path, &policy.overwrite) {
+        Ok(_) => {
+            let pretty_warning = matches!(display_mode, DisplayMode::Pretty);
+
+            report.status = ConversionStatus::Converted;
+            report.outputs.push(output_path.display().to_string());
+
+            if format.is_json() {
+                if pretty_warning {
+                    report
+                        .warnings
+                        .push("Pretty tokens can't be accurately encoded".to_string());
                 }
 
-                if preview {
-                    let mut bytes: Vec<u8> = Vec::new();
-                    bytes.extend(&program.header.bytes);
-                    bytes.extend(&program.metadata.bytes);
-                    bytes.extend(&program.body.bytes);
-                    bytes.extend(&program.checksum.bytes);
+                return Ok(());
+            }
 
-                    print_bytes(bytes);
-                    println!("\n");
+            if pretty_warning {
+                prints!(
+                    "[color:bright-yellow]Warning:[color:reset] Pretty tokens can't be accurately encoded"
+                );
+            }
 
-                    println!("{}\n", program.to_string());
-                }
+            prints!(
+                "[color:bright-green]Successfully saved[color:reset] [color:bright-cyan]{}[color:reset] to [color:bright-cyan]{}",
+                program.metadata.name,
+                output_path.display()
+            );
 
-                program
+            Ok(())
+        }
+        Err(TiToolsError::FileAlreadyExists(file)) => {
+            if report.outputs.is_empty() {
+                report.status = ConversionStatus::Skipped;
             }
-            Err(err) => err.print().exit(),
-        },
+
+            if !format.is_json() {
+                prints!(
+                    "[color:bright-yellow]Skipped:[color:reset] [color:bright-cyan]{}[color:reset] already exists",
+                    file
+                );
+            }
+
+            report.skipped.push(file);
+
+            Ok(())
+        }
+        Err(err) => Err(err),
     }
 }
 
+/// Records a failed file, the rest of the mass conversion carries on
+fn report_failure(report: &mut ConversionReport, err: TiToolsError, format: OutputFormat) {
+    report.fail(&err);
+
+    if !format.is_json() {
+        prints!(
+            "[color:bright-red]Failed to convert[color:reset] [color:bright-cyan]{}[color:reset]: {}",
+            report.input,
+            err
+        );
+    }
+}
+
+fn exit_with_error(mut report: ConversionReport, err: TiToolsError, format: OutputFormat) -> ! {
+    if format.is_json() {
+        report.fail(&err);
+        print_json(&report);
+    }
+
+    err.print().exit()
+}
+
 fn print_bytes(bytes: Vec<u8>) {
     let mut i = 0;
     for byte in bytes {
@@ -1,8 +1,68 @@
-use crate::calculator::program::Program;
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::program::{clear_output_path, Program};
+use crate::calculator::shell::ShellKind;
 use crate::calculator::DisplayMode;
+use crate::commands::output::{print_json, OutputFormat};
+use crate::commands::policy::WritePolicy;
+use serde::Serialize;
 use std::path::Path;
 
-pub fn details_command(input_path_string: String) {
+#[derive(Serialize)]
+pub struct ProgramDetails {
+    pub name: String,
+    pub comment: String,
+    pub total_size: usize,
+    pub body_size: usize,
+    pub model: String,
+    pub language: String,
+    pub destination: String,
+    pub file_type: String,
+    pub checksum: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shell: Option<ShellDetails>,
+}
+
+#[derive(Serialize)]
+pub struct ShellDetails {
+    pub kind: ShellKind,
+    pub description: Option<String>,
+    /// Rows of `#` and `.`, or of two hex digits per pixel for color icons
+    pub icon: Option<Vec<String>>,
+}
+
+impl ProgramDetails {
+    pub fn from_program(program: &Program) -> ProgramDetails {
+        ProgramDetails {
+            name: program.metadata.name.to_string(),
+            comment: program.header.comment.to_string(),
+            // 2 for checksum
+            total_size: program.header.bytes.len()
+                + program.metadata.bytes.len()
+                + program.body.bytes.len()
+                + 2,
+            body_size: program.body.bytes.len(),
+            model: program.model.model.to_string(),
+            language: program.model.language.to_string(),
+            destination: program.metadata.destination.to_string(),
+            file_type: program.metadata.file_type.to_string(),
+            checksum: program.checksum.value,
+            shell: program.shell_header().map(|header| ShellDetails {
+                kind: header.kind,
+                description: header.description,
+                icon: header
+                    .icon
+                    .map(|icon| icon.to_ascii().lines().map(str::to_string).collect()),
+            }),
+        }
+    }
+}
+
+pub fn details_command(
+    input_path_string: String,
+    icon_png: Option<String>,
+    policy: &WritePolicy,
+    format: OutputFormat,
+) {
     let input_path = Path::new(&input_path_string);
 
     let program = Program::load_from_8xp(input_path.to_path_buf(), DisplayMode::Accessible);
@@ -12,16 +72,67 @@ pub fn details_command(input_path_string: String) {
         Err(err) => err.print().exit(),
     };
 
-    println!("{}", program.metadata.name);
-    println!("{}", program.header.comment);
-    println!(
-        "Total Size: {} bytes",
-        program.header.bytes.len() + program.metadata.bytes.len() + program.body.bytes.len() + 2
-    ); // 2 for checksum
-    println!("Body Size: {} bytes", program.body.bytes.len());
+    if let Some(icon_png) = icon_png {
+        write_icon_png(&program, icon_png, policy);
+    }
+
+    let details = ProgramDetails::from_program(&program);
+
+    if format.is_json() {
+        print_json(&details);
+        return;
+    }
+
+    println!("{}", details.name);
+    println!("{}", details.comment);
+    println!("Total Size: {} bytes", details.total_size);
+    println!("Body Size: {} bytes", details.body_size);
     println!("----- Status -----");
-    println!("model: {}", program.model.model.to_string());
-    println!("language: {}", program.model.language);
-    println!("Destination: {}", program.metadata.destination.to_string());
-    println!("File Type: {}", program.metadata.file_type.to_string())
+    println!("model: {}", details.model);
+    println!("language: {}", details.language);
+    println!("Destination: {}", details.destination);
+    println!("File Type: {}", details.file_type);
+
+    if let Some(shell) = details.shell {
+        println!("----- Shell -----");
+        println!("Shell: {}", shell.kind);
+
+        if let Some(description) = shell.description {
+            println!("Description: {}", description);
+        }
+
+        if let Some(icon) = shell.icon {
+            println!("Icon:");
+            println!("{}", icon.join("\n"));
+        }
+    }
+}
+
+fn write_icon_png(program: &Program, path: String, policy: &WritePolicy) {
+    let icon = match program.shell_header() {
+        Some(header) => match header.icon {
+            Some(icon) => icon,
+            None => TiToolsError::NoShellIcon(header.kind.to_string())
+                .print()
+                .exit(),
+        },
+        None => TiToolsError::NoShellHeader(program.metadata.name.to_string())
+            .print()
+            .exit(),
+    };
+
+    let png = match icon.to_png() {
+        Ok(png) => png,
+        Err(err) => err.print().exit(),
+    };
+
+    if let Err(err) = clear_output_path(Path::new(&path), &policy.overwrite) {
+        err.print().exit();
+    }
+
+    if let Err(err) = std::fs::write(&path, png) {
+        TiToolsError::FailedToWriteFile(path, err.to_string())
+            .print()
+            .exit();
+    }
 }
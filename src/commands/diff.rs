@@ -0,0 +1,193 @@
+use crate::calculator::diff::{diff_programs, ChangeKind, LineChange, MetadataChange, ProgramDiff};
+use crate::calculator::program::{
+    detect_file_type, get_file_type, Program, ProgramFileType, TXT_HEADER_LINES,
+};
+use crate::calculator::{DisplayMode, EncodeMode};
+use crate::commands::output::{print_json, OutputFormat};
+use crate::prints;
+use serde::Serialize;
+use std::path::Path;
+
+pub struct DiffOptions {
+    pub display_mode: String,
+    pub encode_mode: String,
+    pub context: String,
+}
+
+#[derive(Serialize)]
+struct DiffReport<'a> {
+    old: String,
+    new: String,
+    metadata: &'a [MetadataChange],
+    lines: Vec<&'a LineChange>,
+}
+
+pub fn diff_command(
+    old_path_string: String,
+    new_path_string: String,
+    options: DiffOptions,
+    format: OutputFormat,
+) {
+    let display_mode = match DisplayMode::from_string(&options.display_mode) {
+        Ok(display_mode) => display_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let encode_mode = match EncodeMode::from_string(&options.encode_mode) {
+        Ok(encode_mode) => encode_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let context = match options.context.parse::<usize>() {
+        Ok(context) => context,
+        Err(_) => {
+            prints!("[color:bright-red]Error:[color:reset] Context must be a number of lines");
+            std::process::exit(1);
+        }
+    };
+
+    let load = |path_string: &str| {
+        let path = Path::new(path_string);
+
+        match detect_file_type(path).and_then(|file_type| {
+            Program::load_from_file(
+                path.to_path_buf(),
+                file_type,
+                display_mode.clone(),
+                &encode_mode,
+            )
+        }) {
+            Ok(program) => program,
+            Err(err) => err.print().exit(),
+        }
+    };
+
+    let old = load(&old_path_string);
+    let new = load(&new_path_string);
+
+    let mut diff = match diff_programs(&old, &new, &display_mode) {
+        Ok(diff) => diff,
+        Err(err) => err.print().exit(),
+    };
+
+    // Lines in txt files are counted from the top of the file
+    let offset = |path_string: &str| match get_file_type(Path::new(path_string)) {
+        Ok(ProgramFileType::TXT) => TXT_HEADER_LINES,
+        _ => 0,
+    };
+    diff.shift_lines(offset(&old_path_string), offset(&new_path_string));
+
+    if format.is_json() {
+        print_json(&DiffReport {
+            old: old_path_string,
+            new: new_path_string,
+            metadata: &diff.metadata,
+            lines: diff
+                .lines
+                .iter()
+                .filter(|line| line.kind != ChangeKind::Equal)
+                .collect(),
+        });
+    } else {
+        print_diff(&old_path_string, &new_path_string, &diff, context);
+    }
+
+    // Like diff, exit with 1 when the programs are different
+    if !diff.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+fn print_diff(old_path: &str, new_path: &str, diff: &ProgramDiff, context: usize) {
+    if diff.is_empty() {
+        prints!("[color:bright-green]The programs are the same");
+        return;
+    }
+
+    prints!("[color:bright-red]--- {}", old_path);
+    prints!("[color:bright-green]+++ {}", new_path);
+
+    for change in &diff.metadata {
+        prints!(
+            "[color:bright-cyan]{}:[color:reset] [color:bright-red]{}[color:reset] -> [color:bright-green]{}",
+            change.field,
+            change.old,
+            change.new
+        );
+    }
+
+    for hunk in hunks(&diff.lines, context) {
+        let lines = &diff.lines[hunk.0..hunk.1];
+        let old_lines: Vec<usize> = lines.iter().filter_map(|line| line.old_line).collect();
+        let new_lines: Vec<usize> = lines.iter().filter_map(|line| line.new_line).collect();
+
+        prints!(
+            "[color:bright-cyan]@@ -{},{} +{},{} @@",
+            old_lines.first().unwrap_or(&0),
+            old_lines.len(),
+            new_lines.first().unwrap_or(&0),
+            new_lines.len()
+        );
+
+        for line in lines {
+            print_line(line);
+        }
+    }
+}
+
+fn print_line(line: &LineChange) {
+    let old = line.old.as_deref().unwrap_or_default();
+    let new = line.new.as_deref().unwrap_or_default();
+
+    match line.kind {
+        ChangeKind::Equal => prints!(" {}", new),
+        ChangeKind::Removed => prints!("[color:bright-red]-{}", old),
+        ChangeKind::Added => prints!("[color:bright-green]+{}", new),
+        ChangeKind::Changed => {
+            // The tokens that changed are inverted inside the line
+            let highlight = |shown: ChangeKind, color: &str| {
+                line.tokens
+                    .iter()
+                    .filter(|token| token.kind == ChangeKind::Equal || token.kind == shown)
+                    .map(|token| {
+                        if token.kind == shown {
+                            format!("[style:invert]{}[style:reset][color:{}]", token.text, color)
+                        } else {
+                            token.text.to_string()
+                        }
+                    })
+                    .collect::<String>()
+            };
+
+            prints!(
+                "[color:bright-red]-{}",
+                highlight(ChangeKind::Removed, "bright-red")
+            );
+            prints!(
+                "[color:bright-green]+{}",
+                highlight(ChangeKind::Added, "bright-green")
+            );
+        }
+    }
+}
+
+/// Ranges of lines with changes, each with up to `context` unchanged lines around it
+fn hunks(lines: &[LineChange], context: usize) -> Vec<(usize, usize)> {
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        if line.kind == ChangeKind::Equal {
+            continue;
+        }
+
+        let start = index.saturating_sub(context);
+        let end = (index + context + 1).min(lines.len());
+
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    hunks
+}
@@ -0,0 +1,154 @@
+use crate::calculator::assembly::{Assembly, AssemblyTarget};
+use crate::calculator::disasm::{disassemble, Cpu, Instruction};
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::models::Model;
+use crate::calculator::program::{detect_file_type, Program};
+use crate::calculator::{DisplayMode, EncodeMode};
+use crate::commands::output::{hex_string, print_json, OutputFormat};
+use crate::prints;
+use serde::Serialize;
+use std::path::Path;
+
+pub struct DisasmOptions {
+    pub cpu: Option<String>,
+    pub origin: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Disassembly {
+    name: String,
+    cpu: Cpu,
+    origin: String,
+    instructions: Vec<InstructionListing>,
+}
+
+#[derive(Serialize)]
+struct InstructionListing {
+    address: String,
+    bytes: String,
+    text: String,
+}
+
+pub fn disasm_command(input_path_string: String, options: DisasmOptions, format: OutputFormat) {
+    let input_path = Path::new(&input_path_string);
+
+    let program = match detect_file_type(input_path).and_then(|file_type| {
+        Program::load_from_file(
+            input_path.to_path_buf(),
+            file_type,
+            DisplayMode::Accessible,
+            &EncodeMode::Smart,
+        )
+    }) {
+        Ok(program) => program,
+        Err(err) => err.print().exit(),
+    };
+
+    let assembly = match program.assembly() {
+        Some(assembly) => assembly,
+        None => TiToolsError::NotAssembly(program.metadata.name.to_string())
+            .print()
+            .exit(),
+    };
+
+    let target = match options.cpu.as_deref() {
+        Some(cpu) => Cpu::from_string(cpu).map(|cpu| {
+            // code for another cpu than the model's runs from where that cpu's models load it
+            match program_target(&program.model.model, &assembly) {
+                Ok(target) if Cpu::for_target(target) == cpu => target,
+                _ if cpu == Cpu::EZ80 => AssemblyTarget::EZ80,
+                _ => AssemblyTarget::Z80,
+            }
+        }),
+        None => program_target(&program.model.model, &assembly),
+    };
+
+    let target = match target {
+        Ok(target) => target,
+        Err(err) => err.print().exit(),
+    };
+    let cpu = Cpu::for_target(target);
+
+    // $9D95, 0x9D95 and 9D95 are the same address
+    let origin = match options.origin.as_deref() {
+        Some(origin) => {
+            let digits = origin.trim_start_matches('$').trim_start_matches("0x");
+
+            match u32::from_str_radix(digits, 16) {
+                Ok(address) if address <= cpu.address_mask() => address,
+                Ok(_) => {
+                    prints!(
+                        "[color:bright-red]Error:[color:reset] [color:bright-cyan]{}[color:reset] is past the highest {} address, {:X}",
+                        origin,
+                        cpu,
+                        cpu.address_mask()
+                    );
+                    std::process::exit(1);
+                }
+                Err(_) => {
+                    prints!(
+                        "[color:bright-red]Error:[color:reset] [color:bright-cyan]{}[color:reset] is not a hex address",
+                        origin
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => target.user_mem(),
+    };
+
+    let instructions = match disassemble(&assembly.code, cpu, origin) {
+        Ok(instructions) => instructions,
+        Err(err) => err.print().exit(),
+    };
+
+    let width = match cpu {
+        Cpu::Z80 => 4,
+        Cpu::EZ80 => 6,
+    };
+
+    if format.is_json() {
+        print_json(&Disassembly {
+            name: program.metadata.name.to_string(),
+            cpu,
+            origin: format!("{:0width$X}", origin, width = width),
+            instructions: instructions
+                .iter()
+                .map(|instruction| InstructionListing {
+                    address: format!("{:0width$X}", instruction.address, width = width),
+                    bytes: hex_string(&instruction.bytes),
+                    text: instruction.text.to_string(),
+                })
+                .collect(),
+        });
+    } else {
+        print_instructions(&instructions, width);
+    }
+}
+
+/// The model in the file picks the target, except for the CE headers which only the eZ80 runs
+fn program_target(model: &Model, assembly: &Assembly) -> Result<AssemblyTarget, TiToolsError> {
+    match (model, assembly.target) {
+        (Model::Latest, target) | (_, target @ AssemblyTarget::EZ80) => Ok(target),
+        (model, _) => AssemblyTarget::for_model(model),
+    }
+}
+
+fn print_instructions(instructions: &[Instruction], width: usize) {
+    for instruction in instructions {
+        let bytes = instruction
+            .bytes
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        prints!(
+            "[color:bright-black]{:0width$X}[color:reset]  {:<15} {}",
+            instruction.address,
+            bytes,
+            instruction.text,
+            width = width
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use crate::calculator::docs::{token_reference_html, token_reference_markdown};
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::program::clear_output_path;
+use crate::calculator::tokens::{load_token_definitions, load_token_table};
+use crate::commands::policy::WritePolicy;
+use crate::prints;
+use std::fs;
+use std::path::Path;
+
+pub struct DocsOptions {
+    pub output_path_string: Option<String>,
+    pub to: Option<String>,
+    pub check: bool,
+}
+
+pub fn docs_command(options: DocsOptions, policy: &WritePolicy) {
+    // the extension of the output picks the format when it is not given
+    let to = match (&options.to, &options.output_path_string) {
+        (Some(to), _) => to.as_str(),
+        (None, Some(output)) if output.ends_with(".html") || output.ends_with(".htm") => "html",
+        _ => "markdown",
+    };
+
+    let table = match load_token_table() {
+        Ok(table) => table,
+        Err(err) => err.print().exit(),
+    };
+
+    let definitions = match load_token_definitions() {
+        Ok(definitions) => definitions,
+        Err(err) => err.print().exit(),
+    };
+
+    let reference = match to {
+        "markdown" | "md" => token_reference_markdown(&table, &definitions),
+        "html" => token_reference_html(&table, &definitions),
+        _ => {
+            prints!(
+                "[color:bright-red]Error:[color:reset] [color:bright-cyan]{}[color:reset] is not a docs format, use markdown or html",
+                to
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let output_path_string = match options.output_path_string {
+        Some(output_path_string) => output_path_string,
+        None if options.check => {
+            prints!("[color:bright-red]Error:[color:reset] --check needs the --output to compare against");
+            std::process::exit(1);
+        }
+        None => {
+            print!("{}", reference);
+            return;
+        }
+    };
+
+    if options.check {
+        let current = fs::read_to_string(&output_path_string).unwrap_or_default();
+
+        if current == reference {
+            prints!(
+                "[color:bright-green]Up to date:[color:reset] {}",
+                output_path_string
+            );
+            return;
+        }
+
+        prints!(
+            "[color:bright-red]Out of date:[color:reset] {} differs from the token data, run ti-tools docs -o {} to update it",
+            output_path_string,
+            output_path_string
+        );
+        std::process::exit(1);
+    }
+
+    if let Err(err) = clear_output_path(Path::new(&output_path_string), &policy.overwrite) {
+        err.print().exit();
+    }
+
+    if let Err(err) = fs::write(&output_path_string, reference) {
+        TiToolsError::FailedToWriteFile(output_path_string, err.to_string())
+            .print()
+            .exit();
+    }
+}
@@ -1,6 +1,6 @@
-use crate::prints;
-
-use super::{load_program, save_edits};
+use super::{load_program, report_edit, save_edits};
+use crate::commands::output::OutputFormat;
+use crate::commands::policy::WritePolicy;
 use std::path::Path;
 
 pub fn comment_command(
@@ -8,6 +8,8 @@ pub fn comment_command(
     comment: String,
     new_file_path: Option<String>,
     delete_old: bool,
+    policy: &WritePolicy,
+    format: OutputFormat,
 ) {
     let input_path = Path::new(&input_path_string).to_path_buf();
     let mut program = load_program(&input_path);
@@ -19,10 +21,21 @@ pub fn comment_command(
         Err(err) => err.print().exit(),
     }
 
-    save_edits(program, &input_path, new_file_path, delete_old);
+    let output_path = save_edits(
+        program,
+        &input_path,
+        new_file_path,
+        delete_old,
+        policy,
+        format,
+    );
 
-    prints!(
-        "[color:bright-green]Successfully set [color:bright-cyan]comment[color:reset] to [color:bright-cyan]{}",
-        comment
+    report_edit(
+        format,
+        &input_path,
+        &output_path,
+        delete_old,
+        "comment",
+        &comment,
     );
 }
@@ -0,0 +1,41 @@
+use super::{load_program, report_edit, save_edits};
+use crate::commands::output::OutputFormat;
+use crate::commands::policy::WritePolicy;
+use std::path::Path;
+
+pub fn description_command(
+    input_path_string: String,
+    description: String,
+    new_file_path: Option<String>,
+    delete_old: bool,
+    policy: &WritePolicy,
+    format: OutputFormat,
+) {
+    let input_path = Path::new(&input_path_string).to_path_buf();
+    let mut program = load_program(&input_path);
+
+    let result = program.set_shell_description(&description);
+
+    match result {
+        Ok(_) => {}
+        Err(err) => err.print().exit(),
+    }
+
+    let output_path = save_edits(
+        program,
+        &input_path,
+        new_file_path,
+        delete_old,
+        policy,
+        format,
+    );
+
+    report_edit(
+        format,
+        &input_path,
+        &output_path,
+        delete_old,
+        "description",
+        &description,
+    );
+}
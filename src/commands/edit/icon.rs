@@ -0,0 +1,61 @@
+use super::{load_program, report_edit, save_edits};
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::shell::Icon;
+use crate::commands::output::OutputFormat;
+use crate::commands::policy::WritePolicy;
+use std::path::Path;
+
+pub fn icon_command(
+    input_path_string: String,
+    icon_path_string: String,
+    new_file_path: Option<String>,
+    delete_old: bool,
+    policy: &WritePolicy,
+    format: OutputFormat,
+) {
+    let input_path = Path::new(&input_path_string).to_path_buf();
+    let mut program = load_program(&input_path);
+
+    let text = match std::fs::read_to_string(&icon_path_string) {
+        Ok(text) => text,
+        Err(err) => TiToolsError::FailedToReadFile(err.to_string())
+            .print()
+            .exit(),
+    };
+
+    // the new icon is drawn like details prints the old one, at the same size
+    let result = match program.shell_header() {
+        Some(header) => match header.icon {
+            Some(old) => {
+                Icon::from_ascii(&text, &old).and_then(|icon| program.set_shell_icon(&icon))
+            }
+            None => Err(TiToolsError::NoShellIcon(header.kind.to_string())),
+        },
+        None => Err(TiToolsError::NoShellHeader(
+            program.metadata.name.to_string(),
+        )),
+    };
+
+    match result {
+        Ok(_) => {}
+        Err(err) => err.print().exit(),
+    }
+
+    let output_path = save_edits(
+        program,
+        &input_path,
+        new_file_path,
+        delete_old,
+        policy,
+        format,
+    );
+
+    report_edit(
+        format,
+        &input_path,
+        &output_path,
+        delete_old,
+        "icon",
+        &icon_path_string,
+    );
+}
@@ -1,5 +1,7 @@
 pub mod archive;
 pub mod comment;
+pub mod description;
+pub mod icon;
 pub mod lock;
 pub mod rename;
 pub mod unarchive;
@@ -8,10 +10,23 @@ pub mod unlock;
 use crate::calculator::errors::TiToolsError;
 use crate::calculator::program::Program;
 use crate::calculator::DisplayMode;
+use crate::commands::output::{print_json, OutputFormat};
+use crate::commands::policy::WritePolicy;
+use crate::prints;
+use serde::Serialize;
 use std::path::{Path, PathBuf};
 
+#[derive(Serialize)]
+struct EditReport<'a> {
+    input: String,
+    output: String,
+    deleted_input: bool,
+    property: &'a str,
+    value: &'a str,
+}
+
 fn load_program(input_path: &Path) -> Program {
-    let program = Program::load_from_8xp(input_path.to_path_buf(), DisplayMode::Accessible); 
+    let program = Program::load_from_8xp(input_path.to_path_buf(), DisplayMode::Accessible);
 
     match program {
         Ok(program) => program,
@@ -24,18 +39,12 @@ fn save_edits(
     input_path: &PathBuf,
     new_file_path: Option<String>,
     delete_old: bool,
-) {
-    if new_file_path.is_none() {
-        let result = program.save_to(&input_path.to_path_buf());
-
-        match result {
-            Ok(_) => {}
-            Err(err) => err.print().exit(),
-        }
-    } else {
-        let new_file_path = new_file_path.unwrap();
+    policy: &WritePolicy,
+    format: OutputFormat,
+) -> PathBuf {
+    if let Some(new_file_path) = new_file_path {
         let new_file_path = Path::new(&new_file_path).to_path_buf();
-        let result = program.save_to(&new_file_path);
+        let result = program.save_to(&new_file_path, &policy.overwrite);
 
         match result {
             Ok(_) => {}
@@ -47,12 +56,51 @@ fn save_edits(
 
             match result {
                 Ok(_) => {
-                    println!("Deleted old file.");
+                    if !format.is_json() {
+                        println!("Deleted old file.");
+                    }
                 }
                 Err(err) => TiToolsError::FailedToDeleteFile(err.to_string())
                     .print()
                     .exit(),
             }
         }
+
+        new_file_path
+    } else {
+        let result = program.save_to(input_path, &policy.overwrite);
+
+        match result {
+            Ok(_) => {}
+            Err(err) => err.print().exit(),
+        }
+
+        input_path.to_path_buf()
     }
 }
+
+fn report_edit(
+    format: OutputFormat,
+    input_path: &Path,
+    output_path: &Path,
+    deleted_input: bool,
+    property: &str,
+    value: &str,
+) {
+    if format.is_json() {
+        print_json(&EditReport {
+            input: input_path.display().to_string(),
+            output: output_path.display().to_string(),
+            deleted_input,
+            property,
+            value,
+        });
+        return;
+    }
+
+    prints!(
+        "[color:bright-green]Successfully set [color:bright-cyan]{}[color:reset] to [color:bright-cyan]{}",
+        property,
+        value
+    );
+}
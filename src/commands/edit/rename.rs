@@ -1,5 +1,6 @@
-use super::{load_program, save_edits};
-use crate::prints;
+use super::{load_program, report_edit, save_edits};
+use crate::commands::output::OutputFormat;
+use crate::commands::policy::WritePolicy;
 use std::path::Path;
 
 pub fn rename_command(
@@ -7,6 +8,8 @@ pub fn rename_command(
     name: String,
     new_file_path: Option<String>,
     delete_old: bool,
+    policy: &WritePolicy,
+    format: OutputFormat,
 ) {
     let input_path = Path::new(&input_path_string).to_path_buf();
     let mut program = load_program(&input_path);
@@ -18,10 +21,14 @@ pub fn rename_command(
         Err(err) => err.print().exit(),
     }
 
-    save_edits(program, &input_path, new_file_path, delete_old);
-
-    prints!(
-        "[color:bright-green]Successfully set [color:bright-cyan]name[color:reset] to [color:bright-cyan]{}",
-        name
+    let output_path = save_edits(
+        program,
+        &input_path,
+        new_file_path,
+        delete_old,
+        policy,
+        format,
     );
+
+    report_edit(format, &input_path, &output_path, delete_old, "name", &name);
 }
@@ -1,18 +1,35 @@
-use super::{load_program, save_edits};
-use crate::prints;
+use super::{load_program, report_edit, save_edits};
+use crate::commands::output::OutputFormat;
+use crate::commands::policy::WritePolicy;
 use std::path::Path;
 
 pub fn unarchive_command(
     input_path_string: String,
     new_file_path: Option<String>,
     delete_old: bool,
+    policy: &WritePolicy,
+    format: OutputFormat,
 ) {
     let input_path = Path::new(&input_path_string).to_path_buf();
     let mut program = load_program(&input_path);
 
     program.metadata.unarchive();
 
-    save_edits(program, &input_path, new_file_path, delete_old);
+    let output_path = save_edits(
+        program,
+        &input_path,
+        new_file_path,
+        delete_old,
+        policy,
+        format,
+    );
 
-    prints!("[color:bright-green]Successfully set [color:bright-cyan]destination[color:reset] to [color:bright-cyan]RAM");
+    report_edit(
+        format,
+        &input_path,
+        &output_path,
+        delete_old,
+        "destination",
+        "RAM",
+    );
 }
@@ -0,0 +1,144 @@
+use crate::calculator::format::format_txt;
+use crate::calculator::{DisplayMode, EncodeMode};
+use crate::commands::output::{print_json, OutputFormat};
+use crate::commands::walk::{collect_program_files, FileFilter};
+use crate::prints;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+pub struct FmtOptions {
+    pub display_mode: String,
+    pub encode_mode: String,
+    pub indent: bool,
+    pub check: bool,
+    pub preview: bool,
+    pub recursive: bool,
+}
+
+#[derive(Serialize)]
+struct FmtReport {
+    file: String,
+    changed: bool,
+    written: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    formatted: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+pub fn fmt_command(input_path_string: String, options: FmtOptions, format: OutputFormat) {
+    let input_path = Path::new(&input_path_string);
+
+    if !input_path.exists() {
+        prints!("[color:bright-red]Error:[color:reset] Failed to find file or directory at the input path");
+        std::process::exit(1);
+    }
+
+    let display_mode = match DisplayMode::from_string(&options.display_mode) {
+        Ok(display_mode) => display_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let encode_mode = match EncodeMode::from_string(&options.encode_mode) {
+        Ok(encode_mode) => encode_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let files = if input_path.is_dir() {
+        let filter = FileFilter {
+            recursive: options.recursive,
+            include: vec!["*.txt".to_string()],
+            exclude: Vec::new(),
+        };
+
+        match collect_program_files(input_path, &filter) {
+            Ok(files) => files,
+            Err(err) => err.print().exit(),
+        }
+    } else {
+        vec![input_path.to_path_buf()]
+    };
+
+    let mut reports = Vec::new();
+
+    for path in files {
+        let mut report = FmtReport {
+            file: path.display().to_string(),
+            changed: false,
+            written: false,
+            formatted: None,
+            error: None,
+        };
+
+        let result = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|source| {
+                format_txt(&path, &display_mode, &encode_mode, options.indent)
+                    .map(|formatted| (source, formatted))
+                    .map_err(|err| err.to_string())
+            });
+
+        match result {
+            Ok((source, formatted)) => {
+                report.changed = source != formatted;
+
+                if options.preview {
+                    report.formatted = Some(formatted);
+                } else if report.changed && !options.check {
+                    match fs::write(&path, &formatted) {
+                        Ok(_) => report.written = true,
+                        Err(err) => report.error = Some(err.to_string()),
+                    }
+                }
+            }
+            Err(err) => report.error = Some(err),
+        }
+
+        reports.push(report);
+    }
+
+    let failed = reports
+        .iter()
+        .any(|report| report.error.is_some() || (options.check && report.changed));
+
+    if format.is_json() {
+        if input_path.is_dir() {
+            print_json(&reports);
+        } else {
+            print_json(&reports[0]);
+        }
+    } else {
+        print_reports(&reports, options.check);
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+fn print_reports(reports: &[FmtReport], check: bool) {
+    for report in reports {
+        if let Some(formatted) = &report.formatted {
+            println!("{}", formatted);
+            continue;
+        }
+
+        match &report.error {
+            Some(error) => prints!(
+                "[color:bright-red]Failed to format[color:reset] [color:bright-cyan]{}[color:reset]: {}",
+                report.file,
+                error
+            ),
+            None if report.written => prints!(
+                "[color:bright-green]Formatted[color:reset] [color:bright-cyan]{}",
+                report.file
+            ),
+            None if check && report.changed => prints!(
+                "[color:bright-yellow]Needs formatting:[color:reset] [color:bright-cyan]{}",
+                report.file
+            ),
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::lint::{lint_program, program_calls, Diagnostic, Severity};
+use crate::calculator::program::{
+    detect_file_type, get_file_type, Program, ProgramFileType, TXT_HEADER_LINES,
+};
+use crate::calculator::{DisplayMode, EncodeMode};
+use crate::commands::output::{print_json, OutputFormat};
+use crate::commands::walk::{collect_program_files, FileFilter};
+use crate::prints;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+pub struct LintOptions {
+    pub encode_mode: String,
+    pub recursive: bool,
+    pub subprogram: bool,
+}
+
+#[derive(Serialize)]
+struct LintReport {
+    file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+pub fn lint_command(input_path_string: String, options: LintOptions, format: OutputFormat) {
+    let input_path = Path::new(&input_path_string);
+
+    if !input_path.exists() {
+        prints!("[color:bright-red]Error:[color:reset] Failed to find file or directory at the input path");
+        std::process::exit(1);
+    }
+
+    let encode_mode = match EncodeMode::from_string(&options.encode_mode) {
+        Ok(encode_mode) => encode_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let files = if input_path.is_dir() {
+        let filter = FileFilter {
+            recursive: options.recursive,
+            include: Vec::new(),
+            exclude: Vec::new(),
+        };
+
+        match collect_program_files(input_path, &filter) {
+            Ok(files) => files,
+            Err(err) => err.print().exit(),
+        }
+    } else {
+        vec![input_path.to_path_buf()]
+    };
+
+    let programs: Vec<(PathBuf, Result<Program, TiToolsError>)> = files
+        .into_iter()
+        .map(|path| {
+            let program = detect_file_type(&path).and_then(|file_type| {
+                Program::load_from_file(
+                    path.to_path_buf(),
+                    file_type,
+                    DisplayMode::Accessible,
+                    &encode_mode,
+                )
+            });
+            (path, program)
+        })
+        .collect();
+
+    // Programs called by another program in the directory are treated as subprograms
+    let mut called_programs = Vec::new();
+    for (_, program) in &programs {
+        if let Ok(program) = program {
+            called_programs.extend(program_calls(program).unwrap_or_default());
+        }
+    }
+
+    let reports: Vec<LintReport> = programs
+        .iter()
+        .map(|(path, program)| {
+            let mut report = LintReport {
+                file: path.display().to_string(),
+                error: None,
+                diagnostics: Vec::new(),
+            };
+
+            let diagnostics = program
+                .as_ref()
+                .map_err(|err| err.to_string())
+                .and_then(|program| {
+                    let subprogram =
+                        options.subprogram || called_programs.contains(&program.metadata.name);
+
+                    lint_program(program, subprogram).map_err(|err| err.to_string())
+                });
+
+            match diagnostics {
+                Ok(diagnostics) => report.diagnostics = diagnostics,
+                Err(err) => report.error = Some(err),
+            }
+
+            // Lines in txt files are counted from the top of the file
+            if let Ok(ProgramFileType::TXT) = get_file_type(path) {
+                for diagnostic in &mut report.diagnostics {
+                    diagnostic.line += TXT_HEADER_LINES;
+                }
+            }
+
+            report
+        })
+        .collect();
+
+    let failed = reports.iter().any(|report| {
+        report.error.is_some()
+            || report
+                .diagnostics
+                .iter()
+                .any(|diagnostic| diagnostic.severity == Severity::Error)
+    });
+
+    if format.is_json() {
+        if input_path.is_dir() {
+            print_json(&reports);
+        } else {
+            print_json(&reports[0]);
+        }
+    } else {
+        print_reports(&reports);
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+fn print_reports(reports: &[LintReport]) {
+    let mut errors = 0;
+    let mut warnings = 0;
+
+    for report in reports {
+        if let Some(error) = &report.error {
+            errors += 1;
+            prints!(
+                "[color:bright-cyan]{}[color:reset]: [color:bright-red]error:[color:reset] {}",
+                report.file,
+                error
+            );
+        }
+
+        for diagnostic in &report.diagnostics {
+            let color = match diagnostic.severity {
+                Severity::Error => {
+                    errors += 1;
+                    "bright-red"
+                }
+                Severity::Warning => {
+                    warnings += 1;
+                    "bright-yellow"
+                }
+            };
+
+            prints!(
+                "[color:bright-cyan]{}:{}[color:reset]: [color:{}]{}[color:reset] ({}) {}",
+                report.file,
+                diagnostic.line,
+                color,
+                diagnostic.severity,
+                diagnostic.rule,
+                diagnostic.message
+            );
+        }
+    }
+
+    prints!(
+        "[color:bright-red]{}[color:reset] errors, [color:bright-yellow]{}[color:reset] warnings in {} files",
+        errors,
+        warnings,
+        reports.len()
+    );
+}
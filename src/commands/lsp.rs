@@ -0,0 +1,122 @@
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::lsp::Server;
+use crate::calculator::{DisplayMode, EncodeMode};
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+
+const PARSE_ERROR: i64 = -32700;
+
+pub struct LspOptions {
+    pub display_mode: String,
+    pub encode_mode: String,
+}
+
+/// Serves the Language Server Protocol over stdin and stdout until the client exits
+pub fn lsp_command(options: LspOptions) {
+    let display_mode = match DisplayMode::from_string(&options.display_mode) {
+        Ok(display_mode) => display_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let encode_mode = match EncodeMode::from_string(&options.encode_mode) {
+        Ok(encode_mode) => encode_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let mut server = match Server::new(display_mode, encode_mode) {
+        Ok(server) => server,
+        Err(err) => err.print().exit(),
+    };
+
+    let stdin = std::io::stdin();
+    let mut reader = stdin.lock();
+    let mut stdout = std::io::stdout();
+
+    // stdout belongs to the protocol, so errors go to stderr
+    loop {
+        let content = match read_message(&mut reader) {
+            Ok(Some(content)) => content,
+            Ok(None) => break,
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        };
+
+        let replies = match serde_json::from_str::<Value>(&content) {
+            Ok(message) => server.handle(&message),
+            Err(err) => vec![json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": PARSE_ERROR, "message": err.to_string() },
+            })],
+        };
+
+        for reply in replies {
+            if let Err(err) = write_message(&mut stdout, &reply) {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+
+        if let Some(code) = server.exit_code() {
+            std::process::exit(code);
+        }
+    }
+
+    // the client went away without asking to exit
+    std::process::exit(1);
+}
+
+/// Reads the content of the next message, `None` once the input is closed
+fn read_message(reader: &mut impl BufRead) -> Result<Option<String>, TiToolsError> {
+    let mut length = None;
+
+    loop {
+        let mut header = String::new();
+
+        match reader.read_line(&mut header) {
+            Ok(0) => return Ok(None),
+            Ok(_) => {}
+            Err(err) => return Err(TiToolsError::FileRead(err.to_string())),
+        }
+
+        let header = header.trim_end();
+
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length.ok_or_else(|| {
+        TiToolsError::FileRead("message is missing its Content-Length".to_string())
+    })?;
+
+    let mut content = vec![0; length];
+    reader
+        .read_exact(&mut content)
+        .map_err(|err| TiToolsError::FileRead(err.to_string()))?;
+
+    String::from_utf8(content)
+        .map(Some)
+        .map_err(|err| TiToolsError::FileRead(err.to_string()))
+}
+
+fn write_message(writer: &mut impl Write, message: &Value) -> Result<(), TiToolsError> {
+    let content = message.to_string();
+
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )
+    .and_then(|_| writer.flush())
+    .map_err(|err| TiToolsError::FileWrite(err.to_string()))
+}
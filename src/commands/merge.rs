@@ -0,0 +1,186 @@
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::merge::{merge_programs, MetadataConflict};
+use crate::calculator::program::{clear_output_path, detect_file_type, Program};
+use crate::calculator::{DisplayMode, EncodeMode};
+use crate::commands::output::{print_json, OutputFormat};
+use crate::commands::policy::WritePolicy;
+use crate::prints;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+pub struct MergeOptions {
+    pub output_path_string: Option<String>,
+    pub conflicts_path_string: Option<String>,
+    pub display_mode: String,
+    pub encode_mode: String,
+}
+
+#[derive(Serialize)]
+struct MergeReport {
+    output: String,
+    conflicts: usize,
+    #[serde(rename = "metadata-conflicts")]
+    metadata_conflicts: Vec<MetadataConflict>,
+    #[serde(rename = "conflict-file", skip_serializing_if = "Option::is_none")]
+    conflict_file: Option<String>,
+}
+
+/// A three way merge that works as a git merge driver, writing the result over ours by default
+pub fn merge_command(
+    base_path_string: String,
+    ours_path_string: String,
+    theirs_path_string: String,
+    options: MergeOptions,
+    policy: &WritePolicy,
+    format: OutputFormat,
+) {
+    let display_mode = match DisplayMode::from_string(&options.display_mode) {
+        Ok(display_mode) => display_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let encode_mode = match EncodeMode::from_string(&options.encode_mode) {
+        Ok(encode_mode) => encode_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    // git gives merge drivers temporary files without extensions
+    let load = |path_string: &str| {
+        let path = Path::new(path_string);
+
+        match detect_file_type(path).and_then(|file_type| {
+            Program::load_from_file(
+                path.to_path_buf(),
+                file_type,
+                display_mode.clone(),
+                &encode_mode,
+            )
+        }) {
+            Ok(program) => program,
+            Err(err) => err.print().exit(),
+        }
+    };
+
+    let base = load(&base_path_string);
+    let ours = load(&ours_path_string);
+    let theirs = load(&theirs_path_string);
+
+    let replaces_ours = options.output_path_string.is_none();
+    let output_path_string = options.output_path_string.unwrap_or(ours_path_string);
+    let output_path = Path::new(&output_path_string);
+
+    let output_type = match detect_file_type(output_path) {
+        Ok(output_type) => output_type,
+        Err(err) => err.print().exit(),
+    };
+
+    let merge = match merge_programs(&base, ours, &theirs, &display_mode) {
+        Ok(merge) => merge,
+        Err(err) => err.print().exit(),
+    };
+
+    let mut report = MergeReport {
+        output: output_path_string.to_string(),
+        conflicts: merge.conflicts,
+        metadata_conflicts: Vec::new(),
+        conflict_file: None,
+    };
+
+    // A txt output can hold the conflict markers itself, other outputs keep the body of ours
+    let output = if merge.conflicts > 0 && output_type.is_txt() {
+        Ok(merge.text.as_bytes().to_vec())
+    } else {
+        merge.program.to_bytes(&output_type)
+    };
+
+    // replacing ours is what git asks of a merge driver, only other outputs follow the policy
+    if !replaces_ours {
+        if let Err(err) = clear_output_path(output_path, &policy.overwrite) {
+            err.print().exit();
+        }
+    }
+
+    if let Err(err) = output.and_then(|output| write_file(&output_path_string, output)) {
+        err.print().exit();
+    }
+
+    // Only written when asked for, git runs merge drivers on temporary files in the worktree root
+    // and a file next to them would be left behind
+    if merge.conflicts > 0 && !output_type.is_txt() {
+        if let Some(conflicts_path_string) = options.conflicts_path_string {
+            if let Err(err) =
+                clear_output_path(Path::new(&conflicts_path_string), &policy.overwrite)
+                    .and_then(|_| write_file(&conflicts_path_string, merge.text.as_bytes()))
+            {
+                err.print().exit();
+            }
+
+            report.conflict_file = Some(conflicts_path_string);
+        }
+    }
+
+    let clean = merge.is_clean();
+    report.metadata_conflicts = merge.metadata_conflicts;
+
+    if format.is_json() {
+        print_json(&report);
+    } else {
+        print_report(&report, output_type.is_txt());
+    }
+
+    // git reads a non zero exit code as a merge with conflicts
+    if !clean {
+        std::process::exit(1);
+    }
+}
+
+fn write_file<T: AsRef<[u8]>>(path_string: &str, content: T) -> Result<(), TiToolsError> {
+    fs::write(path_string, content)
+        .map_err(|err| TiToolsError::FailedToWriteFile(path_string.to_string(), err.to_string()))
+}
+
+fn print_report(report: &MergeReport, output_is_txt: bool) {
+    if report.conflicts == 0 && report.metadata_conflicts.is_empty() {
+        prints!(
+            "[color:bright-green]Merged cleanly[color:reset] into {}",
+            report.output
+        );
+        return;
+    }
+
+    for conflict in &report.metadata_conflicts {
+        prints!(
+            "[color:bright-red]Conflict:[color:reset] both sides changed the {}, base {}, ours {}, theirs {}, keeping ours",
+            conflict.field,
+            conflict.base,
+            conflict.ours,
+            conflict.theirs
+        );
+    }
+
+    if report.conflicts > 0 {
+        let places = if report.conflicts == 1 {
+            "1 place".to_string()
+        } else {
+            format!("{} places", report.conflicts)
+        };
+
+        match (&report.conflict_file, output_is_txt) {
+            (Some(conflict_file), _) => prints!(
+                "[color:bright-red]Conflict:[color:reset] both sides changed the same lines in {}, marked in {}",
+                places,
+                conflict_file
+            ),
+            (None, true) => prints!(
+                "[color:bright-red]Conflict:[color:reset] both sides changed the same lines in {}, marked in {}",
+                places,
+                report.output
+            ),
+            (None, false) => prints!(
+                "[color:bright-red]Conflict:[color:reset] both sides changed the same lines in {}, keeping ours, use --conflicts to write the markers to a txt file",
+                places
+            ),
+        }
+    }
+}
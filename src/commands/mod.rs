@@ -1,5 +1,25 @@
+pub mod callgraph;
 pub mod convert;
 pub mod details;
+pub mod diff;
+pub mod disasm;
+pub mod docs;
 pub mod edit;
+pub mod format;
+pub mod lint;
+pub mod lsp;
+pub mod merge;
 pub mod models;
+pub mod optimize;
+pub mod output;
+pub mod policy;
+pub mod pool;
+pub mod render;
+pub mod run;
 pub mod search;
+pub mod test;
+pub mod textconv;
+pub mod tokens;
+pub mod walk;
+pub mod watch;
+pub mod xref;
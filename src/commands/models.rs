@@ -1,6 +1,13 @@
 use crate::calculator::models::Model;
+use crate::commands::output::{print_json, OutputFormat};
+
+pub fn models_command(format: OutputFormat) {
+    if format.is_json() {
+        let models: Vec<String> = Model::all().iter().map(Model::to_string).collect();
+        print_json(&models);
+        return;
+    }
 
-pub fn models_command() {
     println!("Supported TI calculator models:");
     Model::display_models();
 }
@@ -0,0 +1,145 @@
+use crate::calculator::optimize::{optimize_program, Transform, TransformSavings};
+use crate::calculator::program::{detect_file_type, Program};
+use crate::calculator::{DisplayMode, EncodeMode};
+use crate::commands::output::{print_json, OutputFormat};
+use crate::commands::policy::WritePolicy;
+use crate::prints;
+use serde::Serialize;
+use std::path::Path;
+
+pub struct OptimizeOptions {
+    pub output_path_string: Option<String>,
+    pub transforms: String,
+    pub display_mode: String,
+    pub encode_mode: String,
+    pub preview: bool,
+}
+
+#[derive(Serialize)]
+struct OptimizeReport {
+    input: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<String>,
+    original_size: usize,
+    optimized_size: usize,
+    transforms: Vec<TransformSavings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    translation: Option<String>,
+}
+
+pub fn optimize_command(
+    input_path_string: String,
+    options: OptimizeOptions,
+    policy: &WritePolicy,
+    format: OutputFormat,
+) {
+    let input_path = Path::new(&input_path_string);
+
+    if options.output_path_string.is_none() && !options.preview {
+        prints!("[color:bright-yellow]Warning:[color:reset] An output path or preview option is required");
+        std::process::exit(1);
+    }
+
+    let transforms = match parse_transforms(&options.transforms) {
+        Some(transforms) => transforms,
+        None => {
+            prints!("[color:bright-red]Error:[color:reset] Transforms must be all or a comma separated list of parens, braces, quotes and zero-compare");
+            std::process::exit(1);
+        }
+    };
+
+    let display_mode = match DisplayMode::from_string(&options.display_mode) {
+        Ok(display_mode) => display_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let encode_mode = match EncodeMode::from_string(&options.encode_mode) {
+        Ok(encode_mode) => encode_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let mut program = match detect_file_type(input_path).and_then(|file_type| {
+        Program::load_from_file(
+            input_path.to_path_buf(),
+            file_type,
+            display_mode,
+            &encode_mode,
+        )
+    }) {
+        Ok(program) => program,
+        Err(err) => err.print().exit(),
+    };
+
+    let original_size = program.body.bytes.len();
+
+    let savings = match optimize_program(&mut program, &transforms) {
+        Ok(savings) => savings,
+        Err(err) => err.print().exit(),
+    };
+
+    let mut report = OptimizeReport {
+        input: input_path.display().to_string(),
+        output: None,
+        original_size,
+        optimized_size: program.body.bytes.len(),
+        transforms: savings,
+        translation: None,
+    };
+
+    if let Some(output_path_string) = &options.output_path_string {
+        let output_path = Path::new(output_path_string).to_path_buf();
+
+        if let Err(err) = program.save_to(&output_path, &policy.overwrite) {
+            err.print().exit()
+        }
+
+        report.output = Some(output_path.display().to_string());
+    }
+
+    if format.is_json() {
+        if options.preview {
+            report.translation = Some(program.body.translation.to_string());
+        }
+
+        print_json(&report);
+        return;
+    }
+
+    if options.preview {
+        println!("{}\n", program);
+    }
+
+    for savings in &report.transforms {
+        prints!(
+            "[color:bright-cyan]{:<14}[color:reset] {} bytes",
+            savings.transform,
+            savings.bytes_saved
+        );
+    }
+
+    prints!(
+        "[color:bright-green]Saved {} bytes[color:reset] ({} -> {} bytes)",
+        report.original_size - report.optimized_size,
+        report.original_size,
+        report.optimized_size
+    );
+
+    if let Some(output) = &report.output {
+        prints!(
+            "[color:bright-green]Successfully saved[color:reset] [color:bright-cyan]{}[color:reset] to [color:bright-cyan]{}",
+            program.metadata.name,
+            output
+        );
+    }
+}
+
+fn parse_transforms(transforms: &str) -> Option<Vec<Transform>> {
+    if transforms == "all" {
+        return Some(Transform::all());
+    }
+
+    transforms
+        .split(',')
+        .map(|transform| Transform::from_string(transform.trim()).ok())
+        .collect()
+}
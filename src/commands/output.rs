@@ -0,0 +1,61 @@
+use crate::calculator::errors::TiToolsError;
+use crate::cli::{Arg, Command};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    pub fn arg() -> Arg {
+        Arg::new(
+            "format",
+            "The format command output is printed in [text, json] | Default: text",
+            "format",
+            'O',
+        )
+        .with_value_name("FORMAT")
+        .with_default_value("text")
+    }
+
+    pub fn from_string(format: &str) -> Result<OutputFormat, TiToolsError> {
+        match format {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(TiToolsError::Match(
+                format.to_string(),
+                "OutputFormat".to_string(),
+            )),
+        }
+    }
+
+    pub fn from_command(command: &Command) -> OutputFormat {
+        let format = command.get_arg("format").throw_if_none();
+
+        match OutputFormat::from_string(&format) {
+            Ok(format) => format,
+            Err(err) => err.print().exit(),
+        }
+    }
+
+    pub fn is_json(&self) -> bool {
+        matches!(self, OutputFormat::Json)
+    }
+}
+
+/// Print a serializable document to stdout as pretty json
+pub fn print_json<T: Serialize>(value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{}", json),
+        Err(err) => TiToolsError::FailedToSerializeJson(err.to_string())
+            .print()
+            .exit(),
+    }
+}
+
+/// Bytes as an uppercase hex string without separators, used for json output
+pub fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
@@ -0,0 +1,84 @@
+use crate::calculator::program::OverwritePolicy;
+use crate::cli::{Arg, Command};
+use std::io::Write;
+
+/// How a command should behave when it would otherwise stop and ask the user something
+pub struct WritePolicy {
+    pub overwrite: OverwritePolicy,
+    pub assume_yes: bool,
+}
+
+impl WritePolicy {
+    /// The arguments shared by every command that writes files
+    pub fn args() -> Vec<Arg> {
+        vec![
+            Arg::new(
+                "force",
+                "Overwrite existing files without asking",
+                "force",
+                'F',
+            )
+            .conflicts_with("no-clobber")
+            .conflicts_with("backup"),
+            Arg::new(
+                "no-clobber",
+                "Never overwrite existing files, skip them instead",
+                "no-clobber",
+                'N',
+            )
+            .conflicts_with("force")
+            .conflicts_with("backup"),
+            Arg::new(
+                "backup",
+                "Move existing files to <FILE>.bak before overwriting them",
+                "backup",
+                'B',
+            )
+            .conflicts_with("force")
+            .conflicts_with("no-clobber"),
+            Arg::new(
+                "yes",
+                "Answer yes when asked to overwrite a file or create the output directory",
+                "yes",
+                'y',
+            ),
+        ]
+    }
+
+    pub fn from_command(command: &Command) -> WritePolicy {
+        let assume_yes = command.has("yes");
+
+        let overwrite = if command.has("backup") {
+            OverwritePolicy::Backup
+        } else if command.has("no-clobber") {
+            OverwritePolicy::NoClobber
+        } else if command.has("force") || assume_yes {
+            OverwritePolicy::Force
+        } else {
+            OverwritePolicy::Prompt
+        };
+
+        WritePolicy {
+            overwrite,
+            assume_yes,
+        }
+    }
+
+    /// Ask a yes or no question on stderr, answering yes straight away when `--yes` was passed
+    pub fn confirm(&self, question: &str) -> bool {
+        eprintln!("{} [y/N]", question);
+
+        if self.assume_yes {
+            eprintln!("> y");
+            return true;
+        }
+
+        let mut input = String::new();
+        eprint!("> ");
+        std::io::stderr().flush().unwrap();
+        std::io::stdin().read_line(&mut input).unwrap();
+        let input = input.trim();
+
+        input == "y" || input == "Y"
+    }
+}
@@ -0,0 +1,108 @@
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::file::{decode_program, DecodedToken};
+use crate::calculator::program::{clear_output_path, detect_file_type, Program};
+use crate::calculator::render::{highlight, render_html, Highlight};
+use crate::calculator::{DisplayMode, EncodeMode};
+use crate::commands::policy::WritePolicy;
+use crate::prints;
+use crate::styles::Color;
+use std::path::Path;
+
+pub struct RenderOptions {
+    pub output_path_string: Option<String>,
+    pub to: Option<String>,
+    pub display_mode: String,
+    pub encode_mode: String,
+}
+
+pub fn render_command(input_path_string: String, options: RenderOptions, policy: &WritePolicy) {
+    let input_path = Path::new(&input_path_string);
+
+    let display_mode = match DisplayMode::from_string(&options.display_mode) {
+        Ok(display_mode) => display_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let encode_mode = match EncodeMode::from_string(&options.encode_mode) {
+        Ok(encode_mode) => encode_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    // a file is most likely meant to be opened in a browser, the terminal understands ANSI
+    let to = match (&options.to, &options.output_path_string) {
+        (Some(to), _) => to.as_str(),
+        (None, Some(_)) => "html",
+        (None, None) => "ansi",
+    };
+
+    let program = match detect_file_type(input_path).and_then(|file_type| {
+        Program::load_from_file(
+            input_path.to_path_buf(),
+            file_type,
+            display_mode.clone(),
+            &encode_mode,
+        )
+    }) {
+        Ok(program) => program,
+        Err(err) => err.print().exit(),
+    };
+
+    let tokens = match decode_program(&program, &display_mode) {
+        Ok(tokens) => tokens,
+        Err(err) => err.print().exit(),
+    };
+
+    let rendered = match to {
+        "html" => match render_html(&program, &tokens) {
+            Ok(html) => html,
+            Err(err) => err.print().exit(),
+        },
+        "ansi" => render_ansi(&tokens),
+        _ => {
+            prints!(
+                "[color:bright-red]Error:[color:reset] [color:bright-cyan]{}[color:reset] is not a render format, use ansi or html",
+                to
+            );
+            std::process::exit(1);
+        }
+    };
+
+    match options.output_path_string {
+        Some(output_path_string) => {
+            if let Err(err) = clear_output_path(Path::new(&output_path_string), &policy.overwrite) {
+                err.print().exit();
+            }
+
+            if let Err(err) = std::fs::write(&output_path_string, rendered) {
+                TiToolsError::FailedToWriteFile(output_path_string, err.to_string())
+                    .print()
+                    .exit();
+            }
+        }
+        None => print!("{}", rendered),
+    }
+}
+
+fn render_ansi(tokens: &[DecodedToken]) -> String {
+    let mut rendered = String::new();
+
+    for (token, highlight) in tokens.iter().zip(highlight(tokens)) {
+        let color = match highlight {
+            Highlight::Command => Color::BrightBlue,
+            Highlight::Function => Color::BrightCyan,
+            Highlight::Variable => Color::BrightYellow,
+            Highlight::String => Color::BrightGreen,
+            Highlight::Number => Color::BrightPurple,
+            Highlight::Comment => Color::BrightBlack,
+            Highlight::Operator => Color::Reset,
+        };
+
+        rendered.push_str(&color.to_ansi_color_string());
+        rendered.push_str(&token.text);
+    }
+
+    rendered.push_str(&Color::Reset.to_ansi_color_string());
+    rendered.push('\n');
+
+    rendered
+}
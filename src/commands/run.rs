@@ -0,0 +1,129 @@
+use crate::calculator::interpreter::{run_program, RunOptions};
+use crate::calculator::program::{
+    detect_file_type, get_file_type, Program, ProgramFileType, TXT_HEADER_LINES,
+};
+use crate::calculator::{DisplayMode, EncodeMode};
+use crate::commands::output::{print_json, OutputFormat};
+use crate::prints;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub struct RunCommandOptions {
+    pub input_script: Option<String>,
+    pub keys: Option<String>,
+    pub max_steps: String,
+    pub encode_mode: String,
+    pub home_screen: bool,
+}
+
+pub fn run_command(input_path_string: String, options: RunCommandOptions, format: OutputFormat) {
+    let input_path = Path::new(&input_path_string);
+
+    let encode_mode = match EncodeMode::from_string(&options.encode_mode) {
+        Ok(encode_mode) => encode_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let max_steps = match options.max_steps.parse::<usize>() {
+        Ok(max_steps) if max_steps > 0 => max_steps,
+        _ => {
+            prints!(
+                "[color:bright-red]Error:[color:reset] Max steps must be a number greater than 0"
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let inputs = match &options.input_script {
+        Some(script) => match fs::read_to_string(script) {
+            Ok(script) => script.lines().map(|line| line.to_string()).collect(),
+            Err(err) => {
+                prints!(
+                    "[color:bright-red]Error:[color:reset] Failed to read the input script: {}",
+                    err
+                );
+                std::process::exit(1);
+            }
+        },
+        None => Vec::new(),
+    };
+
+    let keys = match &options.keys {
+        Some(keys) => match parse_keys(keys) {
+            Some(keys) => keys,
+            None => {
+                prints!("[color:bright-red]Error:[color:reset] Keys must be a comma separated list of key codes, like 105,25,34");
+                std::process::exit(1);
+            }
+        },
+        None => Vec::new(),
+    };
+
+    let program = match detect_file_type(input_path).and_then(|file_type| {
+        Program::load_from_file(
+            input_path.to_path_buf(),
+            file_type,
+            DisplayMode::Pretty,
+            &encode_mode,
+        )
+    }) {
+        Ok(program) => program,
+        Err(err) => err.print().exit(),
+    };
+
+    let run_options = RunOptions {
+        inputs,
+        keys,
+        max_steps,
+        variables: BTreeMap::new(),
+    };
+
+    let mut execution = match run_program(&program, run_options) {
+        Ok(execution) => execution,
+        Err(err) => err.print().exit(),
+    };
+
+    // Lines in txt files are counted from the top of the file
+    if let (Ok(ProgramFileType::TXT), Some(error)) =
+        (get_file_type(input_path), &mut execution.error)
+    {
+        error.line += TXT_HEADER_LINES;
+    }
+
+    if format.is_json() {
+        print_json(&execution);
+    } else {
+        for line in &execution.output {
+            println!("{}", line);
+        }
+
+        if options.home_screen {
+            let width = execution
+                .screen
+                .iter()
+                .map(|line| line.chars().count())
+                .max()
+                .unwrap_or(0);
+
+            println!();
+            for line in &execution.screen {
+                println!("|{:<width$}|", line, width = width);
+            }
+        }
+
+        if let Some(error) = &execution.error {
+            prints!("[color:bright-red]{}", error);
+        }
+    }
+
+    if execution.error.is_some() {
+        std::process::exit(1);
+    }
+}
+
+fn parse_keys(keys: &str) -> Option<Vec<u16>> {
+    keys.split(',')
+        .map(|key| key.trim().parse::<u16>().ok())
+        .collect()
+}
@@ -1,7 +1,12 @@
 use crate::calculator::errors::TiToolsError;
-use crate::calculator::tokens::{load_token_definitions, load_tokens, OsVersion};
+use crate::calculator::tokens::search::{search_tokens, SearchMatch};
+use crate::calculator::tokens::{
+    load_token_definitions, load_tokens, Map, OsVersion, TokenDefinition,
+};
 use crate::calculator::DisplayMode;
+use crate::commands::output::{print_json, OutputFormat};
 use crate::prints;
+use serde::Serialize;
 
 #[derive(Debug, PartialEq)]
 enum SearchTokenType {
@@ -24,13 +29,52 @@ impl SearchTokenType {
     }
 }
 
-pub fn search_command(token: String, token_type_string: String) {
+pub struct SearchOptions {
+    pub token_type: String,
+    pub fuzzy: bool,
+    pub limit: String,
+}
+
+#[derive(Serialize)]
+struct SearchResult<'a> {
+    token: &'a str,
+    byte: &'a str,
+    definitions: &'a [TokenDefinition],
+}
+
+#[derive(Serialize)]
+struct RankedResult<'a> {
+    #[serde(flatten)]
+    found: &'a SearchMatch,
+    definitions: &'a [TokenDefinition],
+}
+
+pub fn search_command(token: String, options: SearchOptions, format: OutputFormat) {
+    let token_type_string = options.token_type;
     let token_type = match SearchTokenType::from_string(&token_type_string) {
         Ok(token_type) => token_type,
         Err(err) => err.print().exit(),
     };
 
-    if token_type == SearchTokenType::Pretty {
+    let limit = match options.limit.parse::<usize>() {
+        Ok(limit) if limit > 0 => limit,
+        _ => {
+            prints!("[color:bright-red]Error:[color:reset] Limit must be a number greater than 0");
+            std::process::exit(1);
+        }
+    };
+
+    if options.fuzzy {
+        let tokens = match load_tokens(&OsVersion::latest()) {
+            Ok(tokens) => tokens,
+            Err(err) => err.print().exit(),
+        };
+
+        search_ranked(&token, &tokens, limit, format);
+        return;
+    }
+
+    if token_type == SearchTokenType::Pretty && !format.is_json() {
         prints!("[color:bright-yellow]Warning:[color:reset] Pretty tokens are less accurate for best results use accessible")
     }
 
@@ -45,6 +89,7 @@ pub fn search_command(token: String, token_type_string: String) {
                 Some(byte) => fix_byte_format(&byte.0),
                 None => {
                     prints!("[color:bright-red]Error:[color:reset] Failed to find token");
+                    print_suggestions(&token, &tokens);
                     std::process::exit(1);
                 }
             }
@@ -53,6 +98,7 @@ pub fn search_command(token: String, token_type_string: String) {
                 Some(byte) => fix_byte_format(&byte.0),
                 None => {
                     prints!("[color:bright-red]Error:[color:reset] Failed to find token");
+                    print_suggestions(&token, &tokens);
                     std::process::exit(1);
                 }
             }
@@ -66,7 +112,7 @@ pub fn search_command(token: String, token_type_string: String) {
             std::process::exit(1);
         };
 
-        token
+        token.to_string()
     };
 
     let token_definitions = match load_token_definitions() {
@@ -82,11 +128,112 @@ pub fn search_command(token: String, token_type_string: String) {
         }
     };
 
+    if format.is_json() {
+        print_json(&SearchResult {
+            token: &token,
+            byte: &byte,
+            definitions: token_definition,
+        });
+        return;
+    }
+
     println!();
     for syntax_variation in token_definition {
         println!("syntax: {}", syntax_variation.syntax);
-        println!("{}\n", syntax_variation.description);
+        println!("{}", syntax_variation.description);
+
+        for argument in &syntax_variation.arguments {
+            let optional = if argument.optional { ", optional" } else { "" };
+
+            println!(
+                "  {} ({}{}): {}",
+                argument.name, argument.kind, optional, argument.description
+            );
+        }
+
+        if let Some(returns) = &syntax_variation.returns {
+            println!("returns: {}", returns);
+        }
+
+        if !syntax_variation.models.is_empty() {
+            println!("models: {}", syntax_variation.models.join(", "));
+        }
+
+        for example in &syntax_variation.examples {
+            println!("example: {}", example.replace('\n', ":"));
+        }
+
+        println!();
+    }
+}
+
+/// Lists the closest tokens with their best definition, like `search --fuzzy`
+fn search_ranked(query: &str, tokens: &Map, limit: usize, format: OutputFormat) {
+    let token_definitions = match load_token_definitions() {
+        Ok(token_definitions) => token_definitions,
+        Err(err) => err.print().exit(),
+    };
+
+    let matches = search_tokens(tokens, &token_definitions, query, limit);
+
+    if format.is_json() {
+        let results: Vec<RankedResult> = matches
+            .iter()
+            .map(|found| RankedResult {
+                found,
+                definitions: token_definitions
+                    .get(&found.byte)
+                    .map_or(&[], |definitions| definitions.as_slice()),
+            })
+            .collect();
+
+        print_json(&results);
+        return;
     }
+
+    if matches.is_empty() {
+        prints!(
+            "[color:bright-red]Error:[color:reset] No tokens are close to {}",
+            query
+        );
+        std::process::exit(1);
+    }
+
+    for found in &matches {
+        let syntax = token_definitions
+            .get(&found.byte)
+            .and_then(|definitions| definitions.first())
+            .map_or(String::new(), |definition| definition.syntax.to_string());
+
+        prints!(
+            "[color:bright-cyan]{:<8}[color:reset] {:<20} [color:bright-black]{:<12} {:>3}[color:reset] {}",
+            found.byte,
+            found.token,
+            format!("{:?}", found.kind).to_lowercase(),
+            found.score,
+            syntax
+        );
+    }
+}
+
+fn print_suggestions(query: &str, tokens: &Map) {
+    let token_definitions = load_token_definitions().unwrap_or_default();
+    let matches = search_tokens(tokens, &token_definitions, query, 5);
+
+    if matches.is_empty() {
+        return;
+    }
+
+    let suggestions = matches
+        .iter()
+        .map(|found| found.token.to_string())
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    prints!(
+        "[color:bright-yellow]Did you mean:[color:reset] {}",
+        suggestions
+    );
 }
 
 fn fix_byte_format(byte: &str) -> String {
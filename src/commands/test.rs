@@ -0,0 +1,289 @@
+use crate::calculator::program::{
+    clear_output_path, detect_file_type, get_file_type, Program, ProgramFileType, TXT_HEADER_LINES,
+};
+use crate::calculator::testing::{run_test, TestResult, TestSuite};
+use crate::calculator::{DisplayMode, EncodeMode};
+use crate::commands::output::{print_json, OutputFormat};
+use crate::commands::policy::WritePolicy;
+use crate::commands::walk::{collect_program_files, FileFilter};
+use crate::prints;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+pub struct TestOptions {
+    pub encode_mode: String,
+    pub recursive: bool,
+    pub junit: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SuiteReport {
+    file: String,
+    program: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    tests: Vec<TestResult>,
+}
+
+#[derive(Serialize)]
+struct TestReport {
+    passed: usize,
+    failed: usize,
+    suites: Vec<SuiteReport>,
+}
+
+pub fn test_command(
+    input_path_string: String,
+    options: TestOptions,
+    policy: &WritePolicy,
+    format: OutputFormat,
+) {
+    let input_path = Path::new(&input_path_string);
+
+    if !input_path.exists() {
+        prints!("[color:bright-red]Error:[color:reset] Failed to find file or directory at the input path");
+        std::process::exit(1);
+    }
+
+    let encode_mode = match EncodeMode::from_string(&options.encode_mode) {
+        Ok(encode_mode) => encode_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let files = if input_path.is_dir() {
+        let filter = FileFilter {
+            recursive: options.recursive,
+            include: vec!["*.test.json".to_string()],
+            exclude: Vec::new(),
+        };
+
+        match collect_program_files(input_path, &filter) {
+            Ok(files) => files,
+            Err(err) => err.print().exit(),
+        }
+    } else {
+        vec![input_path.to_path_buf()]
+    };
+
+    let suites: Vec<SuiteReport> = files
+        .iter()
+        .map(|file| run_suite(file, &encode_mode))
+        .collect();
+
+    let failed = suites
+        .iter()
+        .flat_map(|suite| &suite.tests)
+        .filter(|test| !test.passed)
+        .count();
+
+    let report = TestReport {
+        passed: suites.iter().map(|suite| suite.tests.len()).sum::<usize>() - failed,
+        failed,
+        suites,
+    };
+
+    if let Some(junit_path) = &options.junit {
+        // the tests already ran, so keeping an old report fails the run instead of quitting quietly
+        if let Err(err) = clear_output_path(Path::new(junit_path), &policy.overwrite) {
+            err.print();
+            std::process::exit(1);
+        }
+
+        if let Err(err) = fs::write(junit_path, junit_xml(&report)) {
+            prints!(
+                "[color:bright-red]Error:[color:reset] Failed to write the JUnit report: {}",
+                err
+            );
+            std::process::exit(1);
+        }
+    }
+
+    if format.is_json() {
+        print_json(&report);
+    } else {
+        print_report(&report);
+    }
+
+    if report.failed > 0 || report.suites.iter().any(|suite| suite.error.is_some()) {
+        std::process::exit(1);
+    }
+}
+
+fn run_suite(file: &Path, encode_mode: &EncodeMode) -> SuiteReport {
+    let mut report = SuiteReport {
+        file: file.display().to_string(),
+        program: String::new(),
+        error: None,
+        tests: Vec::new(),
+    };
+
+    let suite = match fs::read_to_string(file) {
+        Ok(json) => TestSuite::from_string(&json),
+        Err(err) => {
+            report.error = Some(format!("Failed to read file: {}", err));
+            return report;
+        }
+    };
+
+    let suite = match suite {
+        Ok(suite) => suite,
+        Err(err) => {
+            report.error = Some(err.to_string());
+            return report;
+        }
+    };
+
+    // The program is found next to the test file
+    let program_path = file.parent().unwrap_or(Path::new(".")).join(&suite.program);
+    report.program = program_path.display().to_string();
+
+    let program = match detect_file_type(&program_path).and_then(|file_type| {
+        Program::load_from_file(
+            program_path.to_path_buf(),
+            file_type,
+            DisplayMode::Pretty,
+            encode_mode,
+        )
+    }) {
+        Ok(program) => program,
+        Err(err) => {
+            report.error = Some(err.to_string());
+            return report;
+        }
+    };
+
+    for test in &suite.tests {
+        match run_test(&program, test) {
+            Ok(mut result) => {
+                // Lines in txt files are counted from the top of the file
+                if let (Ok(ProgramFileType::TXT), Some(error)) =
+                    (get_file_type(&program_path), &mut result.error)
+                {
+                    error.line += TXT_HEADER_LINES;
+                }
+
+                report.tests.push(result);
+            }
+            Err(err) => {
+                report.error = Some(err.to_string());
+                break;
+            }
+        }
+    }
+
+    report
+}
+
+fn print_report(report: &TestReport) {
+    for suite in &report.suites {
+        prints!(
+            "[color:bright-cyan]{}[color:reset] {}",
+            suite.file,
+            suite.program
+        );
+
+        for test in &suite.tests {
+            if test.passed {
+                prints!("    [color:bright-green]PASS[color:reset] {}", test.name);
+                continue;
+            }
+
+            prints!("    [color:bright-red]FAIL[color:reset] {}", test.name);
+
+            for failure in &test.failures {
+                prints!("        {}", failure);
+            }
+
+            if let Some(error) = &test.error {
+                prints!("        [color:bright-black]stopped on line {}", error.line);
+            }
+        }
+
+        if let Some(error) = &suite.error {
+            prints!("    [color:bright-red]Error:[color:reset] {}", error);
+        }
+    }
+
+    let color = if report.failed > 0 {
+        "bright-red"
+    } else {
+        "bright-green"
+    };
+
+    prints!(
+        "\n[color:{}]{} passed, {} failed",
+        color,
+        report.passed,
+        report.failed
+    );
+}
+
+/// A JUnit report, the format most CI services read test results from
+fn junit_xml(report: &TestReport) -> String {
+    let errors = report
+        .suites
+        .iter()
+        .filter(|suite| suite.error.is_some())
+        .count();
+
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"ti-tools\" tests=\"{}\" failures=\"{}\" errors=\"{}\">\n",
+        report.passed + report.failed,
+        report.failed,
+        errors
+    );
+
+    for suite in &report.suites {
+        let failures = suite.tests.iter().filter(|test| !test.passed).count();
+
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\">\n",
+            escape_xml(&suite.file),
+            suite.tests.len(),
+            failures,
+            suite.error.is_some() as usize
+        ));
+
+        for test in &suite.tests {
+            xml.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\"",
+                escape_xml(&test.name),
+                escape_xml(&suite.program)
+            ));
+
+            if test.passed {
+                xml.push_str("/>\n");
+                continue;
+            }
+
+            let mut details = test.failures.join("\n");
+            if let Some(error) = &test.error {
+                details.push_str(&format!("\nstopped on line {}", error.line));
+            }
+
+            xml.push_str(&format!(
+                ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                escape_xml(&test.failures[0]),
+                escape_xml(&details)
+            ));
+        }
+
+        if let Some(error) = &suite.error {
+            xml.push_str(&format!("    <error message=\"{}\"/>\n", escape_xml(error)));
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
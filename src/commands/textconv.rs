@@ -0,0 +1,51 @@
+use crate::calculator::file::{decode, load_program_tokens};
+use crate::calculator::program::{detect_file_type, Program};
+use crate::calculator::{DisplayMode, EncodeMode};
+use std::path::Path;
+
+pub struct TextconvOptions {
+    pub display_mode: String,
+    pub encode_mode: String,
+}
+
+/// Prints a program as a txt file decoded from its bytes, so the same program always prints the same text
+pub fn textconv_command(input_path_string: String, options: TextconvOptions) {
+    let input_path = Path::new(&input_path_string);
+
+    let display_mode = match DisplayMode::from_string(&options.display_mode) {
+        Ok(display_mode) => display_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let encode_mode = match EncodeMode::from_string(&options.encode_mode) {
+        Ok(encode_mode) => encode_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let file_type = match detect_file_type(input_path) {
+        Ok(file_type) => file_type,
+        Err(err) => err.print().exit(),
+    };
+
+    let program = match Program::load_from_file(
+        input_path.to_path_buf(),
+        file_type,
+        display_mode.clone(),
+        &encode_mode,
+    ) {
+        Ok(program) => program,
+        Err(err) => err.print().exit(),
+    };
+
+    let body = load_program_tokens(&program)
+        .and_then(|map| decode(&program.body.bytes, &map, "en", &display_mode));
+
+    match body {
+        Ok(body) => println!(
+            "{}\n{}",
+            program.txt_header(&program.header.comment, &display_mode),
+            body
+        ),
+        Err(err) => err.print().exit(),
+    }
+}
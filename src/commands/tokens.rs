@@ -0,0 +1,227 @@
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::models::Model;
+use crate::calculator::tokens::{
+    check_definitions, load_token_definitions, load_token_table, OsVersion, Token, TokenCategory,
+    TokenEntry,
+};
+use crate::commands::output::{hex_string, print_json, OutputFormat};
+use crate::prints;
+use serde::Serialize;
+
+pub struct TokensOptions {
+    pub category: Option<String>,
+    pub model: Option<String>,
+    pub bytes: Option<String>,
+    pub text: Option<String>,
+    pub undefined: bool,
+}
+
+#[derive(Serialize)]
+struct TokenListing<'a> {
+    key: &'a str,
+    bytes: String,
+    category: TokenCategory,
+    display: &'a str,
+    accessible: &'a str,
+    #[serde(rename = "ti-ascii")]
+    ti_ascii: &'a str,
+    versions: Vec<VersionListing<'a>>,
+}
+
+#[derive(Serialize)]
+struct VersionListing<'a> {
+    since: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    until: Option<String>,
+    display: &'a str,
+    accessible: &'a str,
+    #[serde(rename = "ti-ascii")]
+    ti_ascii: &'a str,
+}
+
+pub fn tokens_command(options: TokensOptions, format: OutputFormat) {
+    let category = match options.category.as_deref().map(TokenCategory::from_string) {
+        Some(Ok(category)) => Some(category),
+        Some(Err(err)) => err.print().exit(),
+        None => None,
+    };
+
+    let target = match options.model.as_deref() {
+        Some(model) => match Model::from_string(model) {
+            // unknown names are read as the latest model
+            Model::Latest if model != "latest" => {
+                TiToolsError::Match(model.to_string(), "Model".to_string())
+                    .print()
+                    .exit()
+            }
+            model => Some(OsVersion::new(model, "latest".to_string())),
+        },
+        None => None,
+    };
+
+    // $BB$6, 0xBB6 and BB6 are the same prefix
+    let prefix = options.bytes.as_deref().map(|bytes| {
+        bytes
+            .trim_start_matches("0x")
+            .replace('$', "")
+            .to_uppercase()
+    });
+
+    let text = options.text.as_deref().map(str::to_lowercase);
+
+    let table = match load_token_table() {
+        Ok(table) => table,
+        Err(err) => err.print().exit(),
+    };
+
+    // only the tokens search and hover cannot describe
+    let coverage = if options.undefined {
+        match load_token_definitions() {
+            Ok(definitions) => Some(check_definitions(&table, &definitions)),
+            Err(err) => err.print().exit(),
+        }
+    } else {
+        None
+    };
+
+    let mut listings = Vec::new();
+
+    for entry in &table {
+        if let Some(coverage) = &coverage {
+            if !coverage.undefined.contains(&entry.key) {
+                continue;
+            }
+        }
+
+        let shown = match &target {
+            Some(target) => match entry.version_for(target) {
+                Some(version) => version,
+                None => continue,
+            },
+            None => match entry.versions.last() {
+                Some(version) => version,
+                None => continue,
+            },
+        };
+
+        if category.is_some_and(|category| category != entry.category) {
+            continue;
+        }
+
+        if let Some(prefix) = &prefix {
+            if !hex_string(&entry.bytes).starts_with(prefix.as_str()) {
+                continue;
+            }
+        }
+
+        if let Some(text) = &text {
+            if !entry
+                .versions
+                .iter()
+                .any(|version| matches_text(version, text))
+            {
+                continue;
+            }
+        }
+
+        listings.push(listing(entry, shown));
+    }
+
+    if format.is_json() {
+        print_json(&listings);
+    } else if let Some(coverage) = &coverage {
+        print_coverage(&listings, &coverage.orphaned);
+    } else if listings.is_empty() {
+        prints!("[color:bright-red]No tokens match");
+    } else {
+        print_listings(&listings);
+    }
+
+    // lets a check for missing definitions fail a build
+    if coverage.is_some() && !listings.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+fn print_coverage(listings: &[TokenListing], orphaned: &[String]) {
+    for key in orphaned {
+        prints!(
+            "[color:bright-yellow]Warning:[color:reset] The definition of {} is not for any token",
+            key
+        );
+    }
+
+    if listings.is_empty() {
+        prints!("[color:bright-green]Every token has a definition");
+        return;
+    }
+
+    print_listings(listings);
+    prints!(
+        "[color:bright-red]{} tokens have no definition",
+        listings.len()
+    );
+}
+
+fn print_listings(listings: &[TokenListing]) {
+    for listing in listings {
+        let available = listing
+            .versions
+            .iter()
+            .map(|version| match &version.until {
+                Some(until) => format!("{} to {}", version.since, until),
+                None => format!("{} on", version.since),
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        prints!(
+            "[color:bright-cyan]{:<8}[color:reset] {:<18} [color:bright-green]{:<20}[color:reset] {:<20} [color:bright-black]{} | {}",
+            listing.key,
+            listing.category.to_string(),
+            visible(listing.display),
+            visible(listing.accessible),
+            listing.ti_ascii,
+            available
+        );
+    }
+}
+
+fn listing<'a>(entry: &'a TokenEntry, shown: &'a Token) -> TokenListing<'a> {
+    let english = |token: &'a Token| token.langs.get("en");
+
+    TokenListing {
+        key: &entry.key,
+        bytes: hex_string(&entry.bytes),
+        category: entry.category,
+        display: english(shown).map_or("", |translation| &translation.display),
+        accessible: english(shown).map_or("", |translation| &translation.accessible),
+        ti_ascii: english(shown).map_or("", |translation| &translation.ti_ascii),
+        versions: entry
+            .versions
+            .iter()
+            .map(|version| VersionListing {
+                since: format!("{} {}", version.since.model, version.since.version),
+                until: version
+                    .until
+                    .as_ref()
+                    .map(|until| format!("{} {}", until.model, until.version)),
+                display: english(version).map_or("", |translation| &translation.display),
+                accessible: english(version).map_or("", |translation| &translation.accessible),
+                ti_ascii: english(version).map_or("", |translation| &translation.ti_ascii),
+            })
+            .collect(),
+    }
+}
+
+fn matches_text(version: &Token, text: &str) -> bool {
+    version.langs.values().any(|translation| {
+        translation.display.to_lowercase().contains(text)
+            || translation.accessible.to_lowercase().contains(text)
+    })
+}
+
+/// Newlines and spaces would not show up in the list
+fn visible(text: &str) -> String {
+    text.replace('\n', "\\n").replace(' ', "␣")
+}
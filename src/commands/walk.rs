@@ -0,0 +1,145 @@
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::program::get_file_type;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Which files under a directory should be picked up
+pub struct FileFilter {
+    pub recursive: bool,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl FileFilter {
+    /// Split a comma separated list of glob patterns
+    pub fn parse_patterns(patterns: Option<String>) -> Vec<String> {
+        match patterns {
+            Some(patterns) => patterns
+                .split(',')
+                .map(|pattern| pattern.trim().to_string())
+                .filter(|pattern| !pattern.is_empty())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn accepts(&self, relative_path: &str) -> bool {
+        let included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| path_matches(pattern, relative_path));
+
+        let excluded = self
+            .exclude
+            .iter()
+            .any(|pattern| path_matches(pattern, relative_path));
+
+        included && !excluded
+    }
+}
+
+/// Collects every program file (8xp, 83p, 82p, txt, json) under `root`, sorted by path.
+/// Symlinked directories are followed, but a directory is only read once so links back up the
+/// tree can't loop
+pub fn collect_program_files(
+    root: &Path,
+    filter: &FileFilter,
+) -> Result<Vec<PathBuf>, TiToolsError> {
+    let mut files = Vec::new();
+    let mut directories = vec![root.to_path_buf()];
+    let mut visited = HashSet::new();
+
+    while let Some(directory) = directories.pop() {
+        let canonical = fs::canonicalize(&directory).unwrap_or_else(|_| directory.to_path_buf());
+
+        if !visited.insert(canonical) {
+            continue;
+        }
+
+        let entries = match fs::read_dir(&directory) {
+            Ok(entries) => entries,
+            Err(err) => return Err(TiToolsError::FailedToReadDirectory(err.to_string())),
+        };
+
+        for entry in entries {
+            let path = match entry {
+                Ok(entry) => entry.path(),
+                Err(err) => return Err(TiToolsError::FailedToReadDirectory(err.to_string())),
+            };
+
+            if path.is_dir() {
+                if filter.recursive {
+                    directories.push(path);
+                }
+                continue;
+            }
+
+            if get_file_type(&path).is_err() {
+                continue;
+            }
+
+            if filter.accepts(&relative_path_string(root, &path)) {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// The path of `path` inside `root` using `/` as the separator
+pub fn relative_path_string(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Patterns without a `/` only match the file name, like a .gitignore
+fn path_matches(pattern: &str, relative_path: &str) -> bool {
+    if pattern.contains('/') {
+        glob_matches(pattern, relative_path)
+    } else {
+        let file_name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+        glob_matches(pattern, file_name)
+    }
+}
+
+/// Supports `*` (anything but `/`), `**` (anything, including `/`) and `?` (one character)
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    match_chars(&pattern, &text)
+}
+
+fn match_chars(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            // "**/" may also match no directories at all
+            let (rest, whole_directories) = match pattern.get(2) {
+                Some('/') => (&pattern[3..], true),
+                _ => (&pattern[2..], false),
+            };
+
+            (0..=text.len())
+                .filter(|&i| !whole_directories || i == 0 || text[i - 1] == '/')
+                .any(|i| match_chars(rest, &text[i..]))
+        }
+        Some('*') => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != '/')
+            .any(|i| match_chars(&pattern[1..], &text[i..])),
+        Some('?') => !text.is_empty() && text[0] != '/' && match_chars(&pattern[1..], &text[1..]),
+        Some(character) => {
+            text.first() == Some(character) && match_chars(&pattern[1..], &text[1..])
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use crate::calculator::errors::TiToolsError;
+use crate::calculator::program::{OverwritePolicy, Program};
+use crate::calculator::EncodeMode;
+use crate::commands::output::OutputFormat;
+use crate::commands::policy::WritePolicy;
+use crate::commands::walk::{collect_program_files, FileFilter};
+use crate::prints;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+pub struct WatchOptions {
+    pub output_path_string: String,
+    pub encode_mode: String,
+    pub recursive: bool,
+    pub interval: String,
+}
+
+#[derive(Serialize)]
+struct RebuildReport {
+    input: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    duration_ms: f64,
+}
+
+pub fn watch_command(
+    input_path_string: String,
+    options: WatchOptions,
+    policy: &WritePolicy,
+    format: OutputFormat,
+) {
+    let input_path = Path::new(&input_path_string);
+    let output_path = Path::new(&options.output_path_string);
+
+    if !input_path.is_dir() {
+        prints!("[color:bright-red]Error:[color:reset] The input path must lead to a directory");
+        std::process::exit(1);
+    }
+
+    let encode_mode = match EncodeMode::from_string(&options.encode_mode) {
+        Ok(encode_mode) => encode_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let interval = match options.interval.parse::<u64>() {
+        Ok(interval) if interval > 0 => Duration::from_millis(interval),
+        _ => {
+            prints!("[color:bright-red]Error:[color:reset] The interval must be a whole number of milliseconds greater than 0");
+            std::process::exit(1);
+        }
+    };
+
+    if !output_path.exists() {
+        if let Err(err) = fs::create_dir_all(output_path) {
+            TiToolsError::FailedToWriteFile(output_path.display().to_string(), err.to_string())
+                .print()
+                .exit()
+        }
+    }
+
+    let filter = FileFilter {
+        recursive: options.recursive,
+        include: vec!["*.txt".to_string()],
+        exclude: Vec::new(),
+    };
+
+    if !format.is_json() {
+        prints!(
+            "Watching [color:bright-cyan]{}[color:reset] for changes, press Ctrl+C to stop",
+            input_path.display()
+        );
+    }
+
+    // Every file counts as changed on the first pass, so the output starts up to date
+    let mut modified_times: BTreeMap<PathBuf, SystemTime> = BTreeMap::new();
+    let mut written = HashSet::new();
+
+    loop {
+        let files = match collect_program_files(input_path, &filter) {
+            Ok(files) => files,
+            Err(err) => {
+                err.print();
+                Vec::new()
+            }
+        };
+
+        modified_times.retain(|path, _| files.contains(path));
+
+        for path in files {
+            let modified = match fs::metadata(&path).and_then(|metadata| metadata.modified()) {
+                Ok(modified) => modified,
+                Err(_) => continue,
+            };
+
+            if modified_times.get(&path) == Some(&modified) {
+                continue;
+            }
+
+            modified_times.insert(path.clone(), modified);
+
+            let report = rebuild(
+                &path,
+                input_path,
+                output_path,
+                &encode_mode,
+                &policy.overwrite,
+                &mut written,
+            );
+            print_report(&report, format);
+        }
+
+        std::thread::sleep(interval);
+    }
+}
+
+/// Encodes one txt file into the output directory, mirroring its location and named after the program.
+/// The overwrite policy only applies to files this watch hasn't written yet, later rebuilds replace
+/// its own output
+fn rebuild(
+    path: &Path,
+    input_root: &Path,
+    output_root: &Path,
+    encode_mode: &EncodeMode,
+    overwrite: &OverwritePolicy,
+    written: &mut HashSet<PathBuf>,
+) -> RebuildReport {
+    let started = Instant::now();
+
+    let mut report = RebuildReport {
+        input: path.display().to_string(),
+        output: None,
+        error: None,
+        duration_ms: 0.0,
+    };
+
+    let result = Program::load_from_txt(path.to_path_buf(), encode_mode).and_then(|program| {
+        let relative_directory = path
+            .parent()
+            .and_then(|parent| parent.strip_prefix(input_root).ok())
+            .unwrap_or(Path::new(""));
+
+        let output_directory = output_root.join(relative_directory);
+
+        fs::create_dir_all(&output_directory).map_err(|err| {
+            TiToolsError::FailedToWriteFile(output_directory.display().to_string(), err.to_string())
+        })?;
+
+        let output_path = output_directory
+            .join(&program.metadata.name)
+            .with_extension("8xp");
+
+        let overwrite = if written.contains(&output_path) {
+            &OverwritePolicy::Force
+        } else {
+            overwrite
+        };
+
+        program.save_to(&output_path, overwrite)?;
+        written.insert(output_path.clone());
+
+        Ok(output_path)
+    });
+
+    match result {
+        Ok(output_path) => report.output = Some(output_path.display().to_string()),
+        Err(err) => report.error = Some(err.to_string()),
+    }
+
+    report.duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    report
+}
+
+/// Reports are printed as they happen, so json output is one document per line
+fn print_report(report: &RebuildReport, format: OutputFormat) {
+    if format.is_json() {
+        match serde_json::to_string(report) {
+            Ok(json) => println!("{}", json),
+            Err(err) => {
+                TiToolsError::FailedToSerializeJson(err.to_string()).print();
+            }
+        }
+
+        return;
+    }
+
+    match (&report.output, &report.error) {
+        (Some(output), _) => prints!(
+            "[color:bright-green]Rebuilt[color:reset] [color:bright-cyan]{}[color:reset] to [color:bright-cyan]{}[color:reset] ({:.2}ms)",
+            report.input,
+            output,
+            report.duration_ms
+        ),
+        (None, Some(error)) => prints!(
+            "[color:bright-red]Failed to encode[color:reset] [color:bright-cyan]{}[color:reset]: {}",
+            report.input,
+            error
+        ),
+        (None, None) => {}
+    }
+}
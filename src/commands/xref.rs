@@ -0,0 +1,120 @@
+use crate::calculator::program::{
+    detect_file_type, get_file_type, Program, ProgramFileType, TXT_HEADER_LINES,
+};
+use crate::calculator::xref::{xref_program, Xref};
+use crate::calculator::{DisplayMode, EncodeMode};
+use crate::commands::output::{print_json, OutputFormat};
+use crate::prints;
+use std::path::Path;
+
+pub struct XrefOptions {
+    pub display_mode: String,
+    pub encode_mode: String,
+}
+
+pub fn xref_command(input_path_string: String, options: XrefOptions, format: OutputFormat) {
+    let input_path = Path::new(&input_path_string);
+
+    let display_mode = match DisplayMode::from_string(&options.display_mode) {
+        Ok(display_mode) => display_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let encode_mode = match EncodeMode::from_string(&options.encode_mode) {
+        Ok(encode_mode) => encode_mode,
+        Err(err) => err.print().exit(),
+    };
+
+    let program = match detect_file_type(input_path).and_then(|file_type| {
+        Program::load_from_file(
+            input_path.to_path_buf(),
+            file_type,
+            display_mode.clone(),
+            &encode_mode,
+        )
+    }) {
+        Ok(program) => program,
+        Err(err) => err.print().exit(),
+    };
+
+    let mut xref = match xref_program(&program, &display_mode) {
+        Ok(xref) => xref,
+        Err(err) => err.print().exit(),
+    };
+
+    // Lines in txt files are counted from the top of the file
+    if let Ok(ProgramFileType::TXT) = get_file_type(input_path) {
+        xref.shift_lines(TXT_HEADER_LINES);
+    }
+
+    if format.is_json() {
+        print_json(&xref);
+    } else {
+        print_xref(&xref);
+    }
+}
+
+fn print_xref(xref: &Xref) {
+    prints!("[color:bright-green]Labels");
+
+    for label in &xref.labels {
+        let defined = if label.defined.is_empty() {
+            "never defined".to_string()
+        } else {
+            format!("defined on {}", join_lines(&label.defined))
+        };
+
+        let references = label
+            .references
+            .iter()
+            .map(|reference| format!("{} {}", reference.command, reference.line))
+            .collect::<Vec<_>>();
+
+        let used = if references.is_empty() {
+            "never used".to_string()
+        } else {
+            format!("used by {}", references.join(", "))
+        };
+
+        prints!(
+            "    [color:bright-cyan]{:<6}[color:reset] {}, {}",
+            label.name,
+            defined,
+            used
+        );
+    }
+
+    prints!("\n[color:bright-green]Program calls");
+
+    for call in &xref.calls {
+        prints!(
+            "    [color:bright-cyan]{:<10}[color:reset] on {}",
+            call.name,
+            join_lines(&call.lines)
+        );
+    }
+
+    prints!("\n[color:bright-green]Variables");
+
+    for variable in &xref.variables {
+        prints!(
+            "    [color:bright-cyan]{:<8}[color:reset] {:<7} read on {}, written on {}",
+            variable.name,
+            variable.kind,
+            join_lines(&variable.reads),
+            join_lines(&variable.writes)
+        );
+    }
+}
+
+fn join_lines(lines: &[usize]) -> String {
+    if lines.is_empty() {
+        return "none".to_string();
+    }
+
+    lines
+        .iter()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
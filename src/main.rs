@@ -4,19 +4,41 @@ pub mod commands;
 pub mod styles;
 
 use cli::{Arg, Cli, CmdOption, Command};
-use commands::convert::convert_command;
+use commands::callgraph::{callgraph_command, CallGraphOptions};
+use commands::convert::{convert_command, ConvertOptions};
 use commands::details::details_command;
+use commands::diff::{diff_command, DiffOptions};
+use commands::disasm::{disasm_command, DisasmOptions};
+use commands::docs::{docs_command, DocsOptions};
 use commands::edit::archive::archive_command;
 use commands::edit::comment::comment_command;
+use commands::edit::description::description_command;
+use commands::edit::icon::icon_command;
 use commands::edit::lock::lock_command;
 use commands::edit::rename::rename_command;
 use commands::edit::unarchive::unarchive_command;
 use commands::edit::unlock::unlock_command;
+use commands::format::{fmt_command, FmtOptions};
+use commands::lint::{lint_command, LintOptions};
+use commands::lsp::{lsp_command, LspOptions};
+use commands::merge::{merge_command, MergeOptions};
 use commands::models::models_command;
-use commands::search::search_command;
+use commands::optimize::{optimize_command, OptimizeOptions};
+use commands::output::OutputFormat;
+use commands::policy::WritePolicy;
+use commands::render::{render_command, RenderOptions};
+use commands::run::{run_command, RunCommandOptions};
+use commands::search::{search_command, SearchOptions};
+use commands::test::{test_command, TestOptions};
+use commands::textconv::{textconv_command, TextconvOptions};
+use commands::tokens::{tokens_command, TokensOptions};
+use commands::walk::FileFilter;
+use commands::watch::{watch_command, WatchOptions};
+use commands::xref::{xref_command, XrefOptions};
 
 fn main() {
     let cli = Cli::new()
+        .with_global_arg(OutputFormat::arg())
         .with_command(
             Command::new("help", "Prints help information")
                 .with_option(
@@ -28,12 +50,12 @@ fn main() {
             Command::new("version", "Prints version information")
         )
         .with_command(
-            Command::new("convert", "Converts between 8xp/83p/82p, json, and txt")
+            Command::new("convert", "Converts between 8xp/83p/82p, json, and txt, and assembly programs to and from bin/hex")
                 .with_option(
-                    CmdOption::new("input", "INPUT", "The input path to an 8xp, 83p, 82p, json or txt file")
+                    CmdOption::new("input", "INPUT", "The input path to an 8xp, 83p, 82p, json, txt, bin or hex file")
                 )
                 .with_arg(
-                    Arg::new("output", "The output path to an 8xp, 83p, json, or txt file", "output", 'o')
+                    Arg::new("output", "The output path to an 8xp, 83p, json, txt, bin or hex file", "output", 'o')
                         .with_value_name("OUTPUT"),
                 )
                 .with_arg(
@@ -55,6 +77,320 @@ fn main() {
                 .with_arg(
                     Arg::new("mass", "Changes input required from file to directory for mass file decoding", "mass", 'm')
                 )
+                .with_arg(
+                    Arg::new("recursive", "Also convert files in subdirectories, mirroring them in the output directory", "recursive", 'r')
+                        .requires("mass")
+                )
+                .with_arg(
+                    Arg::new("include", "Only convert files matching these comma separated globs (used with --mass)", "include", 'i')
+                        .with_value_name("GLOBS")
+                )
+                .with_arg(
+                    Arg::new("exclude", "Skip files matching these comma separated globs (used with --mass)", "exclude", 'x')
+                        .with_value_name("GLOBS")
+                )
+                .with_arg(
+                    Arg::new("to", "The format every file is converted to [txt, json, 8xp, 83p, 82p] (used with --mass) | Default: 8xp for txt files, txt for the rest", "to", 't')
+                        .with_value_name("FORMAT")
+                        .requires("mass")
+                )
+                .with_arg(
+                    Arg::new("jobs", "The number of files converted at once (used with --mass) | Default: number of CPUs", "jobs", 'j')
+                        .with_value_name("JOBS")
+                        .requires("mass")
+                )
+                .with_arg(
+                    Arg::new("model", "The model a bin or hex file is built for, which picks the assembly header | Default: latest", "model", 'M')
+                        .with_value_name("MODEL")
+                        .with_default_value("latest")
+                )
+                .with_args(WritePolicy::args())
+        )
+        .with_command(
+            Command::new("watch", "Re-encodes txt files into 8xp files whenever they change")
+                .with_option(
+                    CmdOption::new("input", "INPUT", "The input path to a directory of txt files")
+                )
+                .with_arg(
+                    Arg::new("output", "The output path to a directory for the 8xp files", "output", 'o')
+                        .with_value_name("OUTPUT"),
+                )
+                .with_arg(
+                    Arg::new("encode-mode", "The mode used to parse tokens [min, max, smart] | Default: smart", "encode-mode", 'e')
+                        .with_default_value("smart")
+                        .with_value_name("ENCODE_MODE")
+                )
+                .with_arg(
+                    Arg::new("recursive", "Also watch files in subdirectories, mirroring them in the output directory", "recursive", 'r')
+                )
+                .with_arg(
+                    Arg::new("interval", "How often to check for changes in milliseconds | Default: 500", "interval", 'w')
+                        .with_default_value("500")
+                        .with_value_name("MILLISECONDS")
+                )
+                .with_args(WritePolicy::args())
+        )
+        .with_command(
+            Command::new("lint", "Checks programs for common TI-BASIC mistakes")
+                .with_option(
+                    CmdOption::new("input", "INPUT", "The input path to an 8xp, 83p, 82p, json or txt file, or a directory of them")
+                )
+                .with_arg(
+                    Arg::new("encode-mode", "The mode used to parse tokens [min, max, smart] | Default: smart", "encode-mode", 'e')
+                        .with_default_value("smart")
+                        .with_value_name("ENCODE_MODE")
+                )
+                .with_arg(
+                    Arg::new("recursive", "Also lint files in subdirectories", "recursive", 'r')
+                )
+                .with_arg(
+                    Arg::new("subprogram", "Lint every program as a subprogram called by another program", "subprogram", 's')
+                )
+        )
+        .with_command(
+            Command::new("xref", "Lists where the labels, program calls and variables of a program are used")
+                .with_option(
+                    CmdOption::new("input", "INPUT", "The input path to an 8xp, 83p, 82p, json or txt file")
+                )
+                .with_arg(
+                    Arg::new("display-mode", "The characters to spell the names with [pretty, accessible, ti] | Default: accessible", "display-mode", 'd')
+                        .with_default_value("accessible")
+                        .with_value_name("DISPLAY_MODE")
+                )
+                .with_arg(
+                    Arg::new("encode-mode", "The mode used to parse tokens [min, max, smart] | Default: smart", "encode-mode", 'e')
+                        .with_default_value("smart")
+                        .with_value_name("ENCODE_MODE")
+                )
+        )
+        .with_command(
+            Command::new("diff", "Compares two programs line by line and token by token, along with their details")
+                .with_option(
+                    CmdOption::new("old", "OLD", "The path to the old 8xp, 83p, 82p, json or txt file")
+                )
+                .with_option(
+                    CmdOption::new("new", "NEW", "The path to the new 8xp, 83p, 82p, json or txt file")
+                )
+                .with_arg(
+                    Arg::new("display-mode", "The characters to translate the tokens to [pretty, accessible, ti] | Default: accessible", "display-mode", 'd')
+                        .with_default_value("accessible")
+                        .with_value_name("DISPLAY_MODE")
+                )
+                .with_arg(
+                    Arg::new("encode-mode", "The mode used to parse tokens [min, max, smart] | Default: smart", "encode-mode", 'e')
+                        .with_default_value("smart")
+                        .with_value_name("ENCODE_MODE")
+                )
+                .with_arg(
+                    Arg::new("context", "Unchanged lines to show around each change | Default: 3", "context", 'c')
+                        .with_default_value("3")
+                        .with_value_name("LINES")
+                )
+        )
+        .with_command(
+            Command::new("textconv", "Prints a program as txt for git diff, set it as the textconv of a diff driver")
+                .with_option(
+                    CmdOption::new("input", "INPUT", "The input path to an 8xp, 83p, 82p, json or txt file")
+                )
+                .with_arg(
+                    Arg::new("display-mode", "The characters to translate the tokens to [pretty, accessible, ti] | Default: accessible", "display-mode", 'd')
+                        .with_default_value("accessible")
+                        .with_value_name("DISPLAY_MODE")
+                )
+                .with_arg(
+                    Arg::new("encode-mode", "The mode used to parse tokens [min, max, smart] | Default: smart", "encode-mode", 'e')
+                        .with_default_value("smart")
+                        .with_value_name("ENCODE_MODE")
+                )
+        )
+        .with_command(
+            Command::new("merge", "Merges the changes from base to theirs into ours, usable as a git merge driver")
+                .with_option(
+                    CmdOption::new("base", "BASE", "The path to the common ancestor, git's %O")
+                )
+                .with_option(
+                    CmdOption::new("ours", "OURS", "The path to our version, git's %A")
+                )
+                .with_option(
+                    CmdOption::new("theirs", "THEIRS", "The path to their version, git's %B")
+                )
+                .with_arg(
+                    Arg::new("output", "The output path for the merged program | Default: OURS", "output", 'o')
+                        .with_value_name("OUTPUT")
+                )
+                .with_arg(
+                    Arg::new("conflicts", "The txt file to write conflict markers to when the output is not txt, none are written without it", "conflicts", 'c')
+                        .with_value_name("FILE")
+                )
+                .with_arg(
+                    Arg::new("display-mode", "The characters to translate the tokens to [pretty, accessible, ti] | Default: accessible", "display-mode", 'd')
+                        .with_default_value("accessible")
+                        .with_value_name("DISPLAY_MODE")
+                )
+                .with_arg(
+                    Arg::new("encode-mode", "The mode used to parse tokens [min, max, smart] | Default: smart", "encode-mode", 'e')
+                        .with_default_value("smart")
+                        .with_value_name("ENCODE_MODE")
+                )
+                .with_args(WritePolicy::args())
+        )
+        .with_command(
+            Command::new("render", "Renders a program with syntax highlighting for the terminal or as an HTML page")
+                .with_option(
+                    CmdOption::new("input", "INPUT", "The input path to an 8xp, 83p, 82p, json or txt file")
+                )
+                .with_arg(
+                    Arg::new("output", "The file to write the rendered program to instead of printing it", "output", 'o')
+                        .with_value_name("OUTPUT")
+                )
+                .with_arg(
+                    Arg::new("to", "The format to render to [ansi, html] | Default: html with --output, ansi otherwise", "to", 't')
+                        .with_value_name("FORMAT")
+                )
+                .with_arg(
+                    Arg::new("display-mode", "The characters to translate the tokens to [pretty, accessible, ti] | Default: pretty", "display-mode", 'd')
+                        .with_default_value("pretty")
+                        .with_value_name("DISPLAY_MODE")
+                )
+                .with_arg(
+                    Arg::new("encode-mode", "The mode used to parse tokens [min, max, smart] | Default: smart", "encode-mode", 'e')
+                        .with_default_value("smart")
+                        .with_value_name("ENCODE_MODE")
+                )
+                .with_args(WritePolicy::args())
+        )
+        .with_command(
+            Command::new("lsp", "Starts a language server for txt files over stdin and stdout, for editors")
+                .with_arg(
+                    Arg::new("display-mode", "The characters to complete tokens with when a file has no valid display mode [pretty, accessible, ti] | Default: accessible", "display-mode", 'd')
+                        .with_default_value("accessible")
+                        .with_value_name("DISPLAY_MODE")
+                )
+                .with_arg(
+                    Arg::new("encode-mode", "The mode used to parse tokens [min, max, smart] | Default: smart", "encode-mode", 'e')
+                        .with_default_value("smart")
+                        .with_value_name("ENCODE_MODE")
+                )
+        )
+        .with_command(
+            Command::new("callgraph", "Shows how the programs in a directory call each other")
+                .with_option(
+                    CmdOption::new("input", "INPUT", "The input path to a directory of 8xp, 83p, 82p, json or txt files")
+                )
+                .with_arg(
+                    Arg::new("encode-mode", "The mode used to parse tokens [min, max, smart] | Default: smart", "encode-mode", 'e')
+                        .with_default_value("smart")
+                        .with_value_name("ENCODE_MODE")
+                )
+                .with_arg(
+                    Arg::new("recursive", "Also include programs in subdirectories", "recursive", 'r')
+                )
+                .with_arg(
+                    Arg::new("dot", "Print the graph in Graphviz DOT instead of the output format", "dot", 'g')
+                )
+        )
+        .with_command(
+            Command::new("run", "Runs a program on the computer with scripted input")
+                .with_option(
+                    CmdOption::new("input", "INPUT", "The input path to an 8xp, 83p, 82p, json or txt file")
+                )
+                .with_arg(
+                    Arg::new("inputs", "A file with one answer per line for Input and Prompt", "inputs", 'i')
+                        .with_value_name("FILE")
+                )
+                .with_arg(
+                    Arg::new("keys", "Comma separated key codes returned by getKey, which returns 0 once they run out", "keys", 'k')
+                        .with_value_name("KEYS")
+                )
+                .with_arg(
+                    Arg::new("max-steps", "Statements to run before stopping a program that never ends | Default: 100000", "max-steps", 's')
+                        .with_default_value("100000")
+                        .with_value_name("STEPS")
+                )
+                .with_arg(
+                    Arg::new("encode-mode", "The mode used to parse tokens [min, max, smart] | Default: smart", "encode-mode", 'e')
+                        .with_default_value("smart")
+                        .with_value_name("ENCODE_MODE")
+                )
+                .with_arg(
+                    Arg::new("home-screen", "Also display the home screen once the program stops", "home-screen", 'H')
+                )
+        )
+        .with_command(
+            Command::new("test", "Runs the test cases in .test.json files against the programs they name")
+                .with_option(
+                    CmdOption::new("input", "INPUT", "The input path to a test file or a directory of .test.json files")
+                )
+                .with_arg(
+                    Arg::new("encode-mode", "The mode used to parse tokens [min, max, smart] | Default: smart", "encode-mode", 'e')
+                        .with_default_value("smart")
+                        .with_value_name("ENCODE_MODE")
+                )
+                .with_arg(
+                    Arg::new("recursive", "Also look for test files in subdirectories", "recursive", 'r')
+                )
+                .with_arg(
+                    Arg::new("junit", "Also write the results as a JUnit report for CI", "junit", 'j')
+                        .with_value_name("FILE")
+                )
+                .with_args(WritePolicy::args())
+        )
+        .with_command(
+            Command::new("optimize", "Shrinks a program with byte saving transforms that keep it working the same")
+                .with_option(
+                    CmdOption::new("input", "INPUT", "The input path to an 8xp, 83p, 82p, json or txt file")
+                )
+                .with_arg(
+                    Arg::new("output", "The output path to an 8xp, 83p, 82p, json, or txt file", "output", 'o')
+                        .with_value_name("OUTPUT"),
+                )
+                .with_arg(
+                    Arg::new("transforms", "The transforms to apply, all or a list of [parens, braces, quotes, zero-compare] | Default: all", "transforms", 't')
+                        .with_default_value("all")
+                        .with_value_name("TRANSFORMS")
+                )
+                .with_arg(
+                    Arg::new("display-mode", "The characters to translate the tokens to [pretty, accessible, ti] | Default: accessible", "display-mode", 'd')
+                        .with_default_value("accessible")
+                        .with_value_name("DISPLAY_MODE")
+                )
+                .with_arg(
+                    Arg::new("encode-mode", "The mode used to parse tokens [min, max, smart] | Default: smart", "encode-mode", 'e')
+                        .with_default_value("smart")
+                        .with_value_name("ENCODE_MODE")
+                )
+                .with_arg(
+                    Arg::new("preview", "Display the optimized program", "preview", 'p')
+                )
+                .with_args(WritePolicy::args())
+        )
+        .with_command(
+            Command::new("fmt", "Formats txt programs with consistent token spelling and optional indentation")
+                .with_option(
+                    CmdOption::new("input", "INPUT", "The input path to a txt file or a directory of them")
+                )
+                .with_arg(
+                    Arg::new("display-mode", "The characters to spell the tokens with [pretty, accessible, ti] | Default: accessible", "display-mode", 'd')
+                        .with_default_value("accessible")
+                        .with_value_name("DISPLAY_MODE")
+                )
+                .with_arg(
+                    Arg::new("encode-mode", "The mode used to parse tokens [min, max, smart] | Default: smart", "encode-mode", 'e')
+                        .with_default_value("smart")
+                        .with_value_name("ENCODE_MODE")
+                )
+                .with_arg(
+                    Arg::new("indent", "Indent blocks with tabs, which are ignored when encoding", "indent", 'i')
+                )
+                .with_arg(
+                    Arg::new("check", "Only report files that are not formatted, without changing them", "check", 'c')
+                )
+                .with_arg(
+                    Arg::new("preview", "Display the formatted program instead of saving it", "preview", 'p')
+                )
+                .with_arg(
+                    Arg::new("recursive", "Also format files in subdirectories", "recursive", 'r')
+                )
         )
         .with_command(
             Command::new("search", "Retrieves a description for a token")
@@ -66,6 +402,65 @@ fn main() {
                     .with_value_name("TYPE")
                     .with_default_value("accessible")
             )
+            .with_arg(
+                Arg::new("fuzzy", "Ranks tokens by how closely any spelling or definition matches, allowing typos", "fuzzy", 'f')
+            )
+            .with_arg(
+                Arg::new("limit", "The most tokens to list with --fuzzy | Default: 10", "limit", 'l')
+                    .with_value_name("LIMIT")
+                    .with_default_value("10")
+            )
+        )
+        .with_command(
+            Command::new("tokens", "Lists tokens with their bytes and every spelling of them")
+            .with_arg(
+                Arg::new("category", "Only list tokens in a category [control-flow, io, math, probability, lists, matrices, statistics, finance, draw, graph, strings, variables, system-variables, settings, operators, numbers, syntax, memory, clock, assembly, characters, editor]", "category", 'c')
+                    .with_value_name("CATEGORY")
+            )
+            .with_arg(
+                Arg::new("model", "Only list tokens the model has, spelled like on that model", "model", 'm')
+                    .with_value_name("MODEL")
+            )
+            .with_arg(
+                Arg::new("bytes", "Only list tokens starting with these bytes, like $BB or 0xBB6", "bytes", 'b')
+                    .with_value_name("BYTES")
+            )
+            .with_arg(
+                Arg::new("text", "Only list tokens spelled with this text in any display mode", "text", 't')
+                    .with_value_name("TEXT")
+            )
+            .with_arg(
+                Arg::new("undefined", "Only list tokens without a definition, failing when there are any", "undefined", 'u')
+            )
+        )
+        .with_command(
+            Command::new("docs", "Writes a reference of every token from the token data built into ti-tools")
+            .with_arg(
+                Arg::new("output", "The file to write the reference to instead of printing it", "output", 'o')
+                    .with_value_name("OUTPUT")
+            )
+            .with_arg(
+                Arg::new("to", "The format of the reference [markdown, html] | Default: html for .html outputs, markdown otherwise", "to", 't')
+                    .with_value_name("FORMAT")
+            )
+            .with_arg(
+                Arg::new("check", "Fails when the output is not what the token data would write, without writing it", "check", 'c')
+            )
+            .with_args(WritePolicy::args())
+        )
+        .with_command(
+            Command::new("disasm", "Disassembles the machine code of an assembly program, naming the OS routines it calls")
+            .with_option(
+                CmdOption::new("input", "INPUT", "The input path to an 8xp/83p/json/txt assembly program")
+            )
+            .with_arg(
+                Arg::new("cpu", "The cpu the code is for [z80, ez80] | Default: from the model of the program", "cpu", 'c')
+                    .with_value_name("CPU")
+            )
+            .with_arg(
+                Arg::new("origin", "The hex address the code starts at | Default: userMem, 9D95, A60B on the TI-84+CSE or D1A881", "origin", 'a')
+                    .with_value_name("ADDRESS")
+            )
         )
         .with_command(
             Command::new("rename", "Renames the program name in a 8xp/82p/83p file")
@@ -84,6 +479,7 @@ fn main() {
                 Arg::new("delete-old", "Delete the old file", "delete-old", 'd')
                     .requires("new-file")
             )
+            .with_args(WritePolicy::args())
         )
         .with_command(
             Command::new("comment", "Write a custom comment to an 8xp/82p/83p file")
@@ -102,6 +498,45 @@ fn main() {
                 Arg::new("delete-old", "Delete the old file", "delete-old", 'd')
                     .requires("new-file")
             )
+            .with_args(WritePolicy::args())
+        )
+        .with_command(
+            Command::new("description", "Write a new shell description to an 8xp/83p file with an Ion, MirageOS, DoorsCS or Cesium header")
+            .with_option(
+                CmdOption::new("input", "INPUT", "The input path to an 8xp/83p file")
+            )
+            .with_arg(
+                Arg::new("description", "The new description, no longer than the old one had room for", "description", 't')
+                    .with_value_name("DESCRIPTION")
+            )
+            .with_arg(
+                Arg::new("new-file", "Save the program with the updated description to a new file", "new-file", 'f')
+                    .with_value_name("NEW_FILE")
+            )
+            .with_arg(
+                Arg::new("delete-old", "Delete the old file", "delete-old", 'd')
+                    .requires("new-file")
+            )
+            .with_args(WritePolicy::args())
+        )
+        .with_command(
+            Command::new("icon", "Draw a new shell icon in an 8xp/83p file with a MirageOS, DoorsCS or Cesium header")
+            .with_option(
+                CmdOption::new("input", "INPUT", "The input path to an 8xp/83p file")
+            )
+            .with_arg(
+                Arg::new("icon", "A text file of the icon drawn like details prints it, # and . or two hex digits per pixel", "icon", 'i')
+                    .with_value_name("ICON")
+            )
+            .with_arg(
+                Arg::new("new-file", "Save the program with the updated icon to a new file", "new-file", 'f')
+                    .with_value_name("NEW_FILE")
+            )
+            .with_arg(
+                Arg::new("delete-old", "Delete the old file", "delete-old", 'd')
+                    .requires("new-file")
+            )
+            .with_args(WritePolicy::args())
         )
         .with_command(
             Command::new("lock", "Lock an 8xp/82p/83p file")
@@ -116,6 +551,7 @@ fn main() {
                 Arg::new("delete-old", "Delete the old file", "delete-old", 'd')
                     .requires("new-file")
             )
+            .with_args(WritePolicy::args())
         )
         .with_command(
             Command::new("unlock", "unlock an 8xp/82p/83p file")
@@ -130,6 +566,7 @@ fn main() {
                 Arg::new("delete-old", "Delete the old file", "delete-old", 'd')
                     .requires("new-file")
             )
+            .with_args(WritePolicy::args())
         )
         .with_command(
             Command::new("archive", "Set the program to be sent to Archive")
@@ -144,6 +581,7 @@ fn main() {
                 Arg::new("delete-old", "Delete the old file", "delete-old", 'd')
                     .requires("new-file")
             )
+            .with_args(WritePolicy::args())
         )
         .with_command(
             Command::new("unarchive", "Set the program to be sent to RAM")
@@ -158,18 +596,25 @@ fn main() {
                 Arg::new("delete-old", "Delete the old file", "delete-old", 'd')
                     .requires("new-file")
             )
+            .with_args(WritePolicy::args())
         )
         .with_command(
             Command::new("details", "Displays information about an 8xp/82p/83p file")
             .with_option(
                 CmdOption::new("input", "INPUT", "The input path to an 8xp/82p/83p file")
             )
+            .with_arg(
+                Arg::new("icon-png", "Write the shell icon of the program to a PNG file", "icon-png", 'p')
+                    .with_value_name("PNG")
+            )
+            .with_args(WritePolicy::args())
         )
         .with_command(
             Command::new("models", "Prints the supported TI calculator models")
         );
 
     let command = cli.match_commands();
+    let format = OutputFormat::from_command(command);
 
     match command.name.as_str() {
         "help" => {
@@ -179,79 +624,345 @@ fn main() {
         "version" => cli.version(),
         "convert" => {
             let input_path_string = command.get_option("input").throw_if_none();
-            let output_path_string = command.get_arg("output").to_option();
-            let display_mode = command.get_arg("display-mode").throw_if_none();
-            let encode_mode = command.get_arg("encode-mode").throw_if_none();
-            let content = command.has("content");
-            let preview = command.has("preview");
-            let mass = command.has("mass");
-            
-            convert_command(
-                input_path_string,
-                output_path_string,
-                display_mode,
-                encode_mode,
-                content,
-                preview,
-                mass,
+            let options = ConvertOptions {
+                output_path_string: command.get_arg("output").to_option(),
+                display_mode: command.get_arg("display-mode").throw_if_none(),
+                encode_mode: command.get_arg("encode-mode").throw_if_none(),
+                content: command.has("content"),
+                preview: command.has("preview"),
+                mass: command.has("mass"),
+                to: command.get_arg("to").to_option(),
+                jobs: command.get_arg("jobs").to_option(),
+                filter: FileFilter {
+                    recursive: command.has("recursive"),
+                    include: FileFilter::parse_patterns(command.get_arg("include").to_option()),
+                    exclude: FileFilter::parse_patterns(command.get_arg("exclude").to_option()),
+                },
+                model: command.get_arg("model").throw_if_none(),
+            };
+            let policy = WritePolicy::from_command(command);
+
+            convert_command(input_path_string, options, &policy, format);
+        }
+        "watch" => {
+            let input_path_string = command.get_option("input").throw_if_none();
+            let options = WatchOptions {
+                output_path_string: command.get_arg("output").throw_if_none(),
+                encode_mode: command.get_arg("encode-mode").throw_if_none(),
+                recursive: command.has("recursive"),
+                interval: command.get_arg("interval").throw_if_none(),
+            };
+            let policy = WritePolicy::from_command(command);
+
+            watch_command(input_path_string, options, &policy, format);
+        }
+        "lint" => {
+            let input_path_string = command.get_option("input").throw_if_none();
+            let options = LintOptions {
+                encode_mode: command.get_arg("encode-mode").throw_if_none(),
+                recursive: command.has("recursive"),
+                subprogram: command.has("subprogram"),
+            };
+
+            lint_command(input_path_string, options, format);
+        }
+        "xref" => {
+            let input_path_string = command.get_option("input").throw_if_none();
+            let options = XrefOptions {
+                display_mode: command.get_arg("display-mode").throw_if_none(),
+                encode_mode: command.get_arg("encode-mode").throw_if_none(),
+            };
+
+            xref_command(input_path_string, options, format);
+        }
+        "callgraph" => {
+            let input_path_string = command.get_option("input").throw_if_none();
+            let options = CallGraphOptions {
+                encode_mode: command.get_arg("encode-mode").throw_if_none(),
+                recursive: command.has("recursive"),
+                dot: command.has("dot"),
+            };
+
+            callgraph_command(input_path_string, options, format);
+        }
+        "diff" => {
+            let old_path_string = command.get_option("old").throw_if_none();
+            let new_path_string = command.get_option("new").throw_if_none();
+            let options = DiffOptions {
+                display_mode: command.get_arg("display-mode").throw_if_none(),
+                encode_mode: command.get_arg("encode-mode").throw_if_none(),
+                context: command.get_arg("context").throw_if_none(),
+            };
+
+            diff_command(old_path_string, new_path_string, options, format);
+        }
+        "textconv" => {
+            let input_path_string = command.get_option("input").throw_if_none();
+            let options = TextconvOptions {
+                display_mode: command.get_arg("display-mode").throw_if_none(),
+                encode_mode: command.get_arg("encode-mode").throw_if_none(),
+            };
+
+            textconv_command(input_path_string, options);
+        }
+        "merge" => {
+            let base_path_string = command.get_option("base").throw_if_none();
+            let ours_path_string = command.get_option("ours").throw_if_none();
+            let theirs_path_string = command.get_option("theirs").throw_if_none();
+            let options = MergeOptions {
+                output_path_string: command.get_arg("output").to_option(),
+                conflicts_path_string: command.get_arg("conflicts").to_option(),
+                display_mode: command.get_arg("display-mode").throw_if_none(),
+                encode_mode: command.get_arg("encode-mode").throw_if_none(),
+            };
+            let policy = WritePolicy::from_command(command);
+
+            merge_command(
+                base_path_string,
+                ours_path_string,
+                theirs_path_string,
+                options,
+                &policy,
+                format,
             );
         }
+        "render" => {
+            let input_path_string = command.get_option("input").throw_if_none();
+            let options = RenderOptions {
+                output_path_string: command.get_arg("output").to_option(),
+                to: command.get_arg("to").to_option(),
+                display_mode: command.get_arg("display-mode").throw_if_none(),
+                encode_mode: command.get_arg("encode-mode").throw_if_none(),
+            };
+            let policy = WritePolicy::from_command(command);
+
+            render_command(input_path_string, options, &policy);
+        }
+        "lsp" => {
+            let options = LspOptions {
+                display_mode: command.get_arg("display-mode").throw_if_none(),
+                encode_mode: command.get_arg("encode-mode").throw_if_none(),
+            };
+
+            lsp_command(options);
+        }
+        "run" => {
+            let input_path_string = command.get_option("input").throw_if_none();
+            let options = RunCommandOptions {
+                input_script: command.get_arg("inputs").to_option(),
+                keys: command.get_arg("keys").to_option(),
+                max_steps: command.get_arg("max-steps").throw_if_none(),
+                encode_mode: command.get_arg("encode-mode").throw_if_none(),
+                home_screen: command.has("home-screen"),
+            };
+
+            run_command(input_path_string, options, format);
+        }
+        "test" => {
+            let input_path_string = command.get_option("input").throw_if_none();
+            let options = TestOptions {
+                encode_mode: command.get_arg("encode-mode").throw_if_none(),
+                recursive: command.has("recursive"),
+                junit: command.get_arg("junit").to_option(),
+            };
+            let policy = WritePolicy::from_command(command);
+
+            test_command(input_path_string, options, &policy, format);
+        }
+        "optimize" => {
+            let input_path_string = command.get_option("input").throw_if_none();
+            let options = OptimizeOptions {
+                output_path_string: command.get_arg("output").to_option(),
+                transforms: command.get_arg("transforms").throw_if_none(),
+                display_mode: command.get_arg("display-mode").throw_if_none(),
+                encode_mode: command.get_arg("encode-mode").throw_if_none(),
+                preview: command.has("preview"),
+            };
+            let policy = WritePolicy::from_command(command);
+
+            optimize_command(input_path_string, options, &policy, format);
+        }
+        "fmt" => {
+            let input_path_string = command.get_option("input").throw_if_none();
+            let options = FmtOptions {
+                display_mode: command.get_arg("display-mode").throw_if_none(),
+                encode_mode: command.get_arg("encode-mode").throw_if_none(),
+                indent: command.has("indent"),
+                check: command.has("check"),
+                preview: command.has("preview"),
+                recursive: command.has("recursive"),
+            };
+
+            fmt_command(input_path_string, options, format);
+        }
         "search" => {
             let token = command.get_option("token").throw_if_none();
-            let token_type = command.get_arg("type").throw_if_none();
-    
-            search_command(token, token_type)
+            let options = SearchOptions {
+                token_type: command.get_arg("type").throw_if_none(),
+                fuzzy: command.has("fuzzy"),
+                limit: command.get_arg("limit").throw_if_none(),
+            };
+
+            search_command(token, options, format)
+        }
+        "tokens" => {
+            let options = TokensOptions {
+                category: command.get_arg("category").to_option(),
+                model: command.get_arg("model").to_option(),
+                bytes: command.get_arg("bytes").to_option(),
+                text: command.get_arg("text").to_option(),
+                undefined: command.has("undefined"),
+            };
+
+            tokens_command(options, format);
+        }
+        "docs" => {
+            let options = DocsOptions {
+                output_path_string: command.get_arg("output").to_option(),
+                to: command.get_arg("to").to_option(),
+                check: command.has("check"),
+            };
+            let policy = WritePolicy::from_command(command);
+
+            docs_command(options, &policy);
+        }
+        "disasm" => {
+            let input_path_string = command.get_option("input").throw_if_none();
+            let options = DisasmOptions {
+                cpu: command.get_arg("cpu").to_option(),
+                origin: command.get_arg("origin").to_option(),
+            };
+
+            disasm_command(input_path_string, options, format);
         }
         "rename" => {
             let input_path_string = command.get_option("input").throw_if_none();
             let name = command.get_arg("name").throw_if_none();
             let new_file_path = command.get_arg("new-file").to_option();
             let delete_old = command.has("delete-old");
+            let policy = WritePolicy::from_command(command);
 
-            rename_command(input_path_string, name, new_file_path, delete_old);
+            rename_command(
+                input_path_string,
+                name,
+                new_file_path,
+                delete_old,
+                &policy,
+                format,
+            );
         }
         "comment" => {
             let input_path_string = command.get_option("input").throw_if_none();
             let comment = command.get_arg("comment").throw_if_none();
             let new_file_path = command.get_arg("new-file").to_option();
             let delete_old = command.has("delete-old");
+            let policy = WritePolicy::from_command(command);
+
+            comment_command(
+                input_path_string,
+                comment,
+                new_file_path,
+                delete_old,
+                &policy,
+                format,
+            );
+        }
+        "description" => {
+            let input_path_string = command.get_option("input").throw_if_none();
+            let description = command.get_arg("description").throw_if_none();
+            let new_file_path = command.get_arg("new-file").to_option();
+            let delete_old = command.has("delete-old");
+            let policy = WritePolicy::from_command(command);
+
+            description_command(
+                input_path_string,
+                description,
+                new_file_path,
+                delete_old,
+                &policy,
+                format,
+            );
+        }
+        "icon" => {
+            let input_path_string = command.get_option("input").throw_if_none();
+            let icon_path_string = command.get_arg("icon").throw_if_none();
+            let new_file_path = command.get_arg("new-file").to_option();
+            let delete_old = command.has("delete-old");
+            let policy = WritePolicy::from_command(command);
 
-            comment_command(input_path_string, comment, new_file_path, delete_old);
+            icon_command(
+                input_path_string,
+                icon_path_string,
+                new_file_path,
+                delete_old,
+                &policy,
+                format,
+            );
         }
         "lock" => {
             let input_path_string = command.get_option("input").throw_if_none();
             let new_file_path = command.get_arg("new-file").to_option();
             let delete_old = command.has("delete-old");
+            let policy = WritePolicy::from_command(command);
 
-            lock_command(input_path_string, new_file_path, delete_old);
+            lock_command(
+                input_path_string,
+                new_file_path,
+                delete_old,
+                &policy,
+                format,
+            );
         }
         "unlock" => {
             let input_path_string = command.get_option("input").throw_if_none();
             let new_file_path = command.get_arg("new-file").to_option();
             let delete_old = command.has("delete-old");
+            let policy = WritePolicy::from_command(command);
 
-            unlock_command(input_path_string, new_file_path, delete_old);
+            unlock_command(
+                input_path_string,
+                new_file_path,
+                delete_old,
+                &policy,
+                format,
+            );
         }
         "archive" => {
             let input_path_string = command.get_option("input").throw_if_none();
             let new_file_path = command.get_arg("new-file").to_option();
             let delete_old = command.has("delete-old");
+            let policy = WritePolicy::from_command(command);
 
-            archive_command(input_path_string, new_file_path, delete_old);
+            archive_command(
+                input_path_string,
+                new_file_path,
+                delete_old,
+                &policy,
+                format,
+            );
         }
         "unarchive" => {
             let input_path_string = command.get_option("input").throw_if_none();
             let new_file_path = command.get_arg("new-file").to_option();
             let delete_old = command.has("delete-old");
+            let policy = WritePolicy::from_command(command);
 
-            unarchive_command(input_path_string, new_file_path, delete_old);
+            unarchive_command(
+                input_path_string,
+                new_file_path,
+                delete_old,
+                &policy,
+                format,
+            );
         }
         "details" => {
             let input_path_string = command.get_option("input").throw_if_none();
+            let icon_png = command.get_arg("icon-png").to_option();
+            let policy = WritePolicy::from_command(command);
 
-            details_command(input_path_string)
+            details_command(input_path_string, icon_png, &policy, format)
         }
-        "models" => models_command(),
+        "models" => models_command(format),
         _ => cli.help(None),
     }
 }
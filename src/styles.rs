@@ -148,27 +148,33 @@ macro_rules! prints {
         let len = styled_string.len();
 
         while i < len {
-            if &styled_string[i..i + 1] == "[" {
-                let end = styled_string[i..].find("]").unwrap() + i;
+            if styled_string[i..].starts_with('[') {
+                // an unclosed bracket is plain text
+                let end = match styled_string[i..].find("]") {
+                    Some(end) => end + i,
+                    None => {
+                        result.push_str(&styled_string[i..]);
+                        break;
+                    }
+                };
                 let directive = &styled_string[i + 1..end];
                 let parts: Vec<&str> = directive.split(':').collect();
 
-                if parts.len() == 2 {
-                    match parts[0] {
-                        "color" => {
-                            let color = Color::from_string(parts[1].to_string());
-                            result.push_str(&color.to_ansi_color_string());
-                        }
-                        "bg" => {
-                            let bg_color = Color::from_string(parts[1].to_string());
-                            result.push_str(&bg_color.to_ansi_background_color_string());
-                        }
-                        "style" => {
-                            let style = TextStyle::from_string(parts[1].to_string());
-                            result.push_str(&style.to_ansi_style_string());
-                        }
-                        _ => {}
+                match parts.as_slice() {
+                    ["color", color] => {
+                        let color = Color::from_string(color.to_string());
+                        result.push_str(&color.to_ansi_color_string());
+                    }
+                    ["bg", bg_color] => {
+                        let bg_color = Color::from_string(bg_color.to_string());
+                        result.push_str(&bg_color.to_ansi_background_color_string());
+                    }
+                    ["style", style] => {
+                        let style = TextStyle::from_string(style.to_string());
+                        result.push_str(&style.to_ansi_style_string());
                     }
+                    // anything else in brackets, like the name of a matrix, is plain text
+                    _ => result.push_str(&styled_string[i..end + 1]),
                 }
                 i = end + 1;
             } else {
@@ -0,0 +1,60 @@
+use ti_tools::calculator::assembly::{parse_hex, Assembly, AssemblyForm, AssemblyTarget};
+use ti_tools::calculator::errors::TiToolsError;
+use ti_tools::calculator::models::{Model, ModelDetails};
+use ti_tools::calculator::program::{OverwritePolicy, Program, ProgramFileType};
+use ti_tools::calculator::DisplayMode;
+
+#[test]
+fn test_compiled_assembly_round_trip() {
+    let code = parse_hex("21 0000\nC9").unwrap();
+
+    let output_dir = std::env::temp_dir().join("ti-tools-test-assembly");
+    let _ = std::fs::remove_dir_all(&output_dir);
+    std::fs::create_dir_all(&output_dir).unwrap();
+
+    for (model, prefix) in [
+        (Model::TI84Plus, [0xBB, 0x6D]),
+        (Model::TI84PlusCE, [0xEF, 0x7B]),
+    ] {
+        let assembly = Assembly::compiled(code.clone(), &model).unwrap();
+        let program =
+            Program::from_assembly("HELLO", ModelDetails::from_model(&model), &assembly).unwrap();
+
+        assert_eq!(program.body.bytes[0..2], prefix);
+
+        let output_path = output_dir.join(format!("{}.8xp", model));
+        program
+            .save_to(&output_path, &OverwritePolicy::Force)
+            .unwrap();
+
+        // loading does not read the machine code as tokens
+        let loaded = Program::load_from_8xp(output_path, DisplayMode::Accessible).unwrap();
+        let assembly = loaded.assembly().unwrap();
+
+        assert_eq!(assembly.form, AssemblyForm::Compiled);
+        assert_eq!(assembly.code, code);
+        assert_eq!(loaded.body.translation, "210000C9\n");
+        assert!(matches!(
+            loaded.to_bytes(&ProgramFileType::TXT),
+            Err(TiToolsError::CompiledAssembly(_))
+        ));
+    }
+
+    assert!(Assembly::compiled(code, &Model::TI83).is_err());
+}
+
+#[test]
+fn test_detect_hex_assembly() {
+    // AsmPrgm, then EF4045 and C9 typed on two lines
+    let body = [
+        0xBB, 0x6C, b'E', b'F', b'4', b'0', b'4', b'5', 0x3F, b'C', b'9',
+    ];
+
+    let assembly = Assembly::detect(&body).unwrap();
+
+    assert_eq!(assembly.form, AssemblyForm::Hex);
+    assert_eq!(assembly.target, AssemblyTarget::Z80);
+    assert_eq!(assembly.code, [0xEF, 0x40, 0x45, 0xC9]);
+
+    assert!(Assembly::detect(&[0xDE, 0x2A, 0x41]).is_none());
+}
@@ -0,0 +1,75 @@
+use ti_tools::calculator::callgraph::{CallGraph, ProgramCalls};
+
+#[test]
+fn test_call_graph() {
+    let graph = CallGraph::new(vec![
+        program("MAIN", "MAIN.8xp", &["A", "B"]),
+        program("A", "A.8xp", &["B"]),
+        program("B", "B.8xp", &["A", "C"]),
+        program("B", "B.txt", &["A", "D"]),
+        program("R", "R.8xp", &["R", "GONE"]),
+    ]);
+
+    let programs = graph
+        .programs
+        .iter()
+        .map(|program| {
+            (
+                program.name.as_str(),
+                program.files.len(),
+                program.calls.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        programs,
+        vec![
+            ("A", 1, vec!["B".to_string()]),
+            (
+                "B",
+                2,
+                vec!["A".to_string(), "C".to_string(), "D".to_string()]
+            ),
+            ("MAIN", 1, vec!["A".to_string(), "B".to_string()]),
+            ("R", 1, vec!["R".to_string(), "GONE".to_string()]),
+        ]
+    );
+
+    let missing = graph
+        .missing
+        .iter()
+        .map(|missing| (missing.name.as_str(), missing.called_by.clone()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        missing,
+        vec![
+            ("C", vec!["B".to_string()]),
+            ("D", vec!["B".to_string()]),
+            ("GONE", vec!["R".to_string()]),
+        ]
+    );
+
+    assert_eq!(
+        graph.cycles,
+        vec![
+            vec!["A".to_string(), "B".to_string()],
+            vec!["R".to_string()]
+        ]
+    );
+
+    let dot = graph.to_dot();
+
+    assert!(dot.contains("\"A\" -> \"B\" [color=red];"));
+    assert!(dot.contains("\"MAIN\" -> \"A\";"));
+    assert!(dot.contains("\"GONE\" [style=dashed, color=red, fontcolor=red];"));
+}
+
+fn program(name: &str, file: &str, calls: &[&str]) -> ProgramCalls {
+    ProgramCalls {
+        name: name.to_string(),
+        files: vec![file.to_string()],
+        calls: calls.iter().map(|call| call.to_string()).collect(),
+    }
+}
@@ -0,0 +1,82 @@
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use ti_tools::calculator::program::Program;
+use ti_tools::calculator::EncodeMode;
+
+// tests run at the same time, so every program gets its own file
+static FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Run the ti-tools binary, feeding `stdin` to any prompts
+pub fn run(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ti-tools"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start ti-tools");
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+/// Run the ti-tools binary with `--format json` and parse what it printed
+pub fn run_json(args: &[&str], stdin: &str) -> (ExitStatus, serde_json::Value) {
+    let args = [args, &["--format", "json"]].concat();
+    let output = run(&args, stdin);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    match serde_json::from_str(&stdout) {
+        Ok(value) => (output.status, value),
+        Err(err) => panic!(
+            "ti-tools {:?} printed invalid json ({}):\n{}\n{}",
+            args,
+            err,
+            stdout,
+            String::from_utf8_lossy(&output.stderr)
+        ),
+    }
+}
+
+/// A fresh, empty directory in the system temp directory
+pub fn temp_dir(name: &str) -> std::path::PathBuf {
+    let directory = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+
+    directory
+}
+
+/// A txt file holding an unlocked RAM program called `name`
+pub fn program_txt(name: &str, body: &str) -> String {
+    format!("{}\n\nProgram\nRAM\naccessible\nlatest\n{}", name, body)
+}
+
+/// Encodes a txt program, the same way the commands load one from a file
+pub fn load_program(name: &str, body: &str) -> Program {
+    let output_dir = std::env::temp_dir().join("ti-tools-test-programs");
+    std::fs::create_dir_all(&output_dir).unwrap();
+
+    let path = output_dir.join(format!(
+        "{}-{}-{}.txt",
+        name,
+        std::process::id(),
+        FILES.fetch_add(1, Ordering::SeqCst)
+    ));
+    std::fs::write(&path, program_txt(name, body)).unwrap();
+
+    let program = Program::load_from_txt(path.to_path_buf(), &EncodeMode::Smart);
+
+    std::fs::remove_file(&path).unwrap();
+
+    program.unwrap()
+}
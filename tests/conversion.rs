@@ -178,3 +178,19 @@ fn test_program(path_to_8xp: &str, path_to_txt: &str) {
         path_to_8xp, path_to_txt
     );
 }
+
+#[test]
+fn test_truncated_program_fails_to_load() {
+    let output_dir = std::env::temp_dir().join("ti-tools-test-truncated");
+    let _ = std::fs::remove_dir_all(&output_dir);
+    std::fs::create_dir_all(&output_dir).unwrap();
+
+    let path = output_dir.join("BROKEN.8xp");
+    std::fs::write(&path, "**TI83F*").unwrap();
+
+    let program = Program::load_from_8xp(path.to_path_buf(), DisplayMode::Accessible);
+
+    assert!(program.is_err(), "Loaded a truncated program: {:?}", path);
+
+    std::fs::remove_dir_all(&output_dir).unwrap();
+}
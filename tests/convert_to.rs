@@ -0,0 +1,93 @@
+mod common;
+
+use common::{run, run_json, temp_dir};
+use std::fs;
+use std::path::Path;
+use ti_tools::calculator::program::ProgramFileType;
+
+#[test]
+fn test_target_file_types() {
+    for extension in ["8xp", "83p", "82p", "txt", "json"] {
+        let file_type = ProgramFileType::from_string(extension);
+
+        assert!(file_type.is_ok(), "{} is not a target format", extension);
+        assert_eq!(file_type.unwrap().to_string(), extension);
+    }
+
+    assert!(ProgramFileType::from_string("8xk").is_err());
+}
+
+#[test]
+fn test_mass_conversion_to_format() {
+    let directory = temp_dir("ti-tools-test-convert-to");
+    let input_dir = directory.join("in");
+    fs::create_dir_all(&input_dir).unwrap();
+
+    // one of each, so without --to they would be converted in opposite directions
+    fs::copy("./tests/programs/FACTOR.8xp", input_dir.join("FACTOR.8xp")).unwrap();
+    fs::copy(
+        "./tests/programs/RADICAL.txt",
+        input_dir.join("RADICAL.txt"),
+    )
+    .unwrap();
+
+    for extension in ["json", "83p"] {
+        let output_dir = directory.join(extension);
+
+        let (status, report) = run_json(
+            &[
+                "convert",
+                input_dir.to_str().unwrap(),
+                "-m",
+                "--to",
+                extension,
+                "-o",
+                output_dir.to_str().unwrap(),
+                "-y",
+            ],
+            "",
+        );
+
+        assert!(status.success(), "Mass conversion failed: {}", report);
+
+        for name in ["FACTOR", "RADICAL"] {
+            let output_path = output_dir.join(name).with_extension(extension);
+
+            assert!(
+                output_path.exists(),
+                "{} was not converted to {:?}",
+                name,
+                output_path
+            );
+        }
+    }
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_to_requires_mass() {
+    let directory = temp_dir("ti-tools-test-convert-to-single");
+    let output_path = directory.join("FACTOR.txt");
+
+    let output = run(
+        &[
+            "convert",
+            "./tests/programs/FACTOR.8xp",
+            "--to",
+            "txt",
+            "-o",
+            output_path.to_str().unwrap(),
+        ],
+        "",
+    );
+
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .contains("The argument \"to\" requires the argument \"mass\""),
+        "--to was accepted without --mass"
+    );
+    assert!(!Path::new(&output_path).exists());
+
+    fs::remove_dir_all(&directory).unwrap();
+}
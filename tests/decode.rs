@@ -0,0 +1,50 @@
+use ti_tools::calculator::file::{decode, decode_tokens};
+use ti_tools::calculator::tokens::{load_tokens, OsVersion};
+use ti_tools::calculator::DisplayMode;
+
+#[test]
+fn test_decode_tokens() {
+    let map = load_tokens(&OsVersion::latest()).unwrap();
+    let bytes = [0xCE, 0x41, 0x6F, 0x30, 0x3F, 0x5D, 0x00, 0x04, 0x41];
+
+    let tokens = decode_tokens(&bytes, &map, "en", &DisplayMode::Accessible).unwrap();
+
+    let summary = tokens
+        .iter()
+        .map(|token| {
+            (
+                token.key.as_str(),
+                token.text.as_str(),
+                token.offset,
+                token.line,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        summary,
+        vec![
+            ("$CE", "If ", 0, 1),
+            ("$41", "A", 1, 1),
+            ("$6F", "!=", 2, 1),
+            ("$30", "0", 3, 1),
+            ("$3F", "\n", 4, 1),
+            ("$5D$00", "L1", 5, 2),
+            ("$04", "->", 7, 2),
+            ("$41", "A", 8, 2),
+        ]
+    );
+
+    assert_eq!(tokens[2].display, "≠");
+    assert_eq!(tokens[5].bytes, vec![0x5D, 0x00]);
+
+    let translation = decode(&bytes, &map, "en", &DisplayMode::Accessible).unwrap();
+
+    assert_eq!(
+        translation,
+        tokens
+            .iter()
+            .map(|token| token.text.as_str())
+            .collect::<String>()
+    );
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::load_program;
+use ti_tools::calculator::diff::{diff_programs, ChangeKind};
+use ti_tools::calculator::DisplayMode;
+
+#[test]
+fn test_diff_lines_and_tokens() {
+    let old = load_program("OLD", "Disp 1\n1->A\nDisp A\nStop");
+    let new = load_program("NEW", "Disp 1\n2->A\nDisp A\nDisp B\nStop");
+
+    let diff = diff_programs(&old, &new, &DisplayMode::Accessible).unwrap();
+
+    let lines = diff
+        .lines
+        .iter()
+        .map(|line| (line.kind, line.old_line, line.new_line))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        lines,
+        vec![
+            (ChangeKind::Equal, Some(1), Some(1)),
+            (ChangeKind::Changed, Some(2), Some(2)),
+            (ChangeKind::Equal, Some(3), Some(3)),
+            (ChangeKind::Added, None, Some(4)),
+            (ChangeKind::Equal, Some(4), Some(5)),
+        ]
+    );
+
+    let tokens = diff.lines[1]
+        .tokens
+        .iter()
+        .map(|token| (token.kind, token.text.as_str()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        tokens,
+        vec![
+            (ChangeKind::Removed, "1"),
+            (ChangeKind::Added, "2"),
+            (ChangeKind::Equal, "->A"),
+        ]
+    );
+
+    assert_eq!(diff.metadata.len(), 1);
+    assert_eq!(diff.metadata[0].field, "name");
+    assert_eq!(diff.metadata[0].old, "OLD");
+    assert_eq!(diff.metadata[0].new, "NEW");
+}
+
+#[test]
+fn test_diff_same_program_is_empty() {
+    let program = load_program("SAME", "For(I,1,10)\nDisp I\nEnd");
+
+    let diff = diff_programs(&program, &program, &DisplayMode::Pretty).unwrap();
+
+    assert!(diff.is_empty());
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::{run, run_json, temp_dir};
+use ti_tools::calculator::assembly::{Assembly, AssemblyTarget};
+use ti_tools::calculator::disasm::{disassemble, Cpu};
+use ti_tools::calculator::models::{Model, ModelDetails};
+use ti_tools::calculator::program::{OverwritePolicy, Program};
+
+#[test]
+fn test_disassemble_z80_bcalls() {
+    let target = AssemblyTarget::Z80Color;
+    let code = [
+        0x21, 0x00, 0x00, 0xEF, 0x0A, 0x45, 0xDD, 0x36, 0xFE, 0x05, 0x18, 0xFE, 0xC9,
+    ];
+
+    let instructions = disassemble(&code, Cpu::for_target(target), target.user_mem()).unwrap();
+    let texts: Vec<&str> = instructions
+        .iter()
+        .map(|instruction| instruction.text.as_str())
+        .collect();
+
+    assert_eq!(
+        texts,
+        [
+            "ld hl,$0000",
+            "bcall(_PutS)",
+            "ld (ix-$02),$05",
+            "jr $A615",
+            "ret"
+        ]
+    );
+    assert_eq!(instructions[1].address, 0xA60E);
+    assert_eq!(instructions[1].bytes, [0xEF, 0x0A, 0x45]);
+}
+
+#[test]
+fn test_disassemble_ez80_adl() {
+    let code = [
+        0x21, 0x34, 0x12, 0x00, 0xCD, 0xC0, 0x07, 0x02, 0x40, 0x21, 0x00, 0x10, 0xED, 0x6C, 0xCD,
+    ];
+
+    let instructions = disassemble(&code, Cpu::EZ80, AssemblyTarget::EZ80.user_mem()).unwrap();
+    let texts: Vec<&str> = instructions
+        .iter()
+        .map(|instruction| instruction.text.as_str())
+        .collect();
+
+    // a call cut off by the end of the code is data
+    assert_eq!(
+        texts,
+        [
+            "ld hl,$001234",
+            "call _PutS",
+            "ld.sis hl,$1000",
+            "mlt hl",
+            "db $CD"
+        ]
+    );
+}
+
+#[test]
+fn test_disassemble_addresses_wrap() {
+    let code = [0x00, 0x18, 0xFC, 0xC9];
+
+    let instructions = disassemble(&code, Cpu::Z80, 0xFFFF).unwrap();
+    let addresses: Vec<u32> = instructions
+        .iter()
+        .map(|instruction| instruction.address)
+        .collect();
+
+    assert_eq!(addresses, [0xFFFF, 0x0000, 0x0002]);
+    assert_eq!(instructions[1].text, "jr $FFFE");
+
+    let instructions = disassemble(&code, Cpu::EZ80, 0xFFFFFF).unwrap();
+
+    assert_eq!(instructions[1].address, 0x000000);
+}
+
+#[test]
+fn test_disasm_origin_follows_model() {
+    let directory = temp_dir("ti-tools-test-disasm-origin");
+
+    for (model, origin) in [
+        (Model::TI84Plus, "9D95"),
+        (Model::TI84PlusCSE, "A60B"),
+        (Model::TI84PlusCE, "D1A881"),
+    ] {
+        let assembly = Assembly::compiled(vec![0xC9], &model).unwrap();
+        let program =
+            Program::from_assembly("RET", ModelDetails::from_model(&model), &assembly).unwrap();
+
+        let path = directory.join(format!("{}.8xp", origin));
+        program.save_to(&path, &OverwritePolicy::Force).unwrap();
+
+        let (status, listing) = run_json(&["disasm", path.to_str().unwrap()], "");
+
+        assert!(status.success(), "disasm failed: {}", listing);
+        assert_eq!(listing["origin"], origin, "Wrong origin for the {}", model);
+    }
+
+    let path = directory.join("9D95.8xp");
+    let output = run(
+        &["disasm", path.to_str().unwrap(), "--origin", "FFFFFFFF"],
+        "",
+    );
+
+    assert_eq!(output.status.code(), Some(1));
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,36 @@
+use std::fs;
+use ti_tools::calculator::docs::{token_reference_html, token_reference_markdown};
+use ti_tools::calculator::tokens::{load_token_definitions, load_token_table};
+
+#[test]
+fn test_markdown_reference_covers_every_token() {
+    let table = load_token_table().unwrap();
+    let definitions = load_token_definitions().unwrap();
+
+    let markdown = token_reference_markdown(&table, &definitions);
+
+    assert_eq!(markdown.matches("\n## 0x").count(), table.len());
+    assert!(markdown.contains("## 0xBB0A `randInt(`"));
+    assert!(markdown.contains("- `numtrials` (number, optional)"));
+    // pieceWise( was only on a few versions of the TI-84+CE
+    assert!(markdown.contains("| TI-84+CE 5.2.0 | TI-84+CE 5.3.0 | `pieceWise(` |"));
+
+    // the wiki is written by the docs command, so it has to match the token data
+    let wiki = fs::read_to_string("wiki/tokens.md").unwrap();
+    assert!(
+        wiki == markdown,
+        "wiki/tokens.md is out of date, run ti-tools docs -o wiki/tokens.md"
+    );
+}
+
+#[test]
+fn test_html_reference_escapes_tokens() {
+    let table = load_token_table().unwrap();
+    let definitions = load_token_definitions().unwrap();
+
+    let html = token_reference_html(&table, &definitions);
+
+    assert_eq!(html.matches("<section id=").count(), table.len());
+    assert!(html.contains("<a href=\"#6B\">&lt;</a>"));
+    assert!(html.contains("<h2>0x3F <code>\\n</code></h2>"));
+}
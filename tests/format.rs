@@ -0,0 +1,77 @@
+mod common;
+
+use common::program_txt;
+use ti_tools::calculator::format::format_txt;
+use ti_tools::calculator::program::Program;
+use ti_tools::calculator::{DisplayMode, EncodeMode};
+
+#[test]
+fn test_format_indents_blocks() {
+    let formatted = format_source(
+        "INDENT",
+        "If A=0:Then\nFor(I,1,10\nDisp I\nEnd\nElse\nWhile B!=0\nB-1->B\nEnd\nEnd",
+        true,
+    );
+
+    assert_eq!(
+        formatted,
+        "INDENT\n\nProgram\nRAM\naccessible\nlatest\nIf A=0:Then\n\tFor(I,1,10\n\t\tDisp I\n\tEnd\nElse\n\tWhile B!=0\n\t\tB-1->B\n\tEnd\nEnd"
+    );
+}
+
+#[test]
+fn test_format_is_idempotent_and_keeps_bytes() {
+    for path in std::fs::read_dir("tests/programs").unwrap() {
+        let path = path.unwrap().path();
+
+        if path.extension().is_none_or(|extension| extension != "txt") {
+            continue;
+        }
+
+        let original = Program::load_from_txt(path.clone(), &EncodeMode::Smart).unwrap();
+        let source = std::fs::read_to_string(&path).unwrap();
+        let name = path.file_stem().unwrap().to_string_lossy().to_string();
+
+        let formatted = format_source(&name, &source, true);
+        let reformatted = format_source(&name, &formatted, true);
+
+        assert_eq!(formatted, reformatted, "{} is not idempotent", name);
+
+        let output_path = write_source(&format!("{}-FORMATTED", name), &formatted);
+        let program = Program::load_from_txt(output_path.clone(), &EncodeMode::Smart).unwrap();
+        std::fs::remove_file(&output_path).unwrap();
+
+        assert_eq!(
+            program.body.bytes, original.body.bytes,
+            "{} changed when formatted",
+            name
+        );
+    }
+}
+
+/// Writes a txt program, the header is added when `source` is only a body
+fn write_source(name: &str, source: &str) -> std::path::PathBuf {
+    let output_dir = std::env::temp_dir().join("ti-tools-test-format");
+    std::fs::create_dir_all(&output_dir).unwrap();
+
+    let contents = if source.lines().count() > 6 && source.lines().nth(2) == Some("Program") {
+        source.to_string()
+    } else {
+        program_txt(name, source)
+    };
+
+    let path = output_dir.join(format!("{}.txt", name));
+    std::fs::write(&path, contents).unwrap();
+
+    path
+}
+
+fn format_source(name: &str, source: &str, indent: bool) -> String {
+    let path = write_source(name, source);
+
+    let formatted = format_txt(&path, &DisplayMode::Accessible, &EncodeMode::Smart, indent);
+
+    std::fs::remove_file(&path).unwrap();
+
+    formatted.unwrap()
+}
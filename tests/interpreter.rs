@@ -0,0 +1,139 @@
+mod common;
+
+use common::load_program;
+use std::collections::BTreeMap;
+use ti_tools::calculator::interpreter::{run_program, RunOptions, Value};
+use ti_tools::calculator::models::{Model, ModelDetails};
+
+#[test]
+fn test_run_input_and_control_flow() {
+    let program = load_program(
+        "FLOW",
+        "Prompt A\nFor(I,1,3)\nA*I->A\nEnd\n0->B\nWhile B<5\nB+2->B\nEnd\nIf A>10:Then\nDisp \"BIG\"\nElse\nDisp \"SMALL\"\nEnd\nDisp A,B",
+    );
+
+    let execution = run_program(&program, options(&["2"], &[])).unwrap();
+
+    assert_eq!(execution.error, None);
+    assert_eq!(execution.output, vec!["A=?2", "BIG", "12", "6"]);
+    assert_eq!(execution.variables["A"], Value::Number(12.0));
+    assert_eq!(execution.variables["I"], Value::Number(4.0));
+}
+
+#[test]
+fn test_run_lists_strings_and_keys() {
+    let program = load_program(
+        "DATA",
+        "{1,2}->L1\n3->L1(3)\nsub(\"HELLO\",2,3)->Str1\nRepeat K\ngetKey->K\nEnd\nDisp length(Str1),sum(L1)\nL1*2",
+    );
+
+    let execution = run_program(&program, options(&[], &[0, 0, 105])).unwrap();
+
+    assert_eq!(execution.error, None);
+    assert_eq!(execution.output, vec!["3", "6", "{2 4 6}"]);
+    assert_eq!(execution.variables["K"], Value::Number(105.0));
+    assert_eq!(execution.variables["L₁"], Value::List(vec![1.0, 2.0, 3.0]));
+    assert_eq!(
+        execution.variables["Str1"],
+        Value::String("ELL".to_string())
+    );
+}
+
+#[test]
+fn test_run_errors() {
+    let program = load_program("DIVIDE", "1->A\nDisp A\nA/0->B");
+    let execution = run_program(&program, options(&[], &[])).unwrap();
+    let error = execution.error.unwrap();
+
+    assert_eq!(error.line, 3);
+    assert_eq!(error.to_string(), "ERR:DIVIDE BY 0 on line 3");
+
+    let program = load_program("FOREVER", "Lbl A\nGoto A");
+    let execution = run_program(&program, options(&[], &[])).unwrap();
+
+    assert_eq!(execution.steps, 100);
+    assert!(execution.error.unwrap().message.starts_with("STOPPED"));
+}
+
+#[test]
+fn test_run_rejects_sizes_out_of_range() {
+    for (body, error) in [
+        ("Disp sub(\"ABC\",2,1|E99)", "DOMAIN"),
+        ("1|E15->dim(L1)", "INVALID DIM"),
+        ("~1->dim(L1)", "DOMAIN"),
+        ("2.5->dim(L1)", "DOMAIN"),
+        ("randInt(1,6,1000)->L1", "INVALID DIM"),
+        ("{1,2}->L1:Disp L1(10^400-10^400)", "DOMAIN"),
+        ("{1,2}->L1:Disp L1(1.5)", "DOMAIN"),
+        ("{1,2}->L1:5->L1(1.5)", "DOMAIN"),
+    ] {
+        let program = load_program("SIZES", body);
+        let execution = run_program(&program, options(&[], &[])).unwrap();
+
+        assert!(
+            execution
+                .error
+                .as_ref()
+                .is_some_and(|err| err.message.starts_with(error)),
+            "{} did not fail with {}: {:?}",
+            body,
+            error,
+            execution.error
+        );
+    }
+
+    let program = load_program(
+        "SIZES",
+        "3->dim(L1)
+randInt(1,6,999)->L2",
+    );
+    let execution = run_program(&program, options(&[], &[])).unwrap();
+
+    assert_eq!(execution.error, None);
+    assert_eq!(execution.variables["L₁"], Value::List(vec![0.0; 3]));
+}
+
+#[test]
+fn test_run_screen_size_follows_model() {
+    // the bottom right corner of the 26x10 home screen of the color models
+    for (model, fits) in [
+        (Model::TI84Plus, false),
+        (Model::TI84PlusCSE, true),
+        (Model::TI84PlusCE, true),
+    ] {
+        let mut program = load_program("SCREEN", "Output(10,26,\"X");
+        program.model = ModelDetails::from_model(&model);
+
+        let execution = run_program(&program, options(&[], &[])).unwrap();
+
+        assert_eq!(
+            execution.error.is_none(),
+            fits,
+            "Wrong home screen size for the {}: {:?}",
+            model,
+            execution.error
+        );
+    }
+}
+
+#[test]
+fn test_run_limits_nested_expr() {
+    let program = load_program("NEST", "Disp expr(Str1)");
+    let mut options = options(&[], &[]);
+    options
+        .variables
+        .insert("Str1".to_string(), Value::String("expr(Str1)".to_string()));
+
+    let execution = run_program(&program, options).unwrap();
+
+    assert!(execution.error.unwrap().message.starts_with("ILLEGAL NEST"));
+}
+
+fn options(inputs: &[&str], keys: &[u16]) -> RunOptions {
+    RunOptions {
+        inputs: inputs.iter().map(|input| input.to_string()).collect(),
+        keys: keys.to_vec(),
+        max_steps: 100,
+        variables: BTreeMap::new(),
+    }
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use common::{run_json, temp_dir};
+
+#[test]
+fn test_details_json() {
+    let (status, details) = run_json(&["details", "./tests/programs/RADICAL.8xp"], "");
+
+    assert!(status.success(), "details failed: {}", details);
+    assert_eq!(details["name"], "RADICAL");
+    assert_eq!(details["file_type"], "Program");
+}
+
+#[test]
+fn test_overwrite_prompt_keeps_json_clean() {
+    let output_dir = temp_dir("ti-tools-test-json-output");
+
+    let output_path = output_dir.join("RADICAL.txt");
+    std::fs::write(&output_path, "existing").unwrap();
+
+    let (status, report) = run_json(
+        &[
+            "convert",
+            "./tests/programs/RADICAL.8xp",
+            "-o",
+            output_path.to_str().unwrap(),
+        ],
+        "y\n",
+    );
+
+    assert!(status.success(), "convert failed: {}", report);
+    assert_eq!(report["status"], "converted");
+    assert_ne!(
+        std::fs::read_to_string(&output_path).unwrap(),
+        "existing",
+        "The existing file was not overwritten"
+    );
+
+    std::fs::remove_dir_all(&output_dir).unwrap();
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::load_program;
+use ti_tools::calculator::lint::{lint_program, program_calls, Severity};
+
+#[test]
+fn test_lint_rules() {
+    let program = load_program(
+        "LINT",
+        "If A=1\nThen\nDisp \"ONE\nEnd\nEnd\nFor(I,1,10\nGoto B\nLbl A\nLbl A\nLbl C\nMenu(\"MENU\",\"C\",C,\"D\",D)\nStop",
+    );
+
+    let diagnostics = lint_program(&program, true).unwrap();
+
+    let found = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.rule.as_str(), diagnostic.line))
+        .collect::<Vec<_>>();
+
+    for expected in [
+        ("unmatched-end", 5),
+        ("missing-end", 6),
+        ("undefined-label", 7),
+        ("unused-label", 8),
+        ("duplicate-label", 9),
+        ("undefined-label", 11),
+        ("stop-in-subprogram", 12),
+    ] {
+        assert!(
+            found.contains(&expected),
+            "Missing {:?} in {:?}",
+            expected,
+            found
+        );
+    }
+
+    assert_eq!(found.len(), 7, "Unexpected diagnostics: {:?}", found);
+}
+
+#[test]
+fn test_lint_allows_strings_closed_by_the_line_end() {
+    let program = load_program("OPEN", "Disp \"HI\n\"A:B\"->Str1\nOutput(1,1,\"X");
+
+    let diagnostics = lint_program(&program, false).unwrap();
+
+    assert!(
+        diagnostics.is_empty(),
+        "Unexpected diagnostics: {:?}",
+        diagnostics
+    );
+}
+
+#[test]
+fn test_lint_clean_program() {
+    let program = load_program(
+        "CLEAN",
+        "Lbl A\nIf A:Then\nDisp \"A:B\"\nElse\nWhile B\nEnd\nEnd\nprgmHELPER\nGoto A",
+    );
+
+    let diagnostics = lint_program(&program, false).unwrap();
+
+    assert!(
+        !diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error),
+        "Clean program has errors: {:?}",
+        diagnostics
+    );
+
+    assert_eq!(program_calls(&program).unwrap(), vec!["HELPER".to_string()]);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::program_txt;
+use serde_json::{json, Value};
+use ti_tools::calculator::lsp::Server;
+use ti_tools::calculator::{DisplayMode, EncodeMode};
+
+const URI: &str = "file:///LOOP.txt";
+
+#[test]
+fn test_lsp_diagnostics_and_hover() {
+    let (mut server, diagnostics) = open("Lbl A\nDisp abs(X)\nIf X\nThen\nGoto A");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["code"], "missing-end");
+    assert_eq!(diagnostics[0]["range"]["start"]["line"], 9);
+
+    let hover = request(&mut server, "textDocument/hover", 7, 6);
+    let contents = hover["contents"]["value"].as_str().unwrap();
+
+    assert!(contents.contains("abs(value)"));
+    assert_eq!(hover["range"]["start"]["character"], 5);
+    assert_eq!(hover["range"]["end"]["character"], 9);
+
+    let replies = server.handle(&change("Disp 1\nDisp §"));
+    let diagnostics = replies[0]["params"]["diagnostics"].as_array().unwrap();
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["code"], "unknown-token");
+    assert_eq!(diagnostics[0]["range"]["start"]["line"], 7);
+    assert_eq!(diagnostics[0]["range"]["start"]["character"], 5);
+}
+
+#[test]
+fn test_lsp_goto_definition() {
+    let (mut server, diagnostics) =
+        open("Menu(\"GO\",\"ONE\",A,\"TWO\",B)\nLbl A\nDisp 1\n\tLbl B\nGoto B");
+
+    assert!(diagnostics.is_empty());
+
+    let definition = request(&mut server, "textDocument/definition", 10, 5);
+
+    assert_eq!(definition["uri"], URI);
+    assert_eq!(
+        definition["range"]["start"],
+        json!({ "line": 9, "character": 1 })
+    );
+    assert_eq!(
+        definition["range"]["end"],
+        json!({ "line": 9, "character": 6 })
+    );
+
+    let definition = request(&mut server, "textDocument/definition", 6, 16);
+
+    assert_eq!(definition["range"]["start"]["line"], 7);
+}
+
+fn open(body: &str) -> (Server, Vec<Value>) {
+    let mut server = Server::new(DisplayMode::Accessible, EncodeMode::Smart).unwrap();
+
+    let replies = server.handle(&json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {
+            "textDocument": { "uri": URI, "languageId": "tibasic", "version": 1, "text": text(body) },
+        },
+    }));
+
+    let diagnostics = replies[0]["params"]["diagnostics"]
+        .as_array()
+        .unwrap()
+        .to_vec();
+
+    (server, diagnostics)
+}
+
+fn change(body: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didChange",
+        "params": {
+            "textDocument": { "uri": URI, "version": 2 },
+            "contentChanges": [{ "text": text(body) }],
+        },
+    })
+}
+
+fn request(server: &mut Server, method: &str, line: usize, character: usize) -> Value {
+    let replies = server.handle(&json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": {
+            "textDocument": { "uri": URI },
+            "position": { "line": line, "character": character },
+        },
+    }));
+
+    replies[0]["result"].clone()
+}
+
+fn text(body: &str) -> String {
+    program_txt("LOOP", body)
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::{load_program, run, temp_dir};
+use ti_tools::calculator::merge::merge_programs;
+use ti_tools::calculator::program::{detect_file_type, ProgramFileType};
+use ti_tools::calculator::DisplayMode;
+
+#[test]
+fn test_merge_changes_from_both_sides() {
+    let base = load_program("BASE", "1->A\nDisp A\n2->B\nDisp B");
+    let ours = load_program("BASE", "5->A\nDisp A\n2->B\nDisp B");
+    let theirs = load_program("OTHER", "1->A\nDisp A\n2->B\nDisp B\nDisp A+B");
+
+    let merge = merge_programs(&base, ours, &theirs, &DisplayMode::Accessible).unwrap();
+
+    assert!(merge.is_clean());
+    assert_eq!(merge.program.metadata.name, "OTHER");
+    assert_eq!(
+        merge.program.body.translation,
+        "5->A\nDisp A\n2->B\nDisp B\nDisp A+B"
+    );
+
+    let checksum = merge
+        .program
+        .metadata
+        .bytes
+        .iter()
+        .chain(merge.program.body.bytes.iter())
+        .map(|&byte| byte as u32)
+        .sum::<u32>() as u16;
+    assert_eq!(merge.program.checksum.value, checksum);
+}
+
+#[test]
+fn test_merge_conflicts_keep_ours() {
+    let base = load_program("BASE", "1->A\nDisp A");
+    let ours = load_program("BASE", "2->A\nDisp A");
+    let theirs = load_program("BASE", "3->A\nDisp A");
+
+    let merge = merge_programs(&base, ours, &theirs, &DisplayMode::Accessible).unwrap();
+
+    assert_eq!(merge.conflicts, 1);
+    assert_eq!(merge.program.body.translation, "2->A\nDisp A");
+    assert!(merge.text.ends_with(
+        "<<<<<<< ours\n2->A\n||||||| base\n1->A\n=======\n3->A\n>>>>>>> theirs\nDisp A"
+    ));
+}
+
+#[test]
+fn test_detect_file_type_without_extension() {
+    let program = load_program("DETECT", "Disp 1");
+    let directory = temp_dir("ti-tools-test-merge-detect");
+    let path = directory.join("DETECT");
+
+    std::fs::write(&path, program.to_bytes(&ProgramFileType::XP).unwrap()).unwrap();
+
+    assert!(matches!(
+        detect_file_type(&path).unwrap(),
+        ProgramFileType::XP
+    ));
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_conflict_file_only_with_flag() {
+    let directory = temp_dir("ti-tools-test-merge-conflict-file");
+
+    // git hands the driver extensionless temporary files in the worktree root
+    for (file, body) in [("base", "1->A"), ("ours", "2->A"), ("theirs", "3->A")] {
+        let program = load_program("BASE", body);
+        let bytes = program.to_bytes(&ProgramFileType::XP).unwrap();
+        std::fs::write(directory.join(file), bytes).unwrap();
+    }
+
+    let path = |file: &str| directory.join(file).to_string_lossy().to_string();
+    let (base, ours, theirs) = (path("base"), path("ours"), path("theirs"));
+    let merge = |extra: &[&str]| run(&[&["merge", &base, &ours, &theirs], extra].concat(), "");
+
+    merge(&[]);
+
+    let mut files = std::fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    files.sort();
+
+    assert_eq!(files, vec!["base", "ours", "theirs"]);
+
+    merge(&["--conflicts", &path("conflicts.txt")]);
+
+    assert!(std::fs::read_to_string(directory.join("conflicts.txt"))
+        .unwrap()
+        .contains("<<<<<<< ours\n2->A"));
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_merge_outputs_follow_write_policy() {
+    let directory = temp_dir("ti-tools-test-merge-policy");
+
+    for (file, body) in [("base", "1->A"), ("ours", "2->A"), ("theirs", "3->A")] {
+        let program = load_program("BASE", body);
+        let bytes = program.to_bytes(&ProgramFileType::XP).unwrap();
+        std::fs::write(directory.join(file), bytes).unwrap();
+    }
+
+    let path = |file: &str| directory.join(file).to_string_lossy().to_string();
+    let (base, ours, theirs) = (path("base"), path("ours"), path("theirs"));
+    let merge = |extra: &[&str]| run(&[&["merge", &base, &ours, &theirs], extra].concat(), "");
+
+    for file in ["merged.txt", "conflicts.txt"] {
+        std::fs::write(directory.join(file), "existing").unwrap();
+    }
+
+    merge(&["-o", &path("merged.txt"), "-N"]);
+    merge(&["--conflicts", &path("conflicts.txt"), "-N"]);
+
+    for file in ["merged.txt", "conflicts.txt"] {
+        assert_eq!(
+            std::fs::read_to_string(directory.join(file)).unwrap(),
+            "existing",
+            "{} was overwritten",
+            file
+        );
+    }
+
+    merge(&["--conflicts", &path("conflicts.txt"), "-F"]);
+
+    assert!(std::fs::read_to_string(directory.join("conflicts.txt"))
+        .unwrap()
+        .contains("<<<<<<< ours"));
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::load_program;
+use ti_tools::calculator::optimize::{optimize_program, Transform};
+
+#[test]
+fn test_optimize_transforms() {
+    let mut program = load_program(
+        "OPTIMIZE",
+        "If A=0\nDisp \"HI\"\nWhile B!=0\nsin(X)->Y\nFor(I,1,10)\n{1,2}->L1\nDisp \"A:B\":Disp \"C)\"\nEnd",
+    );
+
+    let savings = optimize_program(&mut program, &Transform::all()).unwrap();
+
+    let saved = savings
+        .iter()
+        .map(|savings| (savings.transform.as_str(), savings.bytes_saved))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        saved,
+        vec![
+            ("parens", 1),
+            ("braces", 1),
+            ("quotes", 2),
+            ("zero-compare", 3)
+        ]
+    );
+
+    assert_eq!(
+        program.body.translation,
+        "If not(A\nDisp \"HI\nWhile B\nsin(X->Y\nFor(I,1,10)\n{1,2->L1\nDisp \"A:B\":Disp \"C)\nEnd"
+    );
+
+    let checksum = program
+        .metadata
+        .bytes
+        .iter()
+        .chain(program.body.bytes.iter())
+        .map(|&byte| byte as u32)
+        .sum::<u32>() as u16;
+
+    assert_eq!(program.checksum.value, checksum, "Checksum was not updated");
+    assert_eq!(
+        program.metadata.body_length as usize,
+        program.body.bytes.len(),
+        "Body length was not updated"
+    );
+}
+
+#[test]
+fn test_optimize_selected_transforms() {
+    let mut program = load_program("SELECT", "If A=0\nDisp \"HI\"");
+
+    let savings = optimize_program(&mut program, &[Transform::Quotes]).unwrap();
+
+    assert_eq!(savings.len(), 1);
+    assert_eq!(program.body.translation, "If A=0\nDisp \"HI");
+}
+
+#[test]
+fn test_zero_compare_inside_open_group() {
+    let mut program = load_program("ZEROS", "If sum(L1=0\nDisp 1\nIf sum(L1)=0\nDisp 2");
+
+    optimize_program(&mut program, &[Transform::ZeroCompare]).unwrap();
+
+    assert_eq!(
+        program.body.translation,
+        "If sum(L1=0\nDisp 1\nIf not(sum(L1)\nDisp 2"
+    );
+}
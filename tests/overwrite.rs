@@ -0,0 +1,215 @@
+mod common;
+
+use common::{program_txt, run, temp_dir};
+use std::path::Path;
+use ti_tools::calculator::assembly::Assembly;
+use ti_tools::calculator::errors::TiToolsError;
+use ti_tools::calculator::models::{Model, ModelDetails};
+use ti_tools::calculator::program::{OverwritePolicy, Program};
+use ti_tools::calculator::DisplayMode;
+
+#[test]
+fn test_overwrite_policies() {
+    let input_path = Path::new("./tests/programs/RADICAL.8xp");
+
+    let program = Program::load_from_8xp(input_path.to_path_buf(), DisplayMode::Accessible);
+
+    assert!(program.is_ok(), "Failed to load program: {:?}", input_path);
+
+    let program = program.unwrap();
+
+    let output_dir = std::env::temp_dir().join("ti-tools-test-overwrite");
+    let _ = std::fs::remove_dir_all(&output_dir);
+    std::fs::create_dir_all(&output_dir).unwrap();
+
+    let output_path = output_dir.join("RADICAL.txt");
+    let backup_path = output_dir.join("RADICAL.txt.bak");
+
+    std::fs::write(&output_path, "existing").unwrap();
+
+    let result = program.save_to(&output_path, &OverwritePolicy::NoClobber);
+
+    assert!(
+        matches!(result, Err(TiToolsError::FileAlreadyExists(_))),
+        "No clobber did not refuse to overwrite: {:?}",
+        result
+    );
+
+    assert_eq!(
+        std::fs::read_to_string(&output_path).unwrap(),
+        "existing",
+        "No clobber altered the existing file"
+    );
+
+    let result = program.save_to(&output_path, &OverwritePolicy::Backup);
+
+    assert!(result.is_ok(), "Failed to save with backup: {:?}", result);
+
+    assert_eq!(
+        std::fs::read_to_string(&backup_path).unwrap(),
+        "existing",
+        "The backup does not contain the old file"
+    );
+
+    std::fs::write(&output_path, "existing").unwrap();
+
+    let result = program.save_to(&output_path, &OverwritePolicy::Force);
+
+    assert!(result.is_ok(), "Failed to force save: {:?}", result);
+
+    assert_ne!(
+        std::fs::read_to_string(&output_path).unwrap(),
+        "existing",
+        "Force did not overwrite the existing file"
+    );
+
+    std::fs::remove_dir_all(&output_dir).unwrap();
+}
+
+#[test]
+fn test_render_output_policy() {
+    let output_dir = temp_dir("ti-tools-test-overwrite-render");
+    let output_path = output_dir.join("RADICAL.html");
+    let output = output_path.to_str().unwrap();
+
+    std::fs::write(&output_path, "existing").unwrap();
+
+    let refused = run(
+        &["render", "./tests/programs/RADICAL.8xp", "-o", output, "-N"],
+        "",
+    );
+
+    assert!(!refused.status.success());
+    assert_eq!(std::fs::read_to_string(&output_path).unwrap(), "existing");
+
+    let rendered = run(
+        &["render", "./tests/programs/RADICAL.8xp", "-o", output, "-B"],
+        "",
+    );
+
+    assert!(rendered.status.success());
+    assert!(std::fs::read_to_string(&output_path)
+        .unwrap()
+        .contains("<html"));
+    assert_eq!(
+        std::fs::read_to_string(output_dir.join("RADICAL.html.bak")).unwrap(),
+        "existing"
+    );
+
+    std::fs::remove_dir_all(&output_dir).unwrap();
+}
+
+#[test]
+fn test_docs_output_policy() {
+    let output_dir = temp_dir("ti-tools-test-overwrite-docs");
+    let output_path = output_dir.join("tokens.md");
+    let output = output_path.to_str().unwrap();
+
+    std::fs::write(&output_path, "existing").unwrap();
+
+    // answering no quits without writing
+    run(&["docs", "-o", output], "n\n");
+
+    assert_eq!(std::fs::read_to_string(&output_path).unwrap(), "existing");
+
+    let written = run(&["docs", "-o", output, "-F"], "");
+
+    assert!(written.status.success());
+    assert_ne!(std::fs::read_to_string(&output_path).unwrap(), "existing");
+
+    std::fs::remove_dir_all(&output_dir).unwrap();
+}
+
+#[test]
+fn test_icon_png_policy() {
+    let output_dir = temp_dir("ti-tools-test-overwrite-icon-png");
+
+    // ret, 1, a blank 15x15 MirageOS icon, the description and the code
+    let mut code = vec![0xC9, 0x01];
+    code.extend([0x00; 30]);
+    code.extend(b"Icon\0\xC9");
+
+    let assembly = Assembly::compiled(code, &Model::TI84Plus).unwrap();
+    let program = Program::from_assembly(
+        "ICON",
+        ModelDetails::from_model(&Model::TI84Plus),
+        &assembly,
+    )
+    .unwrap();
+
+    let program_path = output_dir.join("ICON.8xp");
+    program
+        .save_to(&program_path, &OverwritePolicy::Force)
+        .unwrap();
+
+    let png_path = output_dir.join("ICON.png");
+    std::fs::write(&png_path, "existing").unwrap();
+
+    let args = [
+        "details",
+        program_path.to_str().unwrap(),
+        "--icon-png",
+        png_path.to_str().unwrap(),
+    ];
+
+    let refused = run(&[&args[..], &["-N"]].concat(), "");
+
+    assert!(!refused.status.success());
+    assert_eq!(std::fs::read_to_string(&png_path).unwrap(), "existing");
+
+    let written = run(&[&args[..], &["-F"]].concat(), "");
+
+    assert!(written.status.success());
+    assert!(std::fs::read(&png_path).unwrap().starts_with(b"\x89PNG"));
+
+    std::fs::remove_dir_all(&output_dir).unwrap();
+}
+
+#[test]
+fn test_conflicting_policies_fail() {
+    let output = run(&["convert", "./tests/programs/RADICAL.8xp", "-F", "-N"], "");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+}
+
+#[test]
+fn test_junit_report_policy() {
+    let directory = temp_dir("ti-tools-test-overwrite-junit");
+
+    std::fs::write(directory.join("ONE.txt"), program_txt("ONE", "Disp 1")).unwrap();
+    std::fs::write(
+        directory.join("ONE.test.json"),
+        r#"{ "program": "ONE.txt", "tests": [{ "name": "shows", "expect": { "output": ["1"] } }] }"#,
+    )
+    .unwrap();
+
+    let report_path = directory.join("results.xml");
+    std::fs::write(&report_path, "existing").unwrap();
+
+    let args = [
+        "test",
+        directory.to_str().unwrap(),
+        "--junit",
+        report_path.to_str().unwrap(),
+    ];
+
+    let refused = run(&[&args[..], &["-N"]].concat(), "");
+
+    assert_eq!(refused.status.code(), Some(1));
+    assert_eq!(std::fs::read_to_string(&report_path).unwrap(), "existing");
+
+    let written = run(&[&args[..], &["-F"]].concat(), "");
+
+    assert!(
+        written.status.success(),
+        "{}",
+        String::from_utf8_lossy(&written.stdout)
+    );
+    assert!(std::fs::read_to_string(&report_path)
+        .unwrap()
+        .contains("<testsuite"));
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
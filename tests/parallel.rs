@@ -0,0 +1,88 @@
+mod common;
+
+use common::{run_json, temp_dir};
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn test_parallel_conversion_keeps_order_and_continues_after_failure() {
+    let directory = temp_dir("ti-tools-test-parallel");
+    let input_dir = directory.join("in");
+    let output_dir = directory.join("out");
+    fs::create_dir_all(&input_dir).unwrap();
+
+    let programs = [
+        "BASECONV", "DBD", "FACTOR", "MATHTOOL", "POLCONIC", "RADICAL",
+    ];
+
+    for program in programs {
+        fs::copy(
+            Path::new("./tests/programs")
+                .join(program)
+                .with_extension("8xp"),
+            input_dir.join(program).with_extension("8xp"),
+        )
+        .unwrap();
+    }
+
+    // sorts between the working files, so it lands in the middle of the queue
+    fs::write(input_dir.join("CORRUPT.8xp"), "**TI83F*").unwrap();
+
+    let (status, report) = run_json(
+        &[
+            "convert",
+            input_dir.to_str().unwrap(),
+            "-m",
+            "-j",
+            "4",
+            "-o",
+            output_dir.to_str().unwrap(),
+            "-y",
+        ],
+        "",
+    );
+
+    assert!(!status.success(), "A failed file did not fail the run");
+
+    let results = report["results"].as_array().unwrap();
+    let inputs: Vec<String> = results
+        .iter()
+        .map(|result| {
+            let input = Path::new(result["input"].as_str().unwrap());
+            input.file_stem().unwrap().to_string_lossy().to_string()
+        })
+        .collect();
+
+    let mut expected: Vec<String> = programs.iter().map(|name| name.to_string()).collect();
+    expected.push("CORRUPT".to_string());
+    expected.sort();
+
+    assert_eq!(
+        inputs, expected,
+        "Results are not in the order of the inputs"
+    );
+
+    for result in results {
+        let expected_status = if result["input"].as_str().unwrap().ends_with("CORRUPT.8xp") {
+            "failed"
+        } else {
+            "converted"
+        };
+
+        assert_eq!(result["status"], expected_status, "{}", result);
+    }
+
+    for program in programs {
+        assert!(
+            output_dir.join(program).with_extension("txt").exists(),
+            "{} was not converted after the failure",
+            program
+        );
+    }
+
+    assert_eq!(report["summary"]["converted"], programs.len());
+    assert_eq!(report["summary"]["skipped"], 0);
+    assert_eq!(report["summary"]["failed"], 1);
+
+    fs::remove_dir_all(&directory).unwrap();
+}
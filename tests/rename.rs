@@ -1,3 +1,5 @@
+#![allow(clippy::nonminimal_bool)]
+
 use std::path::Path;
 use ti_tools::calculator::program::Program;
 use ti_tools::calculator::DisplayMode;
@@ -0,0 +1,56 @@
+mod common;
+
+use common::load_program;
+use ti_tools::calculator::file::decode_program;
+use ti_tools::calculator::render::{highlight, render_html, Highlight};
+use ti_tools::calculator::DisplayMode;
+
+#[test]
+fn test_highlight_categories() {
+    let program = load_program(
+        "HIGHLITE",
+        "\"COUNTS DOWN\nFor(I,10,1,~1\nDisp abs(I)\nEnd\nsqrt(A)->B",
+    );
+    let tokens = decode_program(&program, &DisplayMode::Accessible).unwrap();
+
+    let highlighted = tokens
+        .iter()
+        .zip(highlight(&tokens))
+        .filter(|(token, _)| token.bytes != [0x3F])
+        .map(|(token, highlight)| (token.text.as_str(), highlight))
+        .collect::<Vec<_>>();
+
+    assert_eq!(highlighted[0], ("\"", Highlight::Comment));
+    assert_eq!(highlighted[1], ("C", Highlight::Comment));
+
+    let first = |text: &str| {
+        highlighted
+            .iter()
+            .find(|(token, _)| *token == text)
+            .map(|(_, highlight)| *highlight)
+    };
+
+    assert_eq!(first("For("), Some(Highlight::Command));
+    assert_eq!(first("I"), Some(Highlight::Variable));
+    assert_eq!(first("1"), Some(Highlight::Number));
+    assert_eq!(first(","), Some(Highlight::Operator));
+    assert_eq!(first("Disp "), Some(Highlight::Command));
+    assert_eq!(first("abs("), Some(Highlight::Function));
+    assert_eq!(first("End"), Some(Highlight::Command));
+    // a statement storing a value starts with an expression
+    assert_eq!(first("sqrt("), Some(Highlight::Function));
+}
+
+#[test]
+fn test_render_html() {
+    let program = load_program("PAGE", "If A<B\nDisp abs(A)");
+    let tokens = decode_program(&program, &DisplayMode::Accessible).unwrap();
+
+    let html = render_html(&program, &tokens).unwrap();
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>PAGE</title>"));
+    assert!(html.contains("<span class=\"operator\" title=\"$6B"));
+    assert!(html.contains(">&lt;</span>"));
+    assert!(html.contains("<span class=\"function\" title=\"$B2\n\nabs(value)\n"));
+}
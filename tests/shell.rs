@@ -0,0 +1,137 @@
+mod common;
+
+use common::{run, temp_dir};
+use ti_tools::calculator::assembly::Assembly;
+use ti_tools::calculator::errors::TiToolsError;
+use ti_tools::calculator::models::{Model, ModelDetails};
+use ti_tools::calculator::program::{OverwritePolicy, Program};
+use ti_tools::calculator::shell::{Icon, ShellHeader, ShellKind};
+
+#[test]
+fn test_mirage_os_header() {
+    // ret, 1, a 15x15 box, the description and the code
+    let mut code = vec![0xC9, 0x01, 0xFF, 0xFE];
+    code.extend([0x80, 0x02].repeat(13));
+    code.extend([0xFF, 0xFE]);
+    code.extend(b"Hello World\0");
+    code.push(0xC9);
+
+    let assembly = Assembly::compiled(code, &Model::TI84Plus).unwrap();
+    let mut program = Program::from_assembly(
+        "HELLO",
+        ModelDetails::from_model(&Model::TI84Plus),
+        &assembly,
+    )
+    .unwrap();
+
+    let header = program.shell_header().unwrap();
+    let icon = header.icon.unwrap();
+
+    assert_eq!(header.kind, ShellKind::MirageOS);
+    assert_eq!(header.description.as_deref(), Some("Hello World"));
+    assert_eq!((icon.width, icon.height), (15, 15));
+    assert!(icon
+        .to_ascii()
+        .starts_with("###############\n#.............#\n"));
+    assert!(icon.to_png().unwrap().starts_with(b"\x89PNG"));
+
+    // the code after the description does not move
+    let length = program.body.bytes.len();
+    program.set_shell_description("Hi").unwrap();
+
+    assert_eq!(program.body.bytes.len(), length);
+    assert_eq!(
+        program.shell_header().unwrap().description.as_deref(),
+        Some("Hi")
+    );
+    // only Ion headers keep the room of a description that was shortened
+    assert!(matches!(
+        program.set_shell_description("Hello World"),
+        Err(TiToolsError::ShellDescriptionTooLong(2))
+    ));
+}
+
+#[test]
+fn test_doors_cs_basic_icon() {
+    // :DCS, then a quote and the 64 hex digits of a 16x16 icon
+    let mut body = b"\x3EDCS\x3F\x2A".to_vec();
+    body.extend("F00F".repeat(16).bytes());
+    body.extend(b"\x3FDisp 1");
+
+    let header = ShellHeader::detect(&body).unwrap();
+    let icon = header.icon.clone().unwrap();
+
+    assert_eq!(header.kind, ShellKind::DoorsCS);
+    assert_eq!(header.description, None);
+    assert_eq!(icon.to_ascii().lines().next(), Some("####........####"));
+
+    let drawn = Icon::from_ascii(&"#.".repeat(8).repeat(16), &icon);
+    assert!(matches!(drawn, Err(TiToolsError::InvalidIcon(_))));
+
+    let drawn = Icon::from_ascii(&"#.#.#.#.#.#.#.#.\n".repeat(16), &icon).unwrap();
+    header.write_icon(&mut body, &drawn).unwrap();
+
+    assert_eq!(&body[6..10], b"AAAA");
+    assert_eq!(ShellHeader::detect(&body).unwrap().icon, Some(drawn));
+}
+
+#[test]
+fn test_cesium_header_without_icon_pixels() {
+    // jp start, 1 for an icon, a 0x0 icon, the description and the code
+    let code = vec![
+        0xC3, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, b'h', b'i', 0x00, 0xC9,
+    ];
+
+    let assembly = Assembly::compiled(code, &Model::TI84PlusCE).unwrap();
+    let program = Program::from_assembly(
+        "HI",
+        ModelDetails::from_model(&Model::TI84PlusCE),
+        &assembly,
+    )
+    .unwrap();
+
+    let header = program.shell_header().unwrap();
+
+    assert_eq!(header.kind, ShellKind::Cesium);
+    assert_eq!(header.description.as_deref(), Some("hi"));
+    assert_eq!(header.icon, None);
+
+    let directory = temp_dir("ti-tools-test-shell-cesium");
+    let path = directory.join("HI.8xp");
+    program.save_to(&path, &OverwritePolicy::Force).unwrap();
+
+    let output = run(&["details", path.to_str().unwrap()], "");
+
+    assert!(
+        output.status.success(),
+        "details failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_ion_jump_past_the_program() {
+    // ret, jr nc past the end of the program, the description and the code
+    let code = vec![0xC9, 0x30, 0x7F, b'A', b'B', 0x00, 0xC9];
+
+    let assembly = Assembly::compiled(code, &Model::TI84Plus).unwrap();
+    let mut program =
+        Program::from_assembly("ION", ModelDetails::from_model(&Model::TI84Plus), &assembly)
+            .unwrap();
+
+    assert_eq!(
+        program.shell_header().unwrap().description.as_deref(),
+        Some("AB")
+    );
+    assert!(matches!(
+        program.set_shell_description("HELLO"),
+        Err(TiToolsError::ShellDescriptionTooLong(2))
+    ));
+
+    program.set_shell_description("HI").unwrap();
+
+    assert_eq!(program.body.bytes.len(), 9);
+    assert_eq!(program.body.bytes[7..], [0x00, 0xC9]);
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::load_program;
+use ti_tools::calculator::testing::{run_test, TestSuite};
+
+#[test]
+fn test_run_test_cases() {
+    let program = load_program(
+        "DOUBLE",
+        "Prompt A\nDisp 2A\n2L1->L2\nsub(\"HI!\",1,2)->Str1",
+    );
+
+    let suite = TestSuite::from_string(
+        r#"{
+            "program": "DOUBLE.txt",
+            "tests": [
+                {
+                    "name": "doubles",
+                    "inputs": ["4"],
+                    "variables": { "L1": [1, 2] },
+                    "expect": {
+                        "output": ["A=?4", "8"],
+                        "variables": { "A": 4, "L₂": [2, 4], "Str1": "HI" }
+                    }
+                },
+                {
+                    "name": "wrong",
+                    "inputs": ["1"],
+                    "expect": { "output": ["A=?1", "3"] }
+                },
+                {
+                    "name": "no answer",
+                    "expect": { "error": "INPUT" }
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let results = suite
+        .tests
+        .iter()
+        .map(|test| run_test(&program, test).unwrap())
+        .collect::<Vec<_>>();
+
+    assert!(results[0].passed, "{:?}", results[0].failures);
+
+    assert!(!results[1].passed);
+    assert_eq!(
+        results[1].failures,
+        vec![
+            "unexpected ERR:UNDEFINED: L₁ has no value",
+            "output line 2: expected \"3\", got \"2\"",
+        ]
+    );
+
+    assert!(results[2].passed, "{:?}", results[2].failures);
+    assert_eq!(results[2].error.as_ref().unwrap().line, 1);
+}
+
+#[test]
+fn test_test_suite_rejects_unknown_fields() {
+    let suite = TestSuite::from_string(
+        r#"{ "program": "A.txt", "tests": [{ "name": "typo", "expected": {} }] }"#,
+    );
+
+    assert!(suite.is_err());
+}
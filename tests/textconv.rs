@@ -0,0 +1,24 @@
+mod common;
+
+use common::{run, temp_dir};
+
+#[test]
+fn test_textconv_extensionless_file() {
+    let directory = temp_dir("ti-tools-test-textconv");
+
+    // git diff hands textconv temporary files without an extension
+    let path = directory.join("RADICAL");
+    std::fs::copy("./tests/programs/RADICAL.8xp", &path).unwrap();
+
+    let output = run(&["textconv", path.to_str().unwrap()], "");
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim_end(),
+        std::fs::read_to_string("./tests/programs/RADICAL.txt")
+            .unwrap()
+            .trim_end()
+    );
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
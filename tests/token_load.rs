@@ -1,4 +1,9 @@
-use ti_tools::calculator::tokens::{load_token_definitions, load_tokens, OsVersion};
+use ti_tools::calculator::models::Model;
+use ti_tools::calculator::tokens::search::{search_tokens, MatchKind};
+use ti_tools::calculator::tokens::{
+    check_definitions, load_token_categories, load_token_definitions, load_token_table,
+    load_tokens, OsVersion, TokenCategory,
+};
 
 #[test]
 fn test_load_token_definitions() {
@@ -7,9 +12,89 @@ fn test_load_token_definitions() {
     assert!(tokens.is_ok(), "Failed to load token definitions.");
 }
 
+#[test]
+fn test_every_token_has_a_definition() {
+    let table = load_token_table().unwrap();
+    let definitions = load_token_definitions().unwrap();
+
+    let coverage = check_definitions(&table, &definitions);
+
+    assert_eq!(coverage.undefined, Vec::<String>::new());
+    // the headers of compiled assembly programs are described without being tokens
+    assert_eq!(coverage.orphaned, vec!["0xBB6D", "0xEF69", "0xEF7B"]);
+
+    let rand_int = &definitions["0xBB0A"][0];
+    assert_eq!(rand_int.arguments.len(), 3);
+    assert!(rand_int.arguments[2].optional);
+    assert_eq!(rand_int.returns.as_deref(), Some("number"));
+    assert!(rand_int
+        .to_markdown()
+        .contains("`numtrials` (number, optional)"));
+}
+
 #[test]
 fn test_load_tokens() {
     let tokens = load_tokens(&OsVersion::latest());
 
     assert!(tokens.is_ok(), "Failed to load tokens.");
 }
+
+#[test]
+fn test_every_token_has_a_category() {
+    let categories = load_token_categories().unwrap();
+    let table = load_token_table().unwrap();
+
+    assert_eq!(categories.len(), table.len());
+
+    let category = |key: &str| {
+        table
+            .iter()
+            .find(|entry| entry.key == key)
+            .map(|entry| entry.category)
+    };
+
+    assert_eq!(category("$CE"), Some(TokenCategory::ControlFlow));
+    assert_eq!(category("$BB$0C"), Some(TokenCategory::Strings));
+    assert_eq!(category("$5D$00"), Some(TokenCategory::Lists));
+}
+
+#[test]
+fn test_token_versions_by_model() {
+    let table = load_token_table().unwrap();
+    let abs = table.iter().find(|entry| entry.bytes == [0xB2]).unwrap();
+
+    let spelling = |model: Model| {
+        let version = abs.version_for(&OsVersion::new(model, "latest".to_string()))?;
+        Some(version.langs["en"].accessible.to_string())
+    };
+
+    assert_eq!(spelling(Model::TI82), Some("abs ".to_string()));
+    assert_eq!(spelling(Model::TI84Plus), Some("abs(".to_string()));
+
+    let wait = table
+        .iter()
+        .find(|entry| entry.bytes == [0xEF, 0x96])
+        .unwrap();
+
+    assert!(wait
+        .version_for(&OsVersion::new(Model::TI84Plus, "latest".to_string()))
+        .is_none());
+}
+
+#[test]
+fn test_search_tokens_with_typos_and_descriptions() {
+    let tokens = load_tokens(&OsVersion::latest()).unwrap();
+    let definitions = load_token_definitions().unwrap();
+
+    let typo = search_tokens(&tokens, &definitions, "randitn", 5);
+    assert_eq!(typo[0].byte, "0xBB0A");
+    assert_eq!(typo[0].kind, MatchKind::Fuzzy);
+
+    let description = search_tokens(&tokens, &definitions, "absolute value", 5);
+    assert_eq!(description[0].byte, "0xB2");
+    assert_eq!(description[0].kind, MatchKind::Description);
+
+    let exact = search_tokens(&tokens, &definitions, "sin", 5);
+    assert_eq!(exact[0].byte, "0xC2");
+    assert_eq!(exact[0].score, 100);
+}
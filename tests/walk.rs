@@ -0,0 +1,153 @@
+mod common;
+
+use common::{run_json, temp_dir};
+use std::fs;
+use std::path::Path;
+
+fn converted_inputs(report: &serde_json::Value) -> Vec<String> {
+    report["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| {
+            let input = result["input"].as_str().unwrap();
+            Path::new(input)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect()
+}
+
+fn copy_program(name: &str, to: &Path) {
+    fs::create_dir_all(to.parent().unwrap()).unwrap();
+    fs::copy(Path::new("./tests/programs").join(name), to).unwrap();
+}
+
+#[test]
+fn test_recursive_conversion_mirrors_directories() {
+    let directory = temp_dir("ti-tools-test-walk-mirror");
+    let input_dir = directory.join("in");
+    let output_dir = directory.join("out");
+
+    copy_program("FACTOR.8xp", &input_dir.join("FACTOR.8xp"));
+    copy_program("RADICAL.8xp", &input_dir.join("sub/RADICAL.8xp"));
+    copy_program("DBD.8xp", &input_dir.join("skip/DBD.8xp"));
+
+    // a link back up the tree must not be walked forever
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("..", input_dir.join("sub/loop")).unwrap();
+
+    let (status, report) = run_json(
+        &[
+            "convert",
+            input_dir.to_str().unwrap(),
+            "-m",
+            "-r",
+            "--exclude",
+            "skip/**",
+            "-o",
+            output_dir.to_str().unwrap(),
+            "-y",
+        ],
+        "",
+    );
+
+    assert!(status.success(), "Mass conversion failed: {}", report);
+    assert_eq!(converted_inputs(&report), vec!["FACTOR.8xp", "RADICAL.8xp"]);
+    assert!(output_dir.join("FACTOR.txt").exists());
+    assert!(
+        output_dir.join("sub/RADICAL.txt").exists(),
+        "The subdirectory was not mirrored in the output"
+    );
+    assert!(!output_dir.join("skip").exists());
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_include_globs() {
+    let directory = temp_dir("ti-tools-test-walk-globs");
+    let input_dir = directory.join("in");
+
+    copy_program("FACTOR.8xp", &input_dir.join("FACTOR.8xp"));
+    copy_program("RADICAL.8xp", &input_dir.join("a/b/RADICAL.8xp"));
+    copy_program("DBD.8xp", &input_dir.join("a/DBD.8xp"));
+
+    let cases: [(&str, Vec<&str>); 4] = [
+        ("*.8xp", vec!["DBD.8xp", "FACTOR.8xp", "RADICAL.8xp"]),
+        ("a/*", vec!["DBD.8xp"]),
+        ("**/RAD*", vec!["RADICAL.8xp"]),
+        ("?BD.8xp", vec!["DBD.8xp"]),
+    ];
+
+    for (index, (pattern, expected)) in cases.into_iter().enumerate() {
+        let output_dir = directory.join(format!("out{}", index));
+
+        let (status, report) = run_json(
+            &[
+                "convert",
+                input_dir.to_str().unwrap(),
+                "-m",
+                "-r",
+                "--include",
+                pattern,
+                "-o",
+                output_dir.to_str().unwrap(),
+                "-y",
+            ],
+            "",
+        );
+
+        assert!(status.success(), "Mass conversion failed: {}", report);
+
+        let mut inputs = converted_inputs(&report);
+        inputs.sort();
+
+        assert_eq!(
+            inputs, expected,
+            "Pattern {} matched the wrong files",
+            pattern
+        );
+    }
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_output_collisions_are_refused() {
+    let directory = temp_dir("ti-tools-test-walk-collisions");
+    let input_dir = directory.join("in");
+    let output_dir = directory.join("out");
+
+    // both files hold the program RADICAL, so both would be written to RADICAL.txt
+    copy_program("RADICAL.8xp", &input_dir.join("ONE.8xp"));
+    copy_program("RADICAL.8xp", &input_dir.join("TWO.8xp"));
+
+    let (status, report) = run_json(
+        &[
+            "convert",
+            input_dir.to_str().unwrap(),
+            "-m",
+            "-o",
+            output_dir.to_str().unwrap(),
+            "-y",
+        ],
+        "",
+    );
+
+    assert!(!status.success(), "Colliding outputs were not refused");
+
+    let collisions = report["collisions"].as_array().unwrap();
+
+    assert_eq!(collisions.len(), 1);
+    assert!(collisions[0]["output"]
+        .as_str()
+        .unwrap()
+        .ends_with("RADICAL.txt"));
+    assert_eq!(collisions[0]["inputs"].as_array().unwrap().len(), 2);
+    assert!(!output_dir.join("RADICAL.txt").exists());
+
+    fs::remove_dir_all(&directory).unwrap();
+}
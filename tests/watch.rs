@@ -0,0 +1,130 @@
+mod common;
+
+use common::temp_dir;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, SystemTime};
+
+/// A running watch, its json reports arrive one per line
+struct Watch {
+    child: Child,
+    reports: Receiver<serde_json::Value>,
+}
+
+impl Watch {
+    fn start(args: &[&str]) -> Watch {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_ti-tools"))
+            .arg("watch")
+            .args(args)
+            .args(["--interval", "20", "--format", "json"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to start ti-tools");
+
+        let stdout = child.stdout.take().unwrap();
+        let (sender, reports) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let report = serde_json::from_str(&line.unwrap()).expect("Invalid json report");
+
+                if sender.send(report).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Watch { child, reports }
+    }
+
+    fn next_report(&self) -> serde_json::Value {
+        self.reports
+            .recv_timeout(Duration::from_secs(10))
+            .expect("Watch did not report a rebuild")
+    }
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn test_watch_rebuilds_changed_files() {
+    let directory = temp_dir("ti-tools-test-watch-rebuild");
+    let input_dir = directory.join("in");
+    let output_dir = directory.join("out");
+    fs::create_dir_all(&input_dir).unwrap();
+
+    let input_path = input_dir.join("RADICAL.txt");
+    fs::copy("./tests/programs/RADICAL.txt", &input_path).unwrap();
+
+    let watch = Watch::start(&[
+        input_dir.to_str().unwrap(),
+        "-o",
+        output_dir.to_str().unwrap(),
+        "--no-clobber",
+    ]);
+
+    let report = watch.next_report();
+    let output_path = output_dir.join("RADICAL.8xp");
+
+    assert_eq!(report["output"], output_path.display().to_string());
+    assert!(output_path.exists());
+
+    // a later change replaces the watch's own output, even with --no-clobber
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .open(&input_path)
+        .unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(5))
+        .unwrap();
+
+    let report = watch.next_report();
+
+    assert!(report["error"].is_null(), "Rebuild failed: {}", report);
+    assert_eq!(report["output"], output_path.display().to_string());
+
+    drop(watch);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_watch_respects_no_clobber() {
+    let directory = temp_dir("ti-tools-test-watch-no-clobber");
+    let input_dir = directory.join("in");
+    let output_dir = directory.join("out");
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::create_dir_all(&output_dir).unwrap();
+
+    fs::copy(
+        "./tests/programs/RADICAL.txt",
+        input_dir.join("RADICAL.txt"),
+    )
+    .unwrap();
+
+    let output_path = output_dir.join("RADICAL.8xp");
+    fs::write(&output_path, "existing").unwrap();
+
+    let watch = Watch::start(&[
+        input_dir.to_str().unwrap(),
+        "-o",
+        output_dir.to_str().unwrap(),
+        "--no-clobber",
+    ]);
+
+    let report = watch.next_report();
+
+    assert!(report["output"].is_null(), "Overwrote {:?}", output_path);
+    assert!(report["error"].as_str().unwrap().contains("already exists"));
+    assert_eq!(fs::read_to_string(&output_path).unwrap(), "existing");
+
+    drop(watch);
+    fs::remove_dir_all(&directory).unwrap();
+}
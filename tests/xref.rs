@@ -0,0 +1,78 @@
+mod common;
+
+use common::load_program;
+use ti_tools::calculator::xref::{xref_program, VariableKind};
+use ti_tools::calculator::DisplayMode;
+
+#[test]
+fn test_xref_labels_and_calls() {
+    let program = load_program(
+        "LABELS",
+        "Lbl A\nprgmHELPER\nGoto A\nMenu(\"M\",\"ONE\",B)\nIf 1:prgmHELPER",
+    );
+
+    let xref = xref_program(&program, &DisplayMode::Accessible).unwrap();
+
+    let labels = xref
+        .labels
+        .iter()
+        .map(|label| {
+            let references = label
+                .references
+                .iter()
+                .map(|reference| (reference.command.as_str(), reference.line))
+                .collect::<Vec<_>>();
+
+            (label.name.as_str(), label.defined.clone(), references)
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        labels,
+        vec![
+            ("A", vec![1], vec![("Goto", 3)]),
+            ("B", vec![], vec![("Menu(", 4)]),
+        ]
+    );
+
+    assert_eq!(xref.calls.len(), 1);
+    assert_eq!(xref.calls[0].name, "HELPER");
+    assert_eq!(xref.calls[0].lines, vec![2, 5]);
+}
+
+#[test]
+fn test_xref_variables() {
+    let program = load_program(
+        "VARS",
+        "Input \"X?\",N\nFor(I,1,N)\nIS>(C,5)\nEnd\nN+1->L1(1)\nsub(\"HI\",1,1)->Str1\nsmallLABC(2)->D\nDelVar E",
+    );
+
+    let xref = xref_program(&program, &DisplayMode::Accessible).unwrap();
+
+    let variables = xref
+        .variables
+        .iter()
+        .map(|variable| {
+            (
+                variable.name.as_str(),
+                variable.kind,
+                variable.reads.clone(),
+                variable.writes.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        variables,
+        vec![
+            ("C", VariableKind::Real, vec![3], vec![3]),
+            ("D", VariableKind::Real, vec![], vec![7]),
+            ("E", VariableKind::Real, vec![], vec![8]),
+            ("I", VariableKind::Real, vec![], vec![2]),
+            ("N", VariableKind::Real, vec![2, 5], vec![1]),
+            ("L1", VariableKind::List, vec![], vec![5]),
+            ("smallLABC", VariableKind::List, vec![7], vec![]),
+            ("Str1", VariableKind::String, vec![], vec![6]),
+        ]
+    );
+}